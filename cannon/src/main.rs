@@ -15,8 +15,8 @@ struct Projectile {
 }
 
 fn tick(environment: &Environment, projectile: &mut Projectile) {
-    projectile.position = projectile.position + projectile.velocity;
-    projectile.velocity = projectile.velocity + environment.gravity + environment.wind;
+    projectile.position += projectile.velocity;
+    projectile.velocity += environment.gravity + environment.wind;
 }
 
 fn print_projectile_position(projectile: &Projectile) {
@@ -56,7 +56,7 @@ fn main() -> std::io::Result<()> {
         write_pixel(
             &mut c,
             projectile.position.x as usize,
-            (canvas_height - projectile.position.y as i32) as usize,
+            projectile.position.y as usize,
             Color::new(0.5, 0.0, 0.5),
         );
         if projectile.position.y <= 0.0 {
@@ -66,6 +66,6 @@ fn main() -> std::io::Result<()> {
     }
 
     let mut file = File::create("rocket.ppm")?;
-    file.write_all(canvas_to_ppm(&c).as_bytes())?;
+    file.write_all(canvas_to_ppm(&c.flipped_vertically()).as_bytes())?;
     Ok(())
 }
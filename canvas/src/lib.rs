@@ -1,4 +1,6 @@
 use std::fmt::Write;
+#[cfg(feature = "png")]
+use std::{fs::File, io::BufWriter, path::Path};
 
 use colors::Color;
 
@@ -20,6 +22,48 @@ impl Canvas {
     pub fn pixel_at(&self, x: usize, y: usize) -> Option<&Color> {
         pixel_at(self, x, y)
     }
+
+    pub fn try_color_at(&self, x: i32, y: i32) -> Option<Color> {
+        if x < 0 || y < 0 {
+            return None;
+        }
+        self.pixel_at(x as usize, y as usize).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pixels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pixels.is_empty()
+    }
+
+    pub fn denoise_bilateral(&mut self, spatial_sigma: f64, range_sigma: f64) {
+        denoise_bilateral(self, spatial_sigma, range_sigma);
+    }
+
+    pub fn blit(&mut self, src: &Canvas, dest_x: i32, dest_y: i32) {
+        blit(self, src, dest_x, dest_y);
+    }
+
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        canvas_to_rgba8(self)
+    }
+
+    // The canvas origin is the top left corner; flipping lets y-up drawings
+    // be written with their own coordinates.
+    pub fn flipped_vertically(&self) -> Canvas {
+        let width = self.width.max(0) as usize;
+        let pixels = match width {
+            0 => vec![],
+            _ => self.pixels.chunks(width).rev().flatten().copied().collect(),
+        };
+        Canvas {
+            width: self.width,
+            height: self.height,
+            pixels,
+        }
+    }
 }
 
 pub fn canvas(width: i32, height: i32) -> Canvas {
@@ -30,61 +74,233 @@ pub fn canvas(width: i32, height: i32) -> Canvas {
     }
 }
 
+fn pixel_index(canvas: &Canvas, x: usize, y: usize) -> Option<usize> {
+    if x >= canvas.width as usize || y >= canvas.height as usize {
+        return None;
+    }
+    Some(x + y * canvas.width as usize)
+}
+
 pub fn write_pixel(canvas: &mut Canvas, x: usize, y: usize, color: Color) {
-    if let Some(color_in_canvas) = canvas.pixels.get_mut(x + y * canvas.width as usize) {
-        *color_in_canvas = color;
+    if let Some(index) = pixel_index(canvas, x, y) {
+        canvas.pixels[index] = color;
     }
 }
 
 pub fn pixel_at(canvas: &Canvas, x: usize, y: usize) -> Option<&Color> {
-    canvas.pixels.get(x + y * canvas.width as usize)
+    pixel_index(canvas, x, y).map(|index| &canvas.pixels[index])
+}
+
+pub fn blit(canvas: &mut Canvas, src: &Canvas, dest_x: i32, dest_y: i32) {
+    for y in 0..src.height {
+        for x in 0..src.width {
+            let (tx, ty) = (dest_x + x, dest_y + y);
+            if tx < 0 || ty < 0 {
+                continue;
+            }
+            let color = *pixel_at(src, x as usize, y as usize).unwrap();
+            write_pixel(canvas, tx as usize, ty as usize, color);
+        }
+    }
+}
+
+// Non-positive (or NaN) sigmas describe no blur at all, so the canvas is
+// left unchanged.
+pub fn denoise_bilateral(canvas: &mut Canvas, spatial_sigma: f64, range_sigma: f64) {
+    if !(spatial_sigma > 0.0 && range_sigma > 0.0) {
+        return;
+    }
+    let radius = (spatial_sigma * 2.0).ceil() as i32;
+    let spatial_factor = -1.0 / (2.0 * spatial_sigma * spatial_sigma);
+    let range_factor = -1.0 / (2.0 * range_sigma * range_sigma);
+    let mut pixels = Vec::with_capacity(canvas.pixels.len());
+
+    for y in 0..canvas.height {
+        for x in 0..canvas.width {
+            let center = *pixel_at(canvas, x as usize, y as usize).unwrap();
+            let mut sum = Color::new(0.0, 0.0, 0.0);
+            let mut total_weight = 0.0;
+            for ny in (y - radius).max(0)..=(y + radius).min(canvas.height - 1) {
+                for nx in (x - radius).max(0)..=(x + radius).min(canvas.width - 1) {
+                    let neighbour = *pixel_at(canvas, nx as usize, ny as usize).unwrap();
+                    let distance2 = ((nx - x).pow(2) + (ny - y).pow(2)) as f64;
+                    let difference = neighbour - center;
+                    let range2 =
+                        difference.red.powi(2) + difference.green.powi(2) + difference.blue.powi(2);
+                    let weight = (distance2 * spatial_factor + range2 * range_factor).exp();
+                    sum += neighbour * weight;
+                    total_weight += weight;
+                }
+            }
+            pixels.push(sum * (1.0 / total_weight));
+        }
+    }
+
+    canvas.pixels = pixels;
+}
+
+pub fn tonemap_reinhard(canvas: &Canvas) -> Canvas {
+    tonemap_reinhard_exposure(canvas, 1.0)
 }
 
-fn clamp(value: f64, min: f64, max: f64) -> f64 {
-    if value > max {
-        return max;
-    } else if value < min {
-        return min;
+pub fn tonemap_reinhard_exposure(canvas: &Canvas, exposure: f64) -> Canvas {
+    let map = |value: f64| {
+        let exposed = value * exposure;
+        exposed / (1.0 + exposed)
+    };
+    Canvas {
+        width: canvas.width,
+        height: canvas.height,
+        pixels: canvas
+            .pixels
+            .iter()
+            .map(|c| Color::new(map(c.red), map(c.green), map(c.blue)))
+            .collect(),
     }
+}
+
+// Expects a component already clamped to 0..1.
+fn encode_component(value: f64, gamma: f64) -> f64 {
+    encode_component_max(value, gamma, 255.0)
+}
 
-    value
+fn encode_component_max(value: f64, gamma: f64, maxval: f64) -> f64 {
+    (value.powf(1.0 / gamma) * maxval).round()
 }
 
 pub fn canvas_to_ppm(canvas: &Canvas) -> String {
-    let mut result: String = format!("P3\n{} {}\n255\n", canvas.width, canvas.height);
+    canvas_to_ppm_gamma(canvas, 1.0)
+}
+
+pub fn canvas_to_ppm_gamma(canvas: &Canvas, gamma: f64) -> String {
+    write_ppm(canvas, gamma, 255)
+}
+
+// The same plain PPM with 16 bits per channel, for intermediate images
+// that will be processed further.
+pub fn canvas_to_ppm_16(canvas: &Canvas) -> String {
+    write_ppm(canvas, 1.0, 65535)
+}
+
+fn write_ppm(canvas: &Canvas, gamma: f64, maxval: u16) -> String {
+    let mut result: String = format!("P3\n{} {}\n{}\n", canvas.width, canvas.height, maxval);
     for y in 0..canvas.height {
-        let mut row = vec![];
+        let mut line = String::new();
         for x in 0..canvas.width {
-            let color = pixel_at(&canvas, x as usize, y as usize).unwrap();
-            let red = (clamp(color.red, 0.0, 1.0) * 255.0).round();
-            let green = (clamp(color.green, 0.0, 1.0) * 255.0).round();
-            let blue = (clamp(color.blue, 0.0, 1.0) * 255.0).round();
-            row.push(format!("{}", red));
-            row.push(format!("{}", green));
-            row.push(format!("{}", blue));
-        }
-        let mut row_as_string = String::from("");
-        for color_component in row {
-            if row_as_string.len() + color_component.len() + 1 > 70 {
-                writeln!(&mut result, "{}", row_as_string).unwrap();
-                row_as_string = String::from("");
+            let color = pixel_at(canvas, x as usize, y as usize).unwrap().clamp();
+            for component in &[color.red, color.green, color.blue] {
+                let token = format!("{}", encode_component_max(*component, gamma, maxval as f64));
+                if !line.is_empty() && line.len() + 1 + token.len() > 70 {
+                    writeln!(&mut result, "{}", line).unwrap();
+                    line.clear();
+                }
+                if !line.is_empty() {
+                    line.push(' ');
+                }
+                line.push_str(&token);
             }
-            let mut row_as_vec = row_as_string
-                .split(" ")
-                .filter(|&str| str != "")
-                .collect::<Vec<_>>();
-            row_as_vec.push(&color_component);
-            row_as_string = row_as_vec.join(" ");
         }
-        writeln!(&mut result, "{}", row_as_string).unwrap();
+        writeln!(&mut result, "{}", line).unwrap();
     }
     result
 }
 
+// Row-major RGBA with opaque alpha, the layout of a browser ImageData or
+// an 8-bit texture upload.
+pub fn canvas_to_rgba8(canvas: &Canvas) -> Vec<u8> {
+    let mut data = Vec::with_capacity(canvas.pixels.len() * 4);
+    for color in canvas.pixels.iter().map(Color::clamp) {
+        data.push(encode_component(color.red, 1.0) as u8);
+        data.push(encode_component(color.green, 1.0) as u8);
+        data.push(encode_component(color.blue, 1.0) as u8);
+        data.push(255);
+    }
+    data
+}
+
+pub fn ppm_to_canvas(data: &str) -> Result<Canvas, String> {
+    let mut tokens = data
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .flat_map(|line| line.split_whitespace());
+
+    match tokens.next() {
+        Some("P3") => {}
+        Some(magic) => return Err(format!("Invalid magic number: {}", magic)),
+        None => return Err(String::from("Missing magic number")),
+    }
+
+    let mut next_number = |name: &str| -> Result<i32, String> {
+        let token = tokens.next().ok_or_else(|| format!("Missing {}", name))?;
+        token
+            .parse::<i32>()
+            .map_err(|_| format!("Invalid {}: {}", name, token))
+    };
+
+    let width = next_number("width")?;
+    let height = next_number("height")?;
+    let max_value = next_number("max value")?;
+    if width <= 0 || height <= 0 {
+        return Err(format!("Invalid dimensions: {}x{}", width, height));
+    }
+    if !(1..=65535).contains(&max_value) {
+        return Err(format!("Invalid max value: {}", max_value));
+    }
+    let max_value = max_value as f64;
+
+    let mut canvas = canvas(width, height);
+    for pixel in canvas.pixels.iter_mut() {
+        let red = next_number("red sample")? as f64;
+        let green = next_number("green sample")? as f64;
+        let blue = next_number("blue sample")? as f64;
+        *pixel = Color::new(red / max_value, green / max_value, blue / max_value);
+    }
+    Ok(canvas)
+}
+
+#[cfg(feature = "png")]
+pub fn canvas_to_png(canvas: &Canvas, path: &Path) -> std::io::Result<()> {
+    canvas_to_png_gamma(canvas, path, 1.0)
+}
+
+#[cfg(feature = "png")]
+pub fn canvas_to_png_gamma(canvas: &Canvas, path: &Path, gamma: f64) -> std::io::Result<()> {
+    let file = File::create(path)?;
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(file),
+        canvas.width as u32,
+        canvas.height as u32,
+    );
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    let mut data = Vec::with_capacity(canvas.pixels.len() * 3);
+    for color in canvas.pixels.iter().map(Color::clamp) {
+        data.push(encode_component(color.red, gamma) as u8);
+        data.push(encode_component(color.green, gamma) as u8);
+        data.push(encode_component(color.blue, gamma) as u8);
+    }
+    writer.write_image_data(&data)?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::*;
 
+    #[test]
+    fn converting_a_canvas_to_rgba_bytes() {
+        let mut c = canvas(2, 2);
+        c.write_pixel(0, 0, Color::new(1.5, 0.0, 0.0));
+        c.write_pixel(1, 0, Color::new(0.0, 0.5, -0.5));
+        c.write_pixel(0, 1, Color::new(0.0, 0.0, 1.0));
+        c.write_pixel(1, 1, Color::new(1.0, 1.0, 1.0));
+        assert_eq!(
+            c.to_rgba8(),
+            vec![255, 0, 0, 255, 0, 128, 0, 255, 0, 0, 255, 255, 255, 255, 255, 255]
+        );
+    }
+
     #[test]
     fn creating_a_canvas() {
         let c = canvas(10, 20);
@@ -104,6 +320,17 @@ mod tests {
         assert_eq!(pixel_at(&c, 2, 3).unwrap(), &red);
     }
 
+    #[test]
+    fn writing_a_16_bit_ppm() {
+        let mut c = canvas(3, 1);
+        c.write_pixel(0, 0, Color::new(1.0, 0.5, 0.0));
+        c.write_pixel(1, 0, Color::new(2.0, -1.0, 0.25));
+        let ppm = canvas_to_ppm_16(&c);
+        let lines = ppm.lines().collect::<Vec<_>>();
+        assert_eq!(lines[..3], ["P3", "3 1", "65535"]);
+        assert_eq!(lines[3], "65535 32768 0 65535 0 16384 0 0 0");
+    }
+
     #[test]
     fn constructing_the_ppm_header() {
         let c = canvas(5, 3);
@@ -141,6 +368,104 @@ mod tests {
         )
     }
 
+    #[test]
+    fn reading_pixels_inside_and_outside_the_canvas() {
+        let mut c = canvas(4, 3);
+        let red = Color::new(1.0, 0.0, 0.0);
+        write_pixel(&mut c, 3, 2, red);
+        assert_eq!(c.len(), 12);
+        assert_eq!(c.try_color_at(3, 2), Some(red));
+        assert_eq!(c.pixels[3 + 2 * 4], red);
+        assert_eq!(c.try_color_at(0, 0), Some(Color::new(0.0, 0.0, 0.0)));
+        assert_eq!(c.try_color_at(4, 0), None);
+        assert_eq!(c.try_color_at(0, 3), None);
+        assert_eq!(c.try_color_at(-1, 0), None);
+        assert_eq!(c.try_color_at(0, -1), None);
+    }
+
+    #[test]
+    fn writing_past_the_end_of_a_row_does_not_wrap() {
+        let mut c = canvas(4, 3);
+        write_pixel(&mut c, 4, 0, Color::new(1.0, 1.0, 1.0));
+        assert_eq!(c.pixel_at(0, 1), Some(&Color::new(0.0, 0.0, 0.0)));
+        assert_eq!(c.pixel_at(4, 0), None);
+    }
+
+    #[test]
+    fn flipping_a_canvas_vertically() {
+        let mut c = canvas(3, 2);
+        let red = Color::new(1.0, 0.0, 0.0);
+        write_pixel(&mut c, 2, 0, red);
+        let flipped = c.flipped_vertically();
+        assert_eq!(flipped.pixel_at(2, 1), Some(&red));
+        assert_eq!(flipped.pixel_at(2, 0), Some(&Color::new(0.0, 0.0, 0.0)));
+        assert_eq!(flipped.flipped_vertically().pixels, c.pixels);
+    }
+
+    #[test]
+    fn blitting_a_canvas_clips_to_the_destination() {
+        let mut c = canvas(4, 3);
+        let mut src = canvas(2, 2);
+        let red = Color::new(1.0, 0.0, 0.0);
+        for pixel in src.pixels.iter_mut() {
+            *pixel = red;
+        }
+        c.blit(&src, 3, 2);
+        assert_eq!(c.pixel_at(3, 2), Some(&red));
+        assert_eq!(c.pixel_at(2, 2), Some(&Color::new(0.0, 0.0, 0.0)));
+        assert_eq!(c.pixel_at(0, 0), Some(&Color::new(0.0, 0.0, 0.0)));
+        c.blit(&src, -1, -1);
+        assert_eq!(c.pixel_at(0, 0), Some(&red));
+        assert_eq!(c.pixel_at(1, 0), Some(&Color::new(0.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn reinhard_tonemapping_compresses_bright_pixels() {
+        let mut c = canvas(2, 1);
+        write_pixel(&mut c, 0, 0, Color::new(4.0, 4.0, 4.0));
+        write_pixel(&mut c, 1, 0, Color::new(0.0, 1.0, 0.25));
+        let mapped = tonemap_reinhard(&c);
+        assert_eq!(mapped.pixel_at(0, 0), Some(&Color::new(0.8, 0.8, 0.8)));
+        assert_eq!(mapped.pixel_at(1, 0), Some(&Color::new(0.0, 0.5, 0.2)));
+    }
+
+    #[test]
+    fn exposure_scales_colors_before_tonemapping() {
+        let mut c = canvas(1, 1);
+        write_pixel(&mut c, 0, 0, Color::new(2.0, 0.5, 1.0));
+        let mapped = tonemap_reinhard_exposure(&c, 2.0);
+        assert_eq!(
+            mapped.pixel_at(0, 0),
+            Some(&Color::new(0.8, 0.5, 2.0 / 3.0))
+        );
+    }
+
+    #[test]
+    fn gamma_correction_brightens_mid_gray() {
+        let mut c = canvas(1, 1);
+        write_pixel(&mut c, 0, 0, Color::new(0.5, 0.0, 1.0));
+        let ppm = canvas_to_ppm_gamma(&c, 2.2);
+        assert_eq!(ppm.split_terminator("\n").nth(3), Some("186 0 255"));
+    }
+
+    #[test]
+    fn gamma_of_one_keeps_the_linear_mapping() {
+        let mut c = canvas(16, 4);
+        for (i, pixel) in c.pixels.iter_mut().enumerate() {
+            let v = i as f64 / 63.0;
+            *pixel = Color::new(v, 1.0 - v, v * v);
+        }
+        let tokens = c
+            .pixels
+            .iter()
+            .flat_map(|p| vec![p.red, p.green, p.blue])
+            .map(|v| format!("{}", (v.clamp(0.0, 1.0) * 255.0).round()))
+            .collect::<Vec<_>>();
+        let ppm = canvas_to_ppm_gamma(&c, 1.0);
+        assert_eq!(ppm, canvas_to_ppm(&c));
+        assert_eq!(ppm.split_whitespace().skip(4).collect::<Vec<_>>(), tokens);
+    }
+
     #[test]
     fn splitting_long_lines_in_ppm_files() {
         let mut c = canvas(10, 2);
@@ -161,10 +486,213 @@ mod tests {
         )
     }
 
+    #[test]
+    fn ppm_lines_may_be_exactly_seventy_characters_long() {
+        let mut c = canvas(7, 1);
+        for pixel in c.pixels.iter_mut() {
+            *pixel = Color::new(1.0, 1.0, 1.0);
+        }
+        c.pixels[5] = Color::new(1.0, 1.0, 0.2);
+        let ppm = canvas_to_ppm(&c);
+        let lines = ppm.split_terminator("\n").skip(3).collect::<Vec<&str>>();
+        assert_eq!(lines[0].len(), 70);
+        assert!(lines[0].ends_with(" 255 51"));
+        assert_eq!(lines[1], "255 255 255");
+        assert_eq!(lines.len(), 2);
+    }
+
     #[test]
     fn ppm_files_are_terminated_by_a_newline_character() {
         let c = canvas(5, 3);
         let ppm = canvas_to_ppm(&c);
         assert_eq!(ppm.chars().last().unwrap(), '\n');
     }
+
+    #[test]
+    fn denoising_with_a_zero_sigma_leaves_the_canvas_unchanged() {
+        let mut c = canvas(3, 3);
+        c.write_pixel(1, 1, Color::new(1.0, 0.5, 0.0));
+        let original = c.pixels.clone();
+        c.denoise_bilateral(0.0, 0.1);
+        assert_eq!(c.pixels, original);
+        c.denoise_bilateral(1.0, 0.0);
+        assert_eq!(c.pixels, original);
+        c.denoise_bilateral(-1.0, -1.0);
+        assert_eq!(c.pixels, original);
+    }
+
+    #[test]
+    fn denoising_a_uniform_canvas_leaves_it_unchanged() {
+        let mut c = canvas(6, 4);
+        for pixel in c.pixels.iter_mut() {
+            *pixel = Color::new(0.2, 0.4, 0.6);
+        }
+        c.denoise_bilateral(1.5, 0.1);
+        for pixel in c.pixels.iter() {
+            assert_eq!(pixel, &Color::new(0.2, 0.4, 0.6));
+        }
+    }
+
+    #[test]
+    fn denoising_reduces_noise_and_preserves_edges() {
+        let mut c = canvas(10, 4);
+        for y in 0..4 {
+            for x in 0..10 {
+                let color = if x < 5 {
+                    let noise = if (x + y) % 2 == 0 { 0.05 } else { -0.05 };
+                    Color::new(0.5 + noise, 0.5 + noise, 0.5 + noise)
+                } else {
+                    Color::new(1.0, 1.0, 1.0)
+                };
+                write_pixel(&mut c, x, y, color);
+            }
+        }
+        let variance = |c: &Canvas| {
+            let values = (0..4)
+                .flat_map(|y| (0..5).map(move |x| (x, y)))
+                .map(|(x, y)| pixel_at(c, x, y).unwrap().red)
+                .collect::<Vec<_>>();
+            let mean = values.iter().sum::<f64>() / values.len() as f64;
+            values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64
+        };
+        let before = variance(&c);
+
+        c.denoise_bilateral(1.0, 0.1);
+
+        assert!(variance(&c) < before);
+        assert!((pixel_at(&c, 4, 1).unwrap().red - 0.5).abs() < 0.05);
+        assert_eq!(pixel_at(&c, 5, 1).unwrap(), &Color::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn reading_a_file_with_a_malformed_header() {
+        for header in &["0 1\n255", "1 -2\n255", "1 1\n0", "1 1\n65536"] {
+            let ppm = format!("P3\n{}\n0 0 0\n", header);
+            assert!(ppm_to_canvas(&ppm).is_err(), "accepted {:?}", header);
+        }
+        assert!(ppm_to_canvas("P3\n1 1\n65535\n0 0 0\n").is_ok());
+    }
+
+    #[test]
+    fn reading_a_file_with_the_wrong_magic_number() {
+        let ppm = r#"P32
+1 1
+255
+0 0 0
+"#;
+        assert!(ppm_to_canvas(ppm).is_err());
+    }
+
+    #[test]
+    fn reading_a_ppm_returns_a_canvas_of_the_right_size() {
+        let ppm = r#"P3
+10 2
+255
+0 0 0  0 0 0  0 0 0  0 0 0  0 0 0
+0 0 0  0 0 0  0 0 0  0 0 0  0 0 0
+0 0 0  0 0 0  0 0 0  0 0 0  0 0 0
+0 0 0  0 0 0  0 0 0  0 0 0  0 0 0
+"#;
+        let c = ppm_to_canvas(ppm).unwrap();
+        assert_eq!(c.width, 10);
+        assert_eq!(c.height, 2);
+    }
+
+    #[test]
+    fn reading_pixel_data_from_a_ppm_file() {
+        let ppm = r#"P3
+4 3
+255
+255 127 0  0 127 255  127 255 0  255 255 255
+0 0 0  255 0 0  0 255 0  0 0 255
+255 255 0  0 255 255  255 0 255  127 127 127
+"#;
+        let c = ppm_to_canvas(ppm).unwrap();
+        assert_eq!(pixel_at(&c, 0, 0).unwrap(), &Color::new(1.0, 0.49804, 0.0));
+        assert_eq!(pixel_at(&c, 1, 0).unwrap(), &Color::new(0.0, 0.49804, 1.0));
+        assert_eq!(pixel_at(&c, 3, 1).unwrap(), &Color::new(0.0, 0.0, 1.0));
+        assert_eq!(
+            pixel_at(&c, 3, 2).unwrap(),
+            &Color::new(0.49804, 0.49804, 0.49804)
+        );
+    }
+
+    #[test]
+    fn ppm_parsing_ignores_comment_lines() {
+        let ppm = r#"P3
+# this is a comment
+2 1
+# this, too
+255
+# another comment
+255 255 255
+# oh, no, comments in the pixel data!
+255 0 255
+"#;
+        let c = ppm_to_canvas(ppm).unwrap();
+        assert_eq!(pixel_at(&c, 0, 0).unwrap(), &Color::new(1.0, 1.0, 1.0));
+        assert_eq!(pixel_at(&c, 1, 0).unwrap(), &Color::new(1.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn ppm_parsing_allows_an_rgb_triple_to_span_lines() {
+        let ppm = r#"P3
+1 1
+255
+51
+153
+
+204
+"#;
+        let c = ppm_to_canvas(ppm).unwrap();
+        assert_eq!(pixel_at(&c, 0, 0).unwrap(), &Color::new(0.2, 0.6, 0.8));
+    }
+
+    #[test]
+    fn ppm_parsing_respects_the_scale_setting() {
+        let ppm = r#"P3
+2 2
+100
+100 100 100  50 50 50
+75 50 25  0 0 0
+"#;
+        let c = ppm_to_canvas(ppm).unwrap();
+        assert_eq!(pixel_at(&c, 0, 1).unwrap(), &Color::new(0.75, 0.5, 0.25));
+    }
+
+    #[test]
+    fn reading_a_truncated_pixel_stream() {
+        let ppm = r#"P3
+2 1
+255
+255 255 255
+255 0
+"#;
+        assert!(ppm_to_canvas(ppm).is_err());
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn writing_a_canvas_to_a_png_file() {
+        let mut c = canvas(3, 3);
+        for y in 0..3 {
+            for x in 0..3 {
+                let color = Color::new(x as f64 / 2.0, y as f64 / 2.0, 1.5);
+                write_pixel(&mut c, x, y, color);
+            }
+        }
+        let path = std::env::temp_dir().join("canvas_to_png_gradient.png");
+        canvas_to_png(&c, &path).unwrap();
+
+        let decoder = png::Decoder::new(std::fs::File::open(&path).unwrap());
+        let mut reader = decoder.read_info().unwrap();
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!((info.width, info.height), (3, 3));
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+        assert_eq!(&buf[0..3], &[0, 0, 255]);
+        assert_eq!(&buf[(1 + 3) * 3..(1 + 3) * 3 + 3], &[128, 128, 255]);
+        assert_eq!(&buf[(2 + 2 * 3) * 3..(2 + 2 * 3) * 3 + 3], &[255, 255, 255]);
+    }
 }
@@ -25,12 +25,12 @@ fn main() -> std::io::Result<()> {
         write_pixel(
             &mut c,
             point.x as usize,
-            (canvas_size - point.y as i32) as usize,
+            point.y as usize,
             Color::new(1.0, 1.0, 1.0),
         );
     }
 
     let mut file = File::create("clock.ppm")?;
-    file.write_all(canvas_to_ppm(&c).as_bytes())?;
+    file.write_all(canvas_to_ppm(&c.flipped_vertically()).as_bytes())?;
     Ok(())
 }
@@ -1,6 +1,9 @@
 use float_cmp::ApproxEq;
 
+pub const EPSILON: f64 = 0.00001;
+
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Color {
     pub red: f64,
     pub green: f64,
@@ -11,13 +14,82 @@ impl Color {
     pub fn new(red: f64, green: f64, blue: f64) -> Color {
         Color { red, green, blue }
     }
+
+    pub fn is_black(&self, epsilon: f64) -> bool {
+        self.red.abs() < epsilon && self.green.abs() < epsilon && self.blue.abs() < epsilon
+    }
+
+    pub fn approx_eq(&self, other: &Color, epsilon: f64) -> bool {
+        (self.red - other.red).abs() <= epsilon
+            && (self.green - other.green).abs() <= epsilon
+            && (self.blue - other.blue).abs() <= epsilon
+    }
+
+    pub fn from_hex(hex: &str) -> Result<Color, String> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("Invalid hex color: {}", hex));
+        }
+        let channel = |i: usize, width: usize| {
+            let value = u8::from_str_radix(&digits[i * width..(i + 1) * width], 16).unwrap();
+            let value = if width == 1 { value * 17 } else { value };
+            value as f64 / 255.0
+        };
+        match digits.len() {
+            3 => Ok(Color::new(channel(0, 1), channel(1, 1), channel(2, 1))),
+            6 => Ok(Color::new(channel(0, 2), channel(1, 2), channel(2, 2))),
+            _ => Err(format!("Invalid hex color length: {}", hex)),
+        }
+    }
+
+    pub fn to_hex(&self) -> String {
+        let color = self.clamp();
+        let channel = |value: f64| (value * 255.0).round() as u8;
+        format!(
+            "#{:02X}{:02X}{:02X}",
+            channel(color.red),
+            channel(color.green),
+            channel(color.blue)
+        )
+    }
+
+    pub fn clamp(&self) -> Color {
+        Color::new(
+            self.red.clamp(0.0, 1.0),
+            self.green.clamp(0.0, 1.0),
+            self.blue.clamp(0.0, 1.0),
+        )
+    }
+
+    pub fn lerp(&self, other: &Color, t: f64) -> Color {
+        *self * (1.0 - t) + *other * t
+    }
+
+    // Alpha-over compositing of this color on top of background.
+    pub fn over(&self, background: &Color, alpha: f64) -> Color {
+        background.lerp(self, alpha)
+    }
+
+    pub fn luminance(&self) -> f64 {
+        0.2126 * self.red + 0.7152 * self.green + 0.0722 * self.blue
+    }
+}
+
+pub fn approx_eq_color(a: &Color, b: &Color, epsilon: f64) -> bool {
+    a.approx_eq(b, epsilon)
+}
+
+impl From<f64> for Color {
+    fn from(value: f64) -> Color {
+        Color::new(value, value, value)
+    }
 }
 
 impl PartialEq for Color {
     fn eq(&self, other: &Self) -> bool {
-        self.red.approx_eq(other.red, (0.0001, 2))
-            && self.green.approx_eq(other.green, (0.0001, 2))
-            && self.blue.approx_eq(other.blue, (0.0001, 2))
+        self.red.approx_eq(other.red, (EPSILON, 2))
+            && self.green.approx_eq(other.green, (EPSILON, 2))
+            && self.blue.approx_eq(other.blue, (EPSILON, 2))
     }
 }
 
@@ -33,6 +105,18 @@ impl std::ops::Add for Color {
     }
 }
 
+impl std::ops::AddAssign for Color {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl std::ops::MulAssign<f64> for Color {
+    fn mul_assign(&mut self, other: f64) {
+        *self = *self * other;
+    }
+}
+
 impl std::ops::Sub for Color {
     type Output = Self;
 
@@ -73,6 +157,95 @@ impl std::ops::Mul for Color {
 mod tests {
     use crate::*;
 
+    #[test]
+    fn colors_are_equal_within_epsilon() {
+        assert_eq!(
+            Color::new(0.38066, 0.47583, 0.2855),
+            Color::new(0.380661, 0.475826, 0.28550)
+        );
+        assert_ne!(
+            Color::new(0.38066, 0.47583, 0.2855),
+            Color::new(0.38068, 0.47583, 0.2855)
+        );
+    }
+
+    #[test]
+    fn converting_colors_to_and_from_hex() {
+        assert_eq!(Color::from_hex("#FFFFFF"), Ok(Color::new(1.0, 1.0, 1.0)));
+        assert_eq!(Color::from_hex("000"), Ok(Color::new(0.0, 0.0, 0.0)));
+        assert_eq!(Color::from_hex("#fff"), Ok(Color::new(1.0, 1.0, 1.0)));
+        assert_eq!(Color::new(1.0, 1.0, 1.0).to_hex(), "#FFFFFF");
+        assert_eq!(Color::new(0.0, 0.0, 0.0).to_hex(), "#000000");
+        let mid = Color::from_hex("#7180B9").unwrap();
+        assert_eq!(mid, Color::new(113.0 / 255.0, 128.0 / 255.0, 185.0 / 255.0));
+        assert_eq!(mid.to_hex(), "#7180B9");
+        assert_eq!(Color::new(1.5, -0.5, 0.5).to_hex(), "#FF0080");
+    }
+
+    #[test]
+    fn rejecting_invalid_hex_colors() {
+        assert!(Color::from_hex("#xyz").is_err());
+        assert!(Color::from_hex("#12345").is_err());
+        assert!(Color::from_hex("").is_err());
+    }
+
+    #[test]
+    fn comparing_colors_with_a_tolerance() {
+        let a = Color::new(0.5, 0.25, 1.0);
+        let b = Color::new(0.500001, 0.25, 1.0);
+        assert!(a.approx_eq(&b, 1e-5));
+        assert!(!a.approx_eq(&b, 1e-7));
+        assert!(approx_eq_color(&a, &b, 1e-5));
+    }
+
+    #[test]
+    fn accumulating_colors() {
+        let mut c = Color::new(0.1, 0.2, 0.3);
+        c += Color::new(0.4, 0.5, 0.6);
+        assert_eq!(c, Color::new(0.5, 0.7, 0.9));
+    }
+
+    #[test]
+    fn clamping_an_over_range_color() {
+        let c = Color::new(1.5, -0.2, 0.5);
+        assert_eq!(c.clamp(), Color::new(1.0, 0.0, 0.5));
+    }
+
+    #[test]
+    fn interpolating_between_colors() {
+        let a = Color::new(0.0, 0.5, 1.0);
+        let b = Color::new(1.0, 0.5, 0.0);
+        assert_eq!(a.lerp(&b, 0.0), a);
+        assert_eq!(a.lerp(&b, 0.5), Color::new(0.5, 0.5, 0.5));
+        assert_eq!(a.lerp(&b, 1.0), b);
+    }
+
+    #[test]
+    fn compositing_a_color_over_a_background() {
+        let foreground = Color::new(1.0, 0.0, 0.0);
+        let background = Color::new(0.0, 0.0, 1.0);
+        assert_eq!(foreground.over(&background, 0.0), background);
+        assert_eq!(foreground.over(&background, 1.0), foreground);
+        assert_eq!(
+            foreground.over(&background, 0.25),
+            Color::new(0.25, 0.0, 0.75)
+        );
+    }
+
+    #[test]
+    fn luminance_weights_the_channels() {
+        assert!((Color::new(0.0, 1.0, 0.0).luminance() - 0.7152).abs() < EPSILON);
+        assert!((Color::new(1.0, 1.0, 1.0).luminance() - 1.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn scaling_a_color_in_place() {
+        let mut c = Color::new(0.2, 0.3, 0.4);
+        let expected = c * 2.0;
+        c *= 2.0;
+        assert_eq!(c, expected);
+    }
+
     #[test]
     fn colors_are_tuples() {
         let c = Color::new(-0.5, 0.4, 1.7);
@@ -107,4 +280,15 @@ mod tests {
         let c2 = Color::new(0.9, 1.0, 0.1);
         assert_eq!(c1 * c2, Color::new(0.9, 0.2, 0.04));
     }
+
+    #[test]
+    fn a_scalar_converts_to_a_grey_color() {
+        assert_eq!(Color::from(0.5), Color::new(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn a_nearly_black_color_is_black() {
+        assert!(Color::new(1e-9, 0.0, -1e-9).is_black(0.0001));
+        assert!(!Color::new(0.0, 0.0, 0.01).is_black(0.0001));
+    }
 }
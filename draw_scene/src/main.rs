@@ -5,16 +5,18 @@ use lights::PointLight;
 use matrices::IDENTITY;
 use std::fs::File;
 use std::io::prelude::*;
-use std::rc::Rc;
-use transformations::{view_transform, MatrixTransformations};
-use tuples::{point, vector};
+use transformations::MatrixTransformations;
+use tuples::point;
 use world::{
     camera::Camera,
     materials::Material,
     object::Object,
     patterns::{
-        checkers::CheckersPatternShape, gradient::GradientPatternShape, ring::RingPatternShape,
-        stripes::StripesPatternShape, Pattern,
+        checkers::CheckersPatternShape,
+        gradient::GradientPatternShape,
+        ring::RingPatternShape,
+        stripes::{Axis, StripesPatternShape},
+        Pattern,
     },
     shapes::{planes::Plane, spheres::Sphere},
     World,
@@ -23,70 +25,82 @@ use world::{
 fn main() -> std::io::Result<()> {
     let canvas_width = 600;
     let canvas_height = 300;
-    let mut floor = Object::new(Box::new(Plane::default()));
-    let mut floor_material = Material::default();
-    floor_material.color = Color::new(1.0, 0.9, 0.9);
-    floor_material.specular = 0.0;
-    floor_material.pattern = Some(Pattern::new(Box::new(RingPatternShape {
-        a: Color::new(0.8, 0.8, 0.8),
-        b: Color::new(0.5, 0.5, 0.5),
-    })));
-    floor_material.reflective = 0.5;
+    let mut floor_material = Material {
+        color: Color::new(1.0, 0.9, 0.9),
+        specular: 0.0,
+        pattern: Some(Pattern::new(Box::new(RingPatternShape {
+            a: Color::new(0.8, 0.8, 0.8),
+            b: Color::new(0.5, 0.5, 0.5),
+        }))),
+        reflective: Color::from(0.5),
+        ..Material::default()
+    };
     floor_material.pattern.as_mut().unwrap().transform = IDENTITY.scale(0.5, 0.5, 0.5);
-    floor.transform = IDENTITY.scale(10.0, 1.0, 10.0);
-    floor.material = Rc::new(floor_material);
-    let mut middle = Object::new(Box::new(Sphere::default()));
-    let mut middle_material = Material::default();
-    middle_material.color = Color::new(0.1, 1.0, 0.5);
-    middle_material.diffuse = 0.7;
-    middle_material.specular = 0.3;
-    middle_material.pattern = Some(Pattern::new(Box::new(GradientPatternShape {
-        a: Color::new(1.0, 0.0, 0.0),
-        b: Color::new(0.0, 1.0, 0.0),
-    })));
+    let floor = Object::new(Box::new(Plane::default()))
+        .with_transform(IDENTITY.scale(10.0, 1.0, 10.0))
+        .with_material(floor_material);
+    let mut middle_material = Material {
+        color: Color::new(0.1, 1.0, 0.5),
+        diffuse: 0.7,
+        specular: 0.3,
+        pattern: Some(Pattern::new(Box::new(GradientPatternShape {
+            a: Color::new(1.0, 0.0, 0.0),
+            b: Color::new(0.0, 1.0, 0.0),
+        }))),
+        ..Material::default()
+    };
     middle_material.pattern.as_mut().unwrap().transform =
         IDENTITY.scale(2.0, 1.0, 1.0).translate(1.0, 0.0, 0.0);
-    middle.material = Rc::new(middle_material);
-    middle.transform = IDENTITY.translate(-0.5, 1.0, 0.5);
-    let mut right = Object::new(Box::new(Sphere::default()));
-    let mut right_material = Material::default();
-    right_material.color = Color::new(0.5, 1.0, 0.1);
-    right_material.diffuse = 0.7;
-    right_material.specular = 0.3;
-    right_material.pattern = Some(Pattern::new(Box::new(CheckersPatternShape {
-        a: Color::new(0.7, 0.4, 0.0),
-        b: Color::new(0.0, 1.0, 0.8),
-    })));
+    let middle = Object::new(Box::new(Sphere::default()))
+        .with_transform(IDENTITY.translate(-0.5, 1.0, 0.5))
+        .with_material(middle_material);
+    let mut right_material = Material {
+        color: Color::new(0.5, 1.0, 0.1),
+        diffuse: 0.7,
+        specular: 0.3,
+        pattern: Some(Pattern::new(Box::new(CheckersPatternShape {
+            a: Color::new(0.7, 0.4, 0.0),
+            b: Color::new(0.0, 1.0, 0.8),
+        }))),
+        ..Material::default()
+    };
     right_material.pattern.as_mut().unwrap().transform = IDENTITY.scale(0.5, 0.5, 0.5);
-    right.transform = IDENTITY.scale(0.5, 0.5, 0.5).translate(1.5, 0.5, -0.5);
-    right.material = Rc::new(right_material);
-    let mut left = Object::new(Box::new(Sphere::default()));
-    let mut left_material = Material::default();
-    left_material.color = Color::new(1.0, 0.8, 0.1);
-    left_material.diffuse = 0.7;
-    left_material.specular = 0.3;
-    left_material.pattern = Some(Pattern::new(Box::new(StripesPatternShape {
-        a: Color::new(1.0, 1.0, 1.0),
-        b: Color::new(0.0, 0.0, 0.0),
-    })));
+    let right = Object::new(Box::new(Sphere::default()))
+        .with_transform(IDENTITY.scale(0.5, 0.5, 0.5).translate(1.5, 0.5, -0.5))
+        .with_material(right_material);
+    let mut left_material = Material {
+        color: Color::new(1.0, 0.8, 0.1),
+        diffuse: 0.7,
+        specular: 0.3,
+        pattern: Some(Pattern::new(Box::new(StripesPatternShape {
+            a: Color::new(1.0, 1.0, 1.0),
+            b: Color::new(0.0, 0.0, 0.0),
+            smoothness: 0.0,
+            axis: Axis::X,
+        }))),
+        ..Material::default()
+    };
     left_material.pattern.as_mut().unwrap().transform = IDENTITY.scale(0.25, 1.0, 1.0);
-    left_material.reflective = 0.5;
-    left.transform = IDENTITY
-        .scale(0.33, 0.33, 0.33)
-        .translate(-1.5, 0.33, -0.75);
-    left.material = Rc::new(left_material);
+    let left = Object::new(Box::new(Sphere::default()))
+        .with_transform(
+            IDENTITY
+                .scale(0.33, 0.33, 0.33)
+                .translate(-1.5, 0.33, -0.75),
+        )
+        .with_material(left_material.with_reflective(Color::from(0.5)));
     let mut world = World::new();
-    world.light_source = Some(PointLight {
+    world.lights = vec![Box::new(PointLight {
         position: point(-5.0, 5.0, -5.0),
         intensity: Color::new(1.0, 1.0, 1.0),
-    });
+    })];
     world.objects = vec![floor, middle, right, left];
 
-    let mut camera = Camera::new(canvas_width, canvas_height, PI / 3.0);
-    camera.transform = view_transform(
+    let camera = Camera::looking_at(
+        canvas_width,
+        canvas_height,
+        PI / 3.0,
         &point(0.0, 1.5, -5.0),
         &point(0.0, 1.0, 0.0),
-        &vector(0.0, 1.0, 0.0),
     );
 
     let canvas = camera.render(&world);
@@ -6,7 +6,7 @@ use std::fs::File;
 use std::io::prelude::*;
 use std::rc::Rc;
 use tuples::{normalize, point};
-use world::{materials::Material, object::Object, shapes::{spheres::Sphere}};
+use world::{materials::Material, object::Object, shapes::spheres::Sphere};
 
 fn main() -> std::io::Result<()> {
     let canvas_size = 500;
@@ -16,8 +16,10 @@ fn main() -> std::io::Result<()> {
     };
     let mut c = canvas(canvas_size, canvas_size);
     let mut s = Object::new(Box::new(Sphere::default()));
-    let mut material = Material::default();
-    material.color = Color::new(0.443, 0.502, 0.725);
+    let material = Material {
+        color: Color::new(0.443, 0.502, 0.725),
+        ..Material::default()
+    };
     s.material = Rc::new(material);
     let ray_origin = point(0.0, 0.0, -5.0);
     let wall_z = 10.0;
@@ -31,19 +33,17 @@ fn main() -> std::io::Result<()> {
         for x in 0..canvas_size {
             let world_x = -half + pixel_size * x as f64;
             let position = point(world_x, world_y, wall_z);
-            let r = Ray {
-                origin: ray_origin,
-                direction: normalize(&(position - ray_origin)),
-            };
+            let r = Ray::new(ray_origin, normalize(&(position - ray_origin)));
 
-            if let Some(intersection) = s.intersect(&r) {
+            let intersection = s.intersect(&r);
+            if !intersection.is_empty() {
                 let point = r.position(intersection[0].t);
                 let normal = intersection[0].object.normal_at(&point);
                 let eye = -r.direction;
                 let color = intersection[0]
                     .object
                     .material
-                    .lightning(&s, &light, &point, &eye, &normal, false);
+                    .lightning(&s, &light, &point, &eye, &normal, 1.0);
                 write_pixel(&mut c, x as usize, y as usize, color);
             }
         }
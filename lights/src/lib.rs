@@ -1,7 +1,18 @@
 use colors::Color;
-use tuples::Tuple;
+use tuples::{magnitude, normalize, Tuple};
 
-#[derive(Debug, PartialEq)]
+pub trait Light {
+    fn intensity(&self) -> Color;
+    // Unit vector from the point towards the light.
+    fn direction_from(&self, point: &Tuple) -> Tuple;
+    // Infinite for lights that have no position.
+    fn distance_from(&self, point: &Tuple) -> f64;
+    // None for lights that are infinitely far away.
+    fn position(&self) -> Option<Tuple>;
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PointLight {
     pub position: Tuple,
     pub intensity: Color,
@@ -16,11 +27,64 @@ impl PointLight {
     }
 }
 
+impl Light for PointLight {
+    fn intensity(&self) -> Color {
+        self.intensity
+    }
+
+    fn direction_from(&self, point: &Tuple) -> Tuple {
+        normalize(&(self.position - *point))
+    }
+
+    fn distance_from(&self, point: &Tuple) -> f64 {
+        magnitude(&(self.position - *point))
+    }
+
+    fn position(&self) -> Option<Tuple> {
+        Some(self.position)
+    }
+}
+
+// Parallel light travelling along `direction`, like the sun.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DirectionalLight {
+    pub direction: Tuple,
+    pub intensity: Color,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Tuple, intensity: Color) -> DirectionalLight {
+        DirectionalLight {
+            direction: normalize(&direction),
+            intensity,
+        }
+    }
+}
+
+impl Light for DirectionalLight {
+    fn intensity(&self) -> Color {
+        self.intensity
+    }
+
+    fn direction_from(&self, _point: &Tuple) -> Tuple {
+        -self.direction
+    }
+
+    fn distance_from(&self, _point: &Tuple) -> f64 {
+        f64::INFINITY
+    }
+
+    fn position(&self) -> Option<Tuple> {
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::*;
     use colors::Color;
-    use tuples::point;
+    use tuples::{point, vector};
 
     #[test]
     fn point_light_has_a_position_and_intensity() {
@@ -33,4 +97,40 @@ mod tests {
         assert_eq!(light.position, position);
         assert_eq!(light.intensity, intensity);
     }
+
+    #[test]
+    fn point_light_direction_and_distance_depend_on_the_point() {
+        let light = PointLight::new(point(0.0, 10.0, 0.0), Color::new(1.0, 1.0, 1.0));
+        assert_eq!(
+            light.direction_from(&point(0.0, 0.0, 0.0)),
+            vector(0.0, 1.0, 0.0)
+        );
+        assert_eq!(light.distance_from(&point(0.0, 4.0, 0.0)), 6.0);
+        assert_eq!(Light::position(&light), Some(point(0.0, 10.0, 0.0)));
+    }
+
+    #[test]
+    fn directional_light_points_back_along_its_direction_from_everywhere() {
+        let light = DirectionalLight::new(vector(0.0, -2.0, 0.0), Color::new(1.0, 1.0, 1.0));
+        assert_eq!(light.direction, vector(0.0, -1.0, 0.0));
+        assert_eq!(
+            light.direction_from(&point(0.0, 0.0, 0.0)),
+            vector(0.0, 1.0, 0.0)
+        );
+        assert_eq!(
+            light.direction_from(&point(50.0, -3.0, 7.0)),
+            vector(0.0, 1.0, 0.0)
+        );
+        assert_eq!(light.distance_from(&point(1.0, 2.0, 3.0)), f64::INFINITY);
+        assert_eq!(light.position(), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn point_light_round_trips_through_json() {
+        let light = PointLight::new(point(-10.0, 10.0, -10.0), Color::new(1.0, 0.5, 0.25));
+        let json = serde_json::to_string(&light).unwrap();
+        let loaded: PointLight = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, light);
+    }
 }
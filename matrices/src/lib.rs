@@ -1,5 +1,8 @@
+pub mod matrix;
+
+pub use crate::matrix::Matrix;
 use float_cmp::ApproxEq;
-use tuples::Tuple;
+use tuples::{Tuple, EPSILON};
 
 pub const IDENTITY: [[f64; 4]; 4] = [
     [1.0, 0.0, 0.0, 0.0],
@@ -40,139 +43,18 @@ pub fn transpose(a: &[[f64; 4]; 4]) -> [[f64; 4]; 4] {
     m
 }
 
-fn determinant_2(a: &[[f64; 2]; 2]) -> f64 {
-    a[0][0] * a[1][1] - a[0][1] * a[1][0]
-}
-
-fn determinant_3(a: &[[f64; 3]; 3]) -> f64 {
-    let mut det = 0.0;
-
-    for col in 0..3 {
-        det = det + a[0][col] * cofactor_3(a, 0, col);
-    }
-
-    det
-}
-
-fn determinant_4(a: &[[f64; 4]; 4]) -> f64 {
-    let mut det = 0.0;
-
-    for col in 0..4 {
-        det = det + a[0][col] * cofactor_4(a, 0, col);
-    }
-
-    det
-}
-
-fn submatrix_3(a: &[[f64; 3]; 3], ignore_row: usize, ignore_col: usize) -> [[f64; 2]; 2] {
-    let mut result = [[0.0; 2]; 2];
-    let mut sub = vec![];
-
-    for row in 0..3 {
-        let mut row_vec = vec![];
-
-        if row == ignore_row {
-            continue;
-        }
-
-        for col in 0..3 {
-            if col == ignore_col {
-                continue;
-            }
-            row_vec.push(a[row][col]);
-        }
-
-        sub.push(row_vec);
-    }
-
-    for row in 0..2 {
-        for col in 0..2 {
-            result[row][col] = sub[row][col];
-        }
-    }
-
-    result
-}
-
-fn submatrix_4(a: &[[f64; 4]; 4], ignore_row: usize, ignore_col: usize) -> [[f64; 3]; 3] {
-    let mut result = [[0.0; 3]; 3];
-    let mut sub = vec![];
-
-    for row in 0..4 {
-        let mut row_vec = vec![];
-
-        if row == ignore_row {
-            continue;
-        }
-
-        for col in 0..4 {
-            if col == ignore_col {
-                continue;
-            }
-            row_vec.push(a[row][col]);
-        }
-
-        sub.push(row_vec);
-    }
-
-    for row in 0..3 {
-        for col in 0..3 {
-            result[row][col] = sub[row][col];
-        }
-    }
-
-    result
-}
-
-fn minor_3(a: &[[f64; 3]; 3], row: usize, col: usize) -> f64 {
-    determinant_2(&submatrix_3(a, row, col))
-}
-
-fn minor_4(a: &[[f64; 4]; 4], row: usize, col: usize) -> f64 {
-    determinant_3(&submatrix_4(a, row, col))
-}
-
-fn cofactor_3(a: &[[f64; 3]; 3], row: usize, col: usize) -> f64 {
-    let minor = minor_3(a, row, col);
-    if (row + col) % 2 != 0 {
-        return -minor;
-    }
-    minor
-}
-
-fn cofactor_4(a: &[[f64; 4]; 4], row: usize, col: usize) -> f64 {
-    let minor = minor_4(a, row, col);
-    if (row + col) % 2 != 0 {
-        return -minor;
-    }
-    minor
-}
-
 pub fn is_invertible(a: &[[f64; 4]; 4]) -> bool {
-    determinant_4(a) != 0.0
+    Matrix::from(*a).is_invertible()
 }
 
-pub fn inverse(a: &[[f64; 4]; 4]) -> [[f64; 4]; 4] {
-    let mut cofactors = [[0.0; 4]; 4];
-    for row in 0..4 {
-        for col in 0..4 {
-            cofactors[row][col] = cofactor_4(&a, row, col);
-        }
-    }
-    let mut result = transpose(&cofactors);
-    let determinant = determinant_4(&a);
-    for row in 0..4 {
-        for col in 0..4 {
-            result[row][col] = result[row][col] / determinant;
-        }
-    }
-    result
+pub fn inverse(a: &[[f64; 4]; 4]) -> Option<[[f64; 4]; 4]> {
+    Matrix::from(*a).inverse().map(|m| m.to_array())
 }
 
 pub fn approx_eq(a: &[[f64; 4]; 4], b: &[[f64; 4]; 4]) -> bool {
     for row in 0..4 {
         for col in 0..4 {
-            if !a[row][col].approx_eq(b[row][col], (0.0001, 2)) {
+            if !a[row][col].approx_eq(b[row][col], (EPSILON, 2)) {
                 return false;
             }
         }
@@ -334,13 +216,16 @@ mod tests {
     #[test]
     fn calculating_the_determinant_of_2x2_matrix() {
         let a = [[1.0, 5.0], [-3.0, 2.0]];
-        assert_eq!(determinant_2(&a), 17.0);
+        assert_eq!(Matrix::from(a).determinant(), 17.0);
     }
 
     #[test]
     fn submatrix_of_3x3_matrix_is_2x2_matrix() {
         let a = [[1.0, 5.0, 0.0], [-3.0, 2.0, 7.0], [0.0, 6.0, -3.0]];
-        assert_eq!(submatrix_3(&a, 0, 2), [[-3.0, 2.0], [0.0, 6.0]]);
+        assert_eq!(
+            Matrix::from(a).submatrix(0, 2),
+            Matrix::from([[-3.0, 2.0], [0.0, 6.0]])
+        );
     }
 
     #[test]
@@ -352,32 +237,32 @@ mod tests {
             [-7.0, 1.0, -1.0, 1.0],
         ];
         assert_eq!(
-            submatrix_4(&a, 2, 1),
-            [[-6.0, 1.0, 6.0], [-8.0, 8.0, 6.0], [-7.0, -1.0, 1.0]]
+            Matrix::from(a).submatrix(2, 1),
+            Matrix::from([[-6.0, 1.0, 6.0], [-8.0, 8.0, 6.0], [-7.0, -1.0, 1.0]])
         );
     }
 
     #[test]
     fn calculating_a_minor_of_a_3x3_matrix() {
         let a = [[3.0, 5.0, 0.0], [2.0, -1.0, -7.0], [6.0, -1.0, 5.0]];
-        let b = submatrix_3(&a, 1, 0);
-        assert_eq!(determinant_2(&b), minor_3(&a, 1, 0));
+        let b = Matrix::from(a).submatrix(1, 0);
+        assert_eq!(b.determinant(), Matrix::from(a).minor(1, 0));
     }
 
     #[test]
     fn calculating_a_cofactor_of_a_3x3_matrix() {
         let a = [[3.0, 5.0, 0.0], [2.0, -1.0, -7.0], [6.0, -1.0, 5.0]];
-        assert_eq!(minor_3(&a, 0, 0), cofactor_3(&a, 0, 0));
-        assert_eq!(minor_3(&a, 1, 0), -cofactor_3(&a, 1, 0));
+        assert_eq!(Matrix::from(a).minor(0, 0), Matrix::from(a).cofactor(0, 0));
+        assert_eq!(Matrix::from(a).minor(1, 0), -Matrix::from(a).cofactor(1, 0));
     }
 
     #[test]
     fn calculating_the_determinant_of_3x3_matrix() {
         let a = [[1.0, 2.0, 6.0], [-5.0, 8.0, -4.0], [2.0, 6.0, 4.0]];
-        assert_eq!(cofactor_3(&a, 0, 0), 56.0);
-        assert_eq!(cofactor_3(&a, 0, 1), 12.0);
-        assert_eq!(cofactor_3(&a, 0, 2), -46.0);
-        assert_eq!(determinant_3(&a), -196.0);
+        assert_eq!(Matrix::from(a).cofactor(0, 0), 56.0);
+        assert_eq!(Matrix::from(a).cofactor(0, 1), 12.0);
+        assert_eq!(Matrix::from(a).cofactor(0, 2), -46.0);
+        assert_eq!(Matrix::from(a).determinant(), -196.0);
     }
 
     #[test]
@@ -388,11 +273,11 @@ mod tests {
             [1.0, 2.0, -9.0, 6.0],
             [-6.0, 7.0, 7.0, -9.0],
         ];
-        assert_eq!(cofactor_4(&a, 0, 0), 690.0);
-        assert_eq!(cofactor_4(&a, 0, 1), 447.0);
-        assert_eq!(cofactor_4(&a, 0, 2), 210.0);
-        assert_eq!(cofactor_4(&a, 0, 3), 51.0);
-        assert_eq!(determinant_4(&a), -4071.0);
+        assert_eq!(Matrix::from(a).cofactor(0, 0), 690.0);
+        assert_eq!(Matrix::from(a).cofactor(0, 1), 447.0);
+        assert_eq!(Matrix::from(a).cofactor(0, 2), 210.0);
+        assert_eq!(Matrix::from(a).cofactor(0, 3), 51.0);
+        assert_eq!(Matrix::from(a).determinant(), -4071.0);
     }
 
     #[test]
@@ -403,7 +288,7 @@ mod tests {
             [4.0, -9.0, 3.0, -7.0],
             [9.0, 1.0, 7.0, -6.0],
         ];
-        assert_eq!(determinant_4(&a), -2120.0);
+        assert_eq!(Matrix::from(a).determinant(), -2120.0);
         assert!(is_invertible(&a));
     }
 
@@ -415,8 +300,9 @@ mod tests {
             [0.0, -5.0, 1.0, -5.0],
             [0.0, 0.0, 0.0, 0.0],
         ];
-        assert_eq!(determinant_4(&a), 0.0);
+        assert_eq!(Matrix::from(a).determinant(), 0.0);
         assert!(!is_invertible(&a));
+        assert_eq!(inverse(&a), None);
     }
 
     #[test]
@@ -427,11 +313,11 @@ mod tests {
             [7.0, 7.0, -6.0, -7.0],
             [1.0, -3.0, 7.0, 4.0],
         ];
-        let b = inverse(&a);
-        assert_eq!(determinant_4(&a), 532.0);
-        assert_eq!(cofactor_4(&a, 2, 3), -160.0);
+        let b = inverse(&a).unwrap();
+        assert_eq!(Matrix::from(a).determinant(), 532.0);
+        assert_eq!(Matrix::from(a).cofactor(2, 3), -160.0);
         assert_eq!(b[3][2], -160.0 / 532.0);
-        assert_eq!(cofactor_4(&a, 3, 2), 105.0);
+        assert_eq!(Matrix::from(a).cofactor(3, 2), 105.0);
         assert_eq!(b[2][3], 105.0 / 532.0);
         assert!(approx_eq(
             &b,
@@ -459,7 +345,7 @@ mod tests {
             [6.0, -2.0, 0.0, 5.0],
         ];
         let c = matrix_multiply(&a, &b);
-        let inv_b = inverse(&b);
+        let inv_b = inverse(&b).unwrap();
         assert!(approx_eq(&matrix_multiply(&c, &inv_b), &a))
     }
 }
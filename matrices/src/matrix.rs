@@ -0,0 +1,175 @@
+use std::ops::{Index, IndexMut};
+
+const SINGULAR_EPSILON: f64 = 1e-12;
+
+// Square matrix of any size, stored row-major.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix {
+    size: usize,
+    data: Vec<f64>,
+}
+
+impl Matrix {
+    pub fn new(size: usize) -> Matrix {
+        Matrix {
+            size,
+            data: vec![0.0; size * size],
+        }
+    }
+
+    pub fn identity(size: usize) -> Matrix {
+        let mut m = Matrix::new(size);
+        for i in 0..size {
+            m[(i, i)] = 1.0;
+        }
+        m
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn to_array<const N: usize>(&self) -> [[f64; N]; N] {
+        assert_eq!(self.size, N, "matrix is {0}x{0}, not {1}x{1}", self.size, N);
+        let mut result = [[0.0; N]; N];
+        for (row, values) in result.iter_mut().enumerate() {
+            values.copy_from_slice(&self.data[row * N..row * N + N]);
+        }
+        result
+    }
+
+    pub fn transpose(&self) -> Matrix {
+        let mut m = Matrix::new(self.size);
+        for row in 0..self.size {
+            for col in 0..self.size {
+                m[(row, col)] = self[(col, row)];
+            }
+        }
+        m
+    }
+
+    pub fn submatrix(&self, ignore_row: usize, ignore_col: usize) -> Matrix {
+        let mut m = Matrix::new(self.size - 1);
+        m.data = (0..self.size)
+            .filter(|&row| row != ignore_row)
+            .flat_map(|row| {
+                (0..self.size)
+                    .filter(move |&col| col != ignore_col)
+                    .map(move |col| (row, col))
+            })
+            .map(|index| self[index])
+            .collect();
+        m
+    }
+
+    pub fn minor(&self, row: usize, col: usize) -> f64 {
+        self.submatrix(row, col).determinant()
+    }
+
+    pub fn cofactor(&self, row: usize, col: usize) -> f64 {
+        let minor = self.minor(row, col);
+        if !(row + col).is_multiple_of(2) {
+            return -minor;
+        }
+        minor
+    }
+
+    pub fn determinant(&self) -> f64 {
+        match self.size {
+            0 => 1.0,
+            1 => self.data[0],
+            2 => self.data[0] * self.data[3] - self.data[1] * self.data[2],
+            _ => {
+                let mut det = 0.0;
+                for col in 0..self.size {
+                    det += self[(0, col)] * self.cofactor(0, col);
+                }
+                det
+            }
+        }
+    }
+
+    pub fn is_invertible(&self) -> bool {
+        self.determinant().abs() > SINGULAR_EPSILON
+    }
+
+    pub fn inverse(&self) -> Option<Matrix> {
+        let determinant = self.determinant();
+        if determinant.abs() <= SINGULAR_EPSILON {
+            return None;
+        }
+        let mut result = Matrix::new(self.size);
+        for row in 0..self.size {
+            for col in 0..self.size {
+                result[(col, row)] = self.cofactor(row, col) / determinant;
+            }
+        }
+        Some(result)
+    }
+}
+
+impl<const N: usize> From<[[f64; N]; N]> for Matrix {
+    fn from(rows: [[f64; N]; N]) -> Matrix {
+        Matrix {
+            size: N,
+            data: rows.iter().flatten().copied().collect(),
+        }
+    }
+}
+
+impl Index<(usize, usize)> for Matrix {
+    type Output = f64;
+
+    fn index(&self, (row, col): (usize, usize)) -> &f64 {
+        &self.data[row * self.size + col]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Matrix {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut f64 {
+        &mut self.data[row * self.size + col]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::matrix::Matrix;
+    use crate::{approx_eq, inverse};
+
+    #[test]
+    fn converting_to_and_from_arrays() {
+        let a = [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]];
+        let m = Matrix::from(a);
+        assert_eq!(m.size(), 3);
+        assert_eq!(m[(1, 2)], 6.0);
+        assert_eq!(m.to_array::<3>(), a);
+    }
+
+    #[test]
+    fn inverse_of_a_4x4_matrix_matches_the_array_version() {
+        let a = [
+            [-5.0, 2.0, 6.0, -8.0],
+            [1.0, -5.0, 1.0, 8.0],
+            [7.0, 7.0, -6.0, -7.0],
+            [1.0, -3.0, 7.0, 4.0],
+        ];
+        let b = Matrix::from(a).inverse().unwrap().to_array::<4>();
+        assert_eq!(b[3][2], -160.0 / 532.0);
+        assert!(approx_eq(&b, &inverse(&a).unwrap()));
+        assert!(approx_eq(
+            &b,
+            &[
+                [0.21805, 0.45113, 0.24060, -0.04511],
+                [-0.80827, -1.45677, -0.44361, 0.52068],
+                [-0.07895, -0.22368, -0.05263, 0.19737],
+                [-0.52256, -0.81391, -0.30075, 0.30639]
+            ]
+        ));
+    }
+
+    #[test]
+    fn identity_is_its_own_inverse() {
+        assert_eq!(Matrix::identity(3).inverse(), Some(Matrix::identity(3)));
+        assert_eq!(Matrix::identity(4).transpose(), Matrix::identity(4));
+    }
+}
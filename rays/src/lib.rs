@@ -5,16 +5,22 @@ use tuples::Tuple;
 pub struct Ray {
     pub origin: Tuple,
     pub direction: Tuple,
+    pub time: f64,
 }
 
 impl Ray {
     pub fn new(origin: Tuple, direction: Tuple) -> Ray {
         Ray {
             origin,
-            direction
+            direction,
+            time: 0.0,
         }
     }
 
+    pub fn with_time(self, time: f64) -> Ray {
+        Ray { time, ..self }
+    }
+
     pub fn position(&self, t: f64) -> Tuple {
         self.origin + self.direction * t
     }
@@ -23,6 +29,7 @@ impl Ray {
         Ray {
             origin: matrix_tuple_multiply(m, &self.origin),
             direction: matrix_tuple_multiply(m, &self.direction),
+            time: self.time,
         }
     }
 }
@@ -38,17 +45,14 @@ mod tests {
     fn creating_and_querying_a_ray() {
         let origin = point(1.0, 2.0, 3.0);
         let direction = vector(4.0, 5.0, 6.0);
-        let r = Ray { origin, direction };
+        let r = Ray::new(origin, direction);
         assert_eq!(r.origin, origin);
         assert_eq!(r.direction, direction);
     }
 
     #[test]
     fn computing_a_point_from_a_distance() {
-        let r = Ray {
-            origin: point(2.0, 3.0, 4.0),
-            direction: vector(1.0, 0.0, 0.0),
-        };
+        let r = Ray::new(point(2.0, 3.0, 4.0), vector(1.0, 0.0, 0.0));
         assert_eq!(r.position(0.0), point(2.0, 3.0, 4.0));
         assert_eq!(r.position(1.0), point(3.0, 3.0, 4.0));
         assert_eq!(r.position(-1.0), point(1.0, 3.0, 4.0));
@@ -57,10 +61,7 @@ mod tests {
 
     #[test]
     fn translating_a_ray() {
-        let r = Ray {
-            origin: point(1.0, 2.0, 3.0),
-            direction: vector(0.0, 1.0, 0.0),
-        };
+        let r = Ray::new(point(1.0, 2.0, 3.0), vector(0.0, 1.0, 0.0));
         let m = IDENTITY.translate(3.0, 4.0, 5.0);
         let r2 = r.transform(&m);
         assert_eq!(r2.origin, point(4.0, 6.0, 8.0));
@@ -69,13 +70,21 @@ mod tests {
 
     #[test]
     fn scaling_a_ray() {
-        let r = Ray {
-            origin: point(1.0, 2.0, 3.0),
-            direction: vector(0.0, 1.0, 0.0),
-        };
+        let r = Ray::new(point(1.0, 2.0, 3.0), vector(0.0, 1.0, 0.0));
         let m = IDENTITY.scale(2.0, 3.0, 4.0);
         let r2 = r.transform(&m);
         assert_eq!(r2.origin, point(2.0, 6.0, 12.0));
         assert_eq!(r2.direction, vector(0.0, 3.0, 0.0));
     }
+
+    #[test]
+    fn transforming_a_ray_keeps_its_time() {
+        let r = Ray::new(point(1.0, 2.0, 3.0), vector(0.0, 1.0, 0.0)).with_time(0.25);
+        let r2 = r.transform(&IDENTITY.translate(3.0, 4.0, 5.0));
+        assert_eq!(
+            Ray::new(point(0.0, 0.0, 0.0), vector(0.0, 0.0, 1.0)).time,
+            0.0
+        );
+        assert_eq!(r2.time, 0.25);
+    }
 }
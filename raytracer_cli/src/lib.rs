@@ -0,0 +1,213 @@
+use canvas::{canvas_to_png, canvas_to_ppm, Canvas};
+use lights::PointLight;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use world::{
+    camera::{Camera, CameraConfig},
+    object::{Object, ObjectConfig},
+    World,
+};
+
+pub const USAGE: &str = "Usage: raytracer-cli --scene <yaml> --output <file.ppm|file.png> \
+[--width <pixels>] [--height <pixels>] [--samples <shadow samples>] [--threads <count>]";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneConfig {
+    pub camera: CameraConfig,
+    #[serde(default)]
+    pub lights: Vec<PointLight>,
+    #[serde(default)]
+    pub objects: Vec<ObjectConfig>,
+    #[serde(default)]
+    pub shadow_radius: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub scene: PathBuf,
+    pub output: PathBuf,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub samples: u32,
+    pub threads: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Ppm,
+    Png,
+}
+
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Options, String> {
+    let mut scene = None;
+    let mut output = None;
+    let mut width = None;
+    let mut height = None;
+    let mut samples = 1;
+    let mut threads = thread::available_parallelism().map_or(1, |n| n.get());
+
+    let mut args = args.into_iter();
+    while let Some(flag) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {}", flag))
+        };
+        match flag.as_str() {
+            "--scene" => scene = Some(PathBuf::from(value()?)),
+            "--output" => output = Some(PathBuf::from(value()?)),
+            "--width" => width = Some(parse_number(&flag, &value()?)?),
+            "--height" => height = Some(parse_number(&flag, &value()?)?),
+            "--samples" => samples = parse_number(&flag, &value()?)?,
+            "--threads" => threads = parse_number(&flag, &value()?)?,
+            _ => return Err(format!("Unknown argument: {}", flag)),
+        }
+    }
+
+    Ok(Options {
+        scene: scene.ok_or("Missing --scene")?,
+        output: output.ok_or("Missing --output")?,
+        width,
+        height,
+        samples,
+        threads,
+    })
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value for {}: {}", flag, value))
+}
+
+fn output_format(path: &Path) -> Result<Format, String> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("ppm") => Ok(Format::Ppm),
+        Some(ext) if ext.eq_ignore_ascii_case("png") => Ok(Format::Png),
+        _ => Err(format!("Unsupported output format: {}", path.display())),
+    }
+}
+
+pub fn load_scene(yaml: &str) -> Result<SceneConfig, String> {
+    serde_yaml::from_str(yaml).map_err(|e| format!("Invalid scene: {}", e))
+}
+
+fn build_world(scene: &SceneConfig, samples: u32) -> World {
+    let mut world = World::new();
+    for light in &scene.lights {
+        world.lights.push(Box::new(*light));
+    }
+    world.objects = scene.objects.iter().map(Object::from_config).collect();
+    world.shadow_samples = samples;
+    world.shadow_radius = scene.shadow_radius;
+    world
+}
+
+// World holds Rc handles and cannot cross threads, so every band rebuilds
+// its own copy from the configuration before rendering its rows.
+pub fn render(scene: &SceneConfig, samples: u32, threads: usize) -> Canvas {
+    let config = &scene.camera;
+    let threads = threads.clamp(1, config.vsize.max(1) as usize) as i32;
+    let band = (config.vsize + threads - 1) / threads;
+    let mut image = Canvas::new(config.hsize, config.vsize);
+
+    thread::scope(|s| {
+        let handles: Vec<_> = (0..threads)
+            .map(|i| {
+                let y0 = (i * band).min(config.vsize);
+                let y1 = (y0 + band).min(config.vsize);
+                s.spawn(move || {
+                    let world = build_world(scene, samples);
+                    let camera = Camera::from_config(config);
+                    (y0, camera.render_region(&world, 0, y0, config.hsize, y1))
+                })
+            })
+            .collect();
+        for handle in handles {
+            let (y0, region) = handle.join().expect("render thread panicked");
+            image.blit(&region, 0, y0);
+        }
+    });
+
+    image
+}
+
+pub fn run(options: &Options) -> Result<(), String> {
+    let format = output_format(&options.output)?;
+    let yaml = fs::read_to_string(&options.scene)
+        .map_err(|e| format!("Cannot read {}: {}", options.scene.display(), e))?;
+    let mut scene = load_scene(&yaml)?;
+    if let Some(width) = options.width {
+        scene.camera.hsize = width;
+    }
+    if let Some(height) = options.height {
+        scene.camera.vsize = height;
+    }
+
+    let image = render(&scene, options.samples, options.threads);
+    let written = match format {
+        Format::Ppm => fs::write(&options.output, canvas_to_ppm(&image)),
+        Format::Png => canvas_to_png(&image, &options.output),
+    };
+    written.map_err(|e| format!("Cannot write {}: {}", options.output.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{output_format, parse_args, Format, Options};
+    use std::path::{Path, PathBuf};
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn parsing_command_line_arguments() {
+        let options = parse_args(args(&[
+            "--scene",
+            "scene.yml",
+            "--output",
+            "out.png",
+            "--width",
+            "64",
+            "--height",
+            "32",
+            "--samples",
+            "4",
+            "--threads",
+            "2",
+        ]))
+        .unwrap();
+        assert_eq!(
+            options,
+            Options {
+                scene: PathBuf::from("scene.yml"),
+                output: PathBuf::from("out.png"),
+                width: Some(64),
+                height: Some(32),
+                samples: 4,
+                threads: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn rejecting_invalid_arguments() {
+        assert!(parse_args(args(&["--output", "out.ppm"])).is_err());
+        assert!(parse_args(args(&["--scene", "s.yml", "--output"])).is_err());
+        assert!(parse_args(args(&[
+            "--scene", "s.yml", "--output", "o.ppm", "--width", "x"
+        ]))
+        .is_err());
+        assert!(parse_args(args(&["--frobnicate"])).is_err());
+    }
+
+    #[test]
+    fn choosing_the_output_format_from_the_extension() {
+        assert_eq!(output_format(Path::new("out.ppm")), Ok(Format::Ppm));
+        assert_eq!(output_format(Path::new("out.PNG")), Ok(Format::Png));
+        assert!(output_format(Path::new("out.jpg")).is_err());
+        assert!(output_format(Path::new("out")).is_err());
+    }
+}
@@ -0,0 +1,11 @@
+use std::process;
+
+fn main() {
+    let result = raytracer_cli::parse_args(std::env::args().skip(1))
+        .map_err(|e| format!("{}\n{}", e, raytracer_cli::USAGE))
+        .and_then(|options| raytracer_cli::run(&options));
+    if let Err(message) = result {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,51 @@
+use colors::Color;
+use lights::PointLight;
+use raytracer_cli::{run, Options, SceneConfig};
+use std::fs;
+use tuples::point;
+use world::{camera::Camera, object::Object, shapes::spheres::Sphere};
+
+#[test]
+fn rendering_a_tiny_scene_to_a_file() {
+    let camera = Camera::looking_at(
+        8,
+        8,
+        std::f64::consts::FRAC_PI_2,
+        &point(0.0, 0.0, -5.0),
+        &point(0.0, 0.0, 0.0),
+    );
+    let scene = SceneConfig {
+        camera: camera.to_config(),
+        lights: vec![PointLight::new(
+            point(-10.0, 10.0, -10.0),
+            Color::new(1.0, 1.0, 1.0),
+        )],
+        objects: vec![Object::new(Box::new(Sphere::default()))
+            .to_config()
+            .unwrap()],
+        shadow_radius: 0.0,
+    };
+
+    let dir = std::env::temp_dir().join(format!("raytracer-cli-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let scene_path = dir.join("scene.yml");
+    fs::write(&scene_path, serde_yaml::to_string(&scene).unwrap()).unwrap();
+
+    for name in &["out.ppm", "out.png"] {
+        let output = dir.join(name);
+        run(&Options {
+            scene: scene_path.clone(),
+            output: output.clone(),
+            width: Some(6),
+            height: Some(4),
+            samples: 1,
+            threads: 3,
+        })
+        .unwrap();
+        assert!(fs::metadata(&output).unwrap().len() > 0);
+    }
+
+    let ppm = fs::read_to_string(dir.join("out.ppm")).unwrap();
+    assert!(ppm.starts_with("P3\n6 4\n255\n"));
+    fs::remove_dir_all(&dir).unwrap();
+}
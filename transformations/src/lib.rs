@@ -98,7 +98,7 @@ mod tests {
     #[test]
     fn multiplying_by_the_inverse_of_translation_matrix() {
         let transform = IDENTITY.translate(5.0, -3.0, 2.0);
-        let inv = inverse(&transform);
+        let inv = inverse(&transform).unwrap();
         let p = point(-3.0, 4.0, 5.0);
         assert_eq!(matrix_tuple_multiply(&inv, &p), point(-8.0, 7.0, 3.0));
     }
@@ -133,7 +133,7 @@ mod tests {
     #[test]
     fn multiplying_by_the_inverse_of_scaling_matrix() {
         let transform = IDENTITY.scale(2.0, 3.0, 4.0);
-        let inv = inverse(&transform);
+        let inv = inverse(&transform).unwrap();
         let v = vector(-4.0, 6.0, 8.0);
         assert_eq!(matrix_tuple_multiply(&inv, &v), vector(-2.0, 2.0, 2.0));
     }
@@ -152,7 +152,7 @@ mod tests {
         let full_quarter = IDENTITY.rotate_x(PI / 2.0);
         assert_eq!(
             matrix_tuple_multiply(&half_quarter, &p),
-            point(0.0, (2.0 as f64).sqrt() / 2.0, (2.0 as f64).sqrt() / 2.0)
+            point(0.0, 2.0_f64.sqrt() / 2.0, 2.0_f64.sqrt() / 2.0)
         );
         assert_eq!(
             matrix_tuple_multiply(&full_quarter, &p),
@@ -164,10 +164,10 @@ mod tests {
     fn inverse_of_an_x_rotation_rotates_in_the_opposite_direction() {
         let p = point(0.0, 1.0, 0.0);
         let half_quarter = IDENTITY.rotate_x(PI / 4.0);
-        let inv = inverse(&half_quarter);
+        let inv = inverse(&half_quarter).unwrap();
         assert_eq!(
             matrix_tuple_multiply(&inv, &p),
-            point(0.0, (2.0 as f64).sqrt() / 2.0, -((2.0 as f64).sqrt() / 2.0))
+            point(0.0, 2.0_f64.sqrt() / 2.0, -(2.0_f64.sqrt() / 2.0))
         )
     }
 
@@ -178,7 +178,7 @@ mod tests {
         let full_quarter = IDENTITY.rotate_y(PI / 2.0);
         assert_eq!(
             matrix_tuple_multiply(&half_quarter, &p),
-            point((2.0 as f64).sqrt() / 2.0, 0.0, (2.0 as f64).sqrt() / 2.0)
+            point(2.0_f64.sqrt() / 2.0, 0.0, 2.0_f64.sqrt() / 2.0)
         );
         assert_eq!(
             matrix_tuple_multiply(&full_quarter, &p),
@@ -193,7 +193,7 @@ mod tests {
         let full_quarter = IDENTITY.rotate_z(PI / 2.0);
         assert_eq!(
             matrix_tuple_multiply(&half_quarter, &p),
-            point(-(2.0 as f64).sqrt() / 2.0, (2.0 as f64).sqrt() / 2.0, 0.0)
+            point(-2.0_f64.sqrt() / 2.0, 2.0_f64.sqrt() / 2.0, 0.0)
         );
         assert_eq!(
             matrix_tuple_multiply(&full_quarter, &p),
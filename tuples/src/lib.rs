@@ -1,6 +1,9 @@
 use float_cmp::ApproxEq;
 
+pub const EPSILON: f64 = 0.00001;
+
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Tuple {
     pub x: f64,
     pub y: f64,
@@ -16,14 +19,37 @@ impl Tuple {
     pub fn is_vector(&self) -> bool {
         self.w == 0.0
     }
+
+    pub fn as_point(&self) -> Tuple {
+        point(self.x, self.y, self.z)
+    }
+
+    pub fn as_vector(&self) -> Tuple {
+        vector(self.x, self.y, self.z)
+    }
+
+    pub fn is_near_zero(&self, epsilon: f64) -> bool {
+        self.x.abs() < epsilon
+            && self.y.abs() < epsilon
+            && self.z.abs() < epsilon
+            && self.w.abs() < epsilon
+    }
+
+    pub fn rotated_about(&self, pivot: &Tuple, axis: &Tuple, angle: f64) -> Tuple {
+        let k = normalize(axis);
+        let v = *self - *pivot;
+        let (sin, cos) = angle.sin_cos();
+        let rotated = v * cos + cross(&k, &v) * sin + k * dot(&k, &v) * (1.0 - cos);
+        *pivot + rotated
+    }
 }
 
 impl PartialEq for Tuple {
     fn eq(&self, other: &Self) -> bool {
-        self.x.approx_eq(other.x, (0.0001, 2))
-            && self.y.approx_eq(other.y, (0.0001, 2))
-            && self.z.approx_eq(other.z, (0.0001, 2))
-            && self.w.approx_eq(other.w, (0.0001, 2))
+        self.x.approx_eq(other.x, (EPSILON, 2))
+            && self.y.approx_eq(other.y, (EPSILON, 2))
+            && self.z.approx_eq(other.z, (EPSILON, 2))
+            && self.w.approx_eq(other.w, (EPSILON, 2))
     }
 }
 
@@ -92,6 +118,30 @@ impl std::ops::Div<f64> for Tuple {
     }
 }
 
+impl std::ops::AddAssign for Tuple {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl std::ops::SubAssign for Tuple {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl std::ops::MulAssign<f64> for Tuple {
+    fn mul_assign(&mut self, other: f64) {
+        *self = *self * other;
+    }
+}
+
+impl std::ops::DivAssign<f64> for Tuple {
+    fn div_assign(&mut self, other: f64) {
+        *self = *self / other;
+    }
+}
+
 impl From<[f64; 4]> for Tuple {
     fn from(v: [f64; 4]) -> Tuple {
         Tuple {
@@ -120,6 +170,7 @@ pub fn magnitude(v: &Tuple) -> f64 {
 }
 
 pub fn normalize(v: &Tuple) -> Tuple {
+    debug_assert!(v.is_vector(), "normalize expects a vector, got {:?}", v);
     let m = magnitude(v);
     Tuple {
         x: v.x / m,
@@ -134,6 +185,25 @@ pub fn dot(v1: &Tuple, v2: &Tuple) -> f64 {
 }
 
 pub fn cross(v1: &Tuple, v2: &Tuple) -> Tuple {
+    debug_assert!(
+        v1.is_vector() && v2.is_vector(),
+        "cross expects vectors, got {:?} and {:?}",
+        v1,
+        v2
+    );
+    unchecked_cross(v1, v2)
+}
+
+// Same as cross, but reports points instead of asserting on them.
+pub fn checked_cross(v1: &Tuple, v2: &Tuple) -> Option<Tuple> {
+    if v1.is_vector() && v2.is_vector() {
+        Some(unchecked_cross(v1, v2))
+    } else {
+        None
+    }
+}
+
+fn unchecked_cross(v1: &Tuple, v2: &Tuple) -> Tuple {
     vector(
         v1.y * v2.z - v1.z * v2.y,
         v1.z * v2.x - v1.x * v2.z,
@@ -149,6 +219,18 @@ pub fn reflect(vin: &Tuple, normal: &Tuple) -> Tuple {
 mod tests {
     use crate::*;
 
+    #[test]
+    fn tuples_are_equal_within_epsilon() {
+        assert_eq!(
+            vector(0.0, 0.97014, -0.24254),
+            vector(0.0, 0.970142, -0.242535)
+        );
+        assert_ne!(
+            vector(0.0, 0.97014, -0.24254),
+            vector(0.0, 0.97016, -0.24254)
+        );
+    }
+
     #[test]
     fn tuple_with_w_equal_to_1_is_a_point() {
         let a = tuple(4.3, -4.2, 3.1, 1.0);
@@ -242,6 +324,21 @@ mod tests {
         assert_eq!(a / 2.0, tuple(0.5, -1.0, 1.5, -2.0));
     }
 
+    #[test]
+    fn assignment_operators_match_their_binary_counterparts() {
+        let a = tuple(3.0, -2.0, 5.0, 1.0);
+        let b = tuple(-2.0, 3.0, 1.0, 0.0);
+        let mut c = a;
+        c += b;
+        assert_eq!(c, a + b);
+        c -= b;
+        assert_eq!(c, a);
+        c *= 2.0;
+        assert_eq!(c, a * 2.0);
+        c /= 4.0;
+        assert_eq!(c, a / 2.0);
+    }
+
     #[test]
     fn computing_the_magnitude_of_some_vectors() {
         let v1 = vector(1.0, 0.0, 0.0);
@@ -252,8 +349,8 @@ mod tests {
         assert_eq!(magnitude(&v1), 1.0);
         assert_eq!(magnitude(&v2), 1.0);
         assert_eq!(magnitude(&v3), 1.0);
-        assert_eq!(magnitude(&v4), (14.0 as f64).sqrt());
-        assert_eq!(magnitude(&v5), (14.0 as f64).sqrt());
+        assert_eq!(magnitude(&v4), 14.0_f64.sqrt());
+        assert_eq!(magnitude(&v5), 14.0_f64.sqrt());
     }
 
     #[test]
@@ -264,9 +361,9 @@ mod tests {
         assert_eq!(
             normalize(&v2),
             vector(
-                1.0 / (14.0 as f64).sqrt(),
-                2.0 / (14.0 as f64).sqrt(),
-                3.0 / (14.0 as f64).sqrt()
+                1.0 / 14.0_f64.sqrt(),
+                2.0 / 14.0_f64.sqrt(),
+                3.0 / 14.0_f64.sqrt()
             )
         );
     }
@@ -293,6 +390,31 @@ mod tests {
         assert_eq!(cross(&b, &a), vector(1.0, -2.0, 1.0));
     }
 
+    #[test]
+    fn checked_cross_product_rejects_points() {
+        let a = vector(1.0, 2.0, 3.0);
+        let b = vector(2.0, 3.0, 4.0);
+        assert_eq!(checked_cross(&a, &b), Some(vector(-1.0, 2.0, -1.0)));
+        assert_eq!(checked_cross(&point(1.0, 2.0, 3.0), &b), None);
+        assert_eq!(checked_cross(&a, &point(2.0, 3.0, 4.0)), None);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "cross expects vectors")]
+    fn cross_product_of_a_point_asserts_in_debug_builds() {
+        cross(&point(1.0, 2.0, 3.0), &vector(2.0, 3.0, 4.0));
+    }
+
+    #[test]
+    fn converting_between_points_and_vectors() {
+        let p = point(1.0, -2.0, 3.0);
+        assert!(p.as_vector().is_vector());
+        assert_eq!(p.as_vector(), vector(1.0, -2.0, 3.0));
+        assert!(p.as_vector().as_point().is_point());
+        assert_eq!(p.as_vector().as_point(), p);
+    }
+
     #[test]
     fn reflecting_a_vector_approaching_at_45_degree() {
         let v = vector(1.0, -1.0, 0.0);
@@ -304,8 +426,23 @@ mod tests {
     #[test]
     fn reflecting_a_vector_off_a_slanted_surface() {
         let v = vector(0.0, -1.0, 0.0);
-        let n = vector((2.0 as f64).sqrt() / 2.0, (2.0 as f64).sqrt() / 2.0, 0.0);
+        let n = vector(2.0_f64.sqrt() / 2.0, 2.0_f64.sqrt() / 2.0, 0.0);
         let r = reflect(&v, &n);
         assert_eq!(r, vector(1.0, 0.0, 0.0));
     }
+
+    #[test]
+    fn rotating_a_point_about_a_pivot() {
+        let p = point(2.0, 0.0, 0.0);
+        let pivot = point(1.0, 0.0, 0.0);
+        let r = p.rotated_about(&pivot, &vector(0.0, 1.0, 0.0), std::f64::consts::PI / 2.0);
+        assert_eq!(r, point(1.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn a_tiny_vector_is_near_zero() {
+        assert!(vector(1e-9, -1e-9, 1e-9).is_near_zero(0.0001));
+        assert!(!vector(0.0, 0.001, 0.0).is_near_zero(0.0001));
+        assert!(!point(0.0, 0.0, 0.0).is_near_zero(0.0001));
+    }
 }
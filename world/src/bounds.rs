@@ -0,0 +1,300 @@
+use crate::INTERSECTION_EPSILON;
+use matrices::matrix_tuple_multiply;
+use rays::Ray;
+use tuples::{point, Tuple};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Tuple,
+    pub max: Tuple,
+}
+
+impl Aabb {
+    pub fn new(min: Tuple, max: Tuple) -> Aabb {
+        Aabb { min, max }
+    }
+
+    pub fn add_point(&mut self, p: &Tuple) {
+        self.min = point(
+            self.min.x.min(p.x),
+            self.min.y.min(p.y),
+            self.min.z.min(p.z),
+        );
+        self.max = point(
+            self.max.x.max(p.x),
+            self.max.y.max(p.y),
+            self.max.z.max(p.z),
+        );
+    }
+
+    pub fn add_box(&mut self, other: &Aabb) {
+        self.add_point(&other.min);
+        self.add_point(&other.max);
+    }
+
+    pub fn contains_point(&self, p: &Tuple) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x)
+            && (self.min.y..=self.max.y).contains(&p.y)
+            && (self.min.z..=self.max.z).contains(&p.z)
+    }
+
+    pub fn contains_box(&self, other: &Aabb) -> bool {
+        self.contains_point(&other.min) && self.contains_point(&other.max)
+    }
+
+    pub fn split(&self) -> (Aabb, Aabb) {
+        let dx = self.max.x - self.min.x;
+        let dy = self.max.y - self.min.y;
+        let dz = self.max.z - self.min.z;
+        let greatest = dx.max(dy).max(dz);
+
+        let mut mid_min = self.min;
+        let mut mid_max = self.max;
+        if greatest == dx {
+            mid_min.x = self.min.x + dx / 2.0;
+            mid_max.x = mid_min.x;
+        } else if greatest == dy {
+            mid_min.y = self.min.y + dy / 2.0;
+            mid_max.y = mid_min.y;
+        } else {
+            mid_min.z = self.min.z + dz / 2.0;
+            mid_max.z = mid_min.z;
+        }
+
+        (Aabb::new(self.min, mid_max), Aabb::new(mid_min, self.max))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    pub fn transform(&self, m: &[[f64; 4]; 4]) -> Aabb {
+        if self.is_empty() {
+            return *self;
+        }
+        // Infinite corners turn into NaNs once rotated, so an unbounded box
+        // stays unbounded in every direction.
+        let bounded = [self.min, self.max]
+            .iter()
+            .all(|p| p.x.is_finite() && p.y.is_finite() && p.z.is_finite());
+        if !bounded {
+            return Aabb::new(
+                point(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+                point(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            );
+        }
+        let corners = [
+            self.min,
+            point(self.min.x, self.min.y, self.max.z),
+            point(self.min.x, self.max.y, self.min.z),
+            point(self.min.x, self.max.y, self.max.z),
+            point(self.max.x, self.min.y, self.min.z),
+            point(self.max.x, self.min.y, self.max.z),
+            point(self.max.x, self.max.y, self.min.z),
+            self.max,
+        ];
+        let mut result = Aabb::default();
+        for corner in corners.iter() {
+            result.add_point(&matrix_tuple_multiply(m, corner));
+        }
+        result
+    }
+
+    pub fn intersects(&self, ray: &Ray) -> bool {
+        self.intersect(ray).is_some()
+    }
+
+    pub fn intersect(&self, ray: &Ray) -> Option<(f64, f64)> {
+        let (xtmin, xtmax) = check_axis(ray.origin.x, ray.direction.x, self.min.x, self.max.x);
+        let (ytmin, ytmax) = check_axis(ray.origin.y, ray.direction.y, self.min.y, self.max.y);
+        let (ztmin, ztmax) = check_axis(ray.origin.z, ray.direction.z, self.min.z, self.max.z);
+
+        let tmin = xtmin.max(ytmin).max(ztmin);
+        let tmax = xtmax.min(ytmax).min(ztmax);
+
+        if tmin > tmax {
+            return None;
+        }
+
+        Some((tmin, tmax))
+    }
+}
+
+impl Default for Aabb {
+    fn default() -> Aabb {
+        Aabb::new(
+            point(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            point(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        )
+    }
+}
+
+fn check_axis(origin: f64, direction: f64, min: f64, max: f64) -> (f64, f64) {
+    let tmin_numerator = min - origin;
+    let tmax_numerator = max - origin;
+
+    let (tmin, tmax) = if direction.abs() >= INTERSECTION_EPSILON {
+        (tmin_numerator / direction, tmax_numerator / direction)
+    } else {
+        (
+            tmin_numerator * f64::INFINITY,
+            tmax_numerator * f64::INFINITY,
+        )
+    };
+
+    if tmin > tmax {
+        (tmax, tmin)
+    } else {
+        (tmin, tmax)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bounds::Aabb;
+    use crate::object::Object;
+    use crate::shapes::spheres::Sphere;
+    use core::f64::consts::PI;
+    use matrices::IDENTITY;
+    use rays::Ray;
+    use transformations::MatrixTransformations;
+    use tuples::{point, vector};
+
+    fn unit_box() -> Aabb {
+        Aabb::new(point(-1.0, -1.0, -1.0), point(1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn adding_points_to_an_empty_bounding_box() {
+        let mut b = Aabb::default();
+        b.add_point(&point(-5.0, 2.0, 0.0));
+        b.add_point(&point(7.0, 0.0, -3.0));
+        assert_eq!(b.min, point(-5.0, 0.0, -3.0));
+        assert_eq!(b.max, point(7.0, 2.0, 0.0));
+    }
+
+    #[test]
+    fn adding_one_bounding_box_to_another() {
+        let mut a = Aabb::new(point(-5.0, -2.0, 0.0), point(7.0, 4.0, 4.0));
+        let b = Aabb::new(point(8.0, -7.0, -2.0), point(14.0, 2.0, 8.0));
+        a.add_box(&b);
+        assert_eq!(a.min, point(-5.0, -7.0, -2.0));
+        assert_eq!(a.max, point(14.0, 4.0, 8.0));
+    }
+
+    #[test]
+    fn checking_whether_a_box_contains_a_point() {
+        let b = Aabb::new(point(5.0, -2.0, 0.0), point(11.0, 4.0, 7.0));
+        assert!(b.contains_point(&point(5.0, -2.0, 0.0)));
+        assert!(b.contains_point(&point(11.0, 4.0, 7.0)));
+        assert!(b.contains_point(&point(8.0, 1.0, 3.0)));
+        assert!(!b.contains_point(&point(3.0, 0.0, 3.0)));
+        assert!(!b.contains_point(&point(8.0, -4.0, 3.0)));
+        assert!(!b.contains_point(&point(8.0, 1.0, -1.0)));
+        assert!(!b.contains_point(&point(13.0, 1.0, 3.0)));
+        assert!(!b.contains_point(&point(8.0, 5.0, 3.0)));
+        assert!(!b.contains_point(&point(8.0, 1.0, 8.0)));
+    }
+
+    #[test]
+    fn checking_whether_a_box_contains_another_box() {
+        let b = Aabb::new(point(5.0, -2.0, 0.0), point(11.0, 4.0, 7.0));
+        assert!(b.contains_box(&Aabb::new(point(5.0, -2.0, 0.0), point(11.0, 4.0, 7.0))));
+        assert!(b.contains_box(&Aabb::new(point(6.0, -1.0, 1.0), point(10.0, 3.0, 6.0))));
+        assert!(!b.contains_box(&Aabb::new(point(4.0, -3.0, -1.0), point(10.0, 3.0, 6.0))));
+        assert!(!b.contains_box(&Aabb::new(point(6.0, -1.0, 1.0), point(12.0, 5.0, 8.0))));
+    }
+
+    #[test]
+    fn splitting_a_box_along_its_longest_axis() {
+        let cases = [
+            (
+                Aabb::new(point(-1.0, -4.0, -5.0), point(9.0, 6.0, 5.0)),
+                point(4.0, 6.0, 5.0),
+                point(4.0, -4.0, -5.0),
+            ),
+            (
+                Aabb::new(point(-1.0, -2.0, -3.0), point(9.0, 5.5, 3.0)),
+                point(4.0, 5.5, 3.0),
+                point(4.0, -2.0, -3.0),
+            ),
+            (
+                Aabb::new(point(-1.0, -2.0, -3.0), point(5.0, 8.0, 3.0)),
+                point(5.0, 3.0, 3.0),
+                point(-1.0, 3.0, -3.0),
+            ),
+            (
+                Aabb::new(point(-1.0, -2.0, -3.0), point(5.0, 3.0, 7.0)),
+                point(5.0, 3.0, 2.0),
+                point(-1.0, -2.0, 2.0),
+            ),
+        ];
+        for (b, left_max, right_min) in cases.iter() {
+            let (left, right) = b.split();
+            assert_eq!(left.min, b.min);
+            assert_eq!(left.max, *left_max);
+            assert_eq!(right.min, *right_min);
+            assert_eq!(right.max, b.max);
+        }
+    }
+
+    #[test]
+    fn transforming_a_bounding_box() {
+        let b = Aabb::new(point(-1.0, -1.0, -1.0), point(1.0, 1.0, 1.0));
+        let b2 = b.transform(&IDENTITY.rotate_y(PI / 4.0).rotate_x(PI / 4.0));
+        let sqrt2 = 2.0_f64.sqrt();
+        assert_eq!(b2.min, point(-sqrt2, -1.7071, -1.7071));
+        assert_eq!(b2.max, point(sqrt2, 1.7071, 1.7071));
+    }
+
+    #[test]
+    fn transforming_an_unbounded_box_keeps_it_unbounded() {
+        let plane = Aabb::new(
+            point(f64::NEG_INFINITY, 0.0, f64::NEG_INFINITY),
+            point(f64::INFINITY, 0.0, f64::INFINITY),
+        );
+        let b = plane.transform(&IDENTITY.rotate_x(PI / 4.0));
+        assert!(b.contains_point(&point(0.0, 100.0, 0.0)));
+        assert!(Aabb::default().transform(&IDENTITY).is_empty());
+    }
+
+    #[test]
+    fn querying_a_transformed_spheres_bounds_in_parent_space() {
+        let mut s = Object::new(Box::new(Sphere::default()));
+        s.transform = IDENTITY.scale(0.5, 2.0, 4.0).translate(1.0, -3.0, 5.0);
+        let b = s.parent_space_bounds();
+        assert_eq!(b.min, point(0.5, -5.0, 1.0));
+        assert_eq!(b.max, point(1.5, -1.0, 9.0));
+    }
+
+    #[test]
+    fn a_ray_through_a_box_returns_entry_and_exit() {
+        let b = unit_box();
+        let cases = [
+            (point(5.0, 0.5, 0.0), vector(-1.0, 0.0, 0.0), 4.0, 6.0),
+            (point(-5.0, 0.5, 0.0), vector(1.0, 0.0, 0.0), 4.0, 6.0),
+            (point(0.5, 5.0, 0.0), vector(0.0, -1.0, 0.0), 4.0, 6.0),
+            (point(0.5, 0.0, -5.0), vector(0.0, 0.0, 1.0), 4.0, 6.0),
+            (point(0.0, 0.5, 0.0), vector(0.0, 0.0, 1.0), -1.0, 1.0),
+        ];
+        for (origin, direction, t1, t2) in cases.iter() {
+            let r = Ray::new(*origin, *direction);
+            assert_eq!(b.intersect(&r), Some((*t1, *t2)));
+        }
+    }
+
+    #[test]
+    fn a_ray_misses_a_box() {
+        let b = unit_box();
+        let cases = [
+            (point(-2.0, 0.0, 0.0), vector(0.2673, 0.5345, 0.8018)),
+            (point(0.0, -2.0, 0.0), vector(0.8018, 0.2673, 0.5345)),
+            (point(2.0, 0.0, 2.0), vector(0.0, 0.0, -1.0)),
+            (point(2.0, 2.0, 0.0), vector(-1.0, 0.0, 0.0)),
+        ];
+        for (origin, direction) in cases.iter() {
+            let r = Ray::new(*origin, *direction);
+            assert_eq!(b.intersect(&r), None);
+        }
+    }
+}
@@ -1,9 +1,15 @@
-use crate::{World, RAY_LIMIT};
+use crate::intersections::IntersectionPool;
+use crate::rng::Rng;
+use crate::World;
 use canvas::Canvas;
+use colors::Color;
+use core::f64::consts::PI;
 use matrices::{inverse, matrix_tuple_multiply, IDENTITY};
 use rays::Ray;
-use tuples::{normalize, point};
+use transformations::{view_transform, MatrixTransformations};
+use tuples::{normalize, point, vector, Tuple, EPSILON};
 
+#[derive(Debug, PartialEq)]
 pub struct Camera {
     pub hsize: i32,
     pub vsize: i32,
@@ -12,10 +18,62 @@ pub struct Camera {
     pub field_of_view: f64,
     pub transform: [[f64; 4]; 4],
     pub pixel_size: f64,
+    pub near: f64,
+    pub far: f64,
+    pub aperture: f64,
+    pub focal_distance: f64,
+    pub seed: u64,
+}
+
+// Where the sub-pixel samples of an anti-aliased render land. Jittered
+// moves each grid cell's sample by a random amount drawn from the camera
+// seed, so a render is reproducible for a given seed. Halton spreads the samples over
+// the low-discrepancy sequence in bases 2 and 3.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SamplingPattern {
+    Grid,
+    Jittered,
+    Halton,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CameraConfig {
+    pub hsize: i32,
+    pub vsize: i32,
+    pub field_of_view: f64,
+    pub transform: [f64; 16],
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub near: f64,
+    #[cfg_attr(feature = "serde", serde(default = "default_far"))]
+    pub far: f64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub aperture: f64,
+    #[cfg_attr(feature = "serde", serde(default = "default_focal_distance"))]
+    pub focal_distance: f64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub seed: u64,
+}
+
+// Scene files written before these fields existed load as a pinhole
+// camera with no clipping, like Camera::new.
+#[cfg(feature = "serde")]
+fn default_far() -> f64 {
+    f64::INFINITY
+}
+
+#[cfg(feature = "serde")]
+fn default_focal_distance() -> f64 {
+    1.0
 }
 
 impl Camera {
+    // Sizes below one pixel are raised to one and the field of view is
+    // clamped to the open range (0, π), where its tangent is meaningful.
     pub fn new(hsize: i32, vsize: i32, field_of_view: f64) -> Camera {
+        let hsize = hsize.max(1);
+        let vsize = vsize.max(1);
+        let field_of_view = field_of_view.clamp(EPSILON, PI - EPSILON);
         let half_view = (field_of_view / 2.0).tan();
         let aspect = hsize as f64 / vsize as f64;
         let mut half_width = half_view * aspect;
@@ -34,49 +92,409 @@ impl Camera {
             field_of_view,
             transform: IDENTITY,
             pixel_size: (half_width * 2.0) / hsize as f64,
+            near: 0.0,
+            far: f64::INFINITY,
+            aperture: 0.0,
+            focal_distance: 1.0,
+            seed: 0,
+        }
+    }
+
+    pub fn looking_at(
+        hsize: i32,
+        vsize: i32,
+        field_of_view: f64,
+        from: &Tuple,
+        to: &Tuple,
+    ) -> Camera {
+        let mut camera = Camera::new(hsize, vsize, field_of_view);
+        camera.transform = view_transform(from, to, &vector(0.0, 1.0, 0.0));
+        camera
+    }
+
+    pub fn from_config(config: &CameraConfig) -> Camera {
+        let mut camera = Camera::new(config.hsize, config.vsize, config.field_of_view);
+        for row in 0..4 {
+            for col in 0..4 {
+                camera.transform[row][col] = config.transform[row * 4 + col];
+            }
+        }
+        camera.near = config.near;
+        camera.far = config.far;
+        camera.aperture = config.aperture;
+        camera.focal_distance = config.focal_distance;
+        camera.seed = config.seed;
+        camera
+    }
+
+    pub fn to_config(&self) -> CameraConfig {
+        let mut transform = [0.0; 16];
+        for row in 0..4 {
+            for col in 0..4 {
+                transform[row * 4 + col] = self.transform[row][col];
+            }
+        }
+        CameraConfig {
+            hsize: self.hsize,
+            vsize: self.vsize,
+            field_of_view: self.field_of_view,
+            transform,
+            near: self.near,
+            far: self.far,
+            aperture: self.aperture,
+            focal_distance: self.focal_distance,
+            seed: self.seed,
         }
     }
 
     pub fn ray_for_pixel(&self, px: i32, py: i32) -> Ray {
-        let xoffset = (px as f64 + 0.5) * self.pixel_size;
-        let yoffset = (py as f64 + 0.5) * self.pixel_size;
+        self.ray_for_subpixel(px, py, 0.5, 0.5)
+    }
+
+    pub fn ray_for_subpixel(&self, px: i32, py: i32, dx: f64, dy: f64) -> Ray {
+        let xoffset = (px as f64 + dx) * self.pixel_size;
+        let yoffset = (py as f64 + dy) * self.pixel_size;
 
         let world_x = self.half_width - xoffset;
         let world_y = self.half_height - yoffset;
 
-        let inv_camera_transform = inverse(&self.transform);
+        let inv_camera_transform =
+            inverse(&self.transform).expect("camera transform is not invertible");
 
         let pixel = matrix_tuple_multiply(&inv_camera_transform, &point(world_x, world_y, -1.0));
         let origin = matrix_tuple_multiply(&inv_camera_transform, &point(0.0, 0.0, 0.0));
         let direction = normalize(&(pixel - origin));
 
-        Ray { origin, direction }
+        Ray::new(origin, direction)
+    }
+
+    // Sample k of n spreads the ray origin over the lens disk (a Vogel
+    // spiral) and aims it at the same point on the focal plane, so only
+    // objects away from focal_distance blur.
+    pub fn lens_ray_for_pixel(&self, px: i32, py: i32, k: u32, n: u32) -> Ray {
+        self.rotated_lens_ray(px, py, k, n, 0.0)
+    }
+
+    // The spiral is turned by rotation radians, which render_dof draws per
+    // pixel from the camera seed to trade banding for noise.
+    fn rotated_lens_ray(&self, px: i32, py: i32, k: u32, n: u32, rotation: f64) -> Ray {
+        if self.aperture == 0.0 {
+            return self.ray_for_pixel(px, py);
+        }
+
+        let xoffset = (px as f64 + 0.5) * self.pixel_size;
+        let yoffset = (py as f64 + 0.5) * self.pixel_size;
+
+        let world_x = self.half_width - xoffset;
+        let world_y = self.half_height - yoffset;
+
+        let radius = self.aperture / 2.0 * ((k as f64 + 0.5) / n as f64).sqrt();
+        let theta = k as f64 * PI * (3.0 - 5.0_f64.sqrt()) + rotation;
+
+        let inv_camera_transform =
+            inverse(&self.transform).expect("camera transform is not invertible");
+
+        let focal_point = matrix_tuple_multiply(
+            &inv_camera_transform,
+            &point(
+                world_x * self.focal_distance,
+                world_y * self.focal_distance,
+                -self.focal_distance,
+            ),
+        );
+        let origin = matrix_tuple_multiply(
+            &inv_camera_transform,
+            &point(radius * theta.cos(), radius * theta.sin(), 0.0),
+        );
+        let direction = normalize(&(focal_point - origin));
+
+        Ray::new(origin, direction)
+    }
+
+    pub fn render_dof(&self, world: &World, samples: u32) -> Canvas {
+        let mut image = Canvas::new(self.hsize, self.vsize);
+        let pool = IntersectionPool::new();
+        let samples = samples.max(1);
+
+        for y in 0..self.vsize {
+            for x in 0..self.hsize {
+                let rotation = Rng::for_pixel(self.seed, x, y).next_f64() * 2.0 * PI;
+                let color = (0..samples)
+                    .map(|k| {
+                        let ray = self.rotated_lens_ray(x, y, k, samples, rotation);
+                        world.color_at_within_pooled(&ray, self.near, self.far, &pool)
+                    })
+                    .fold(Color::new(0.0, 0.0, 0.0), |sum, color| sum + color);
+                image.write_pixel(x as usize, y as usize, color * (1.0 / samples as f64));
+            }
+        }
+
+        image
+    }
+
+    // Averages samples rays per pixel spread evenly over the shutter
+    // interval [0, 1), so moving objects smear along their path.
+    pub fn render_motion_blur(&self, world: &World, samples: u32) -> Canvas {
+        let mut image = Canvas::new(self.hsize, self.vsize);
+        let pool = IntersectionPool::new();
+        let samples = samples.max(1);
+
+        for y in 0..self.vsize {
+            for x in 0..self.hsize {
+                let color = (0..samples)
+                    .map(|k| {
+                        let time = (k as f64 + 0.5) / samples as f64;
+                        let ray = self.ray_for_pixel(x, y).with_time(time);
+                        world.color_at_within_pooled(&ray, self.near, self.far, &pool)
+                    })
+                    .fold(Color::new(0.0, 0.0, 0.0), |sum, color| sum + color);
+                image.write_pixel(x as usize, y as usize, color * (1.0 / samples as f64));
+            }
+        }
+
+        image
+    }
+
+    // Averages samples x samples rays per pixel placed by the given pattern.
+    pub fn render_antialiased(
+        &self,
+        world: &World,
+        samples: u32,
+        pattern: SamplingPattern,
+    ) -> Canvas {
+        let mut image = Canvas::new(self.hsize, self.vsize);
+        let pool = IntersectionPool::new();
+        let samples = samples.max(1);
+        let count = samples * samples;
+
+        for y in 0..self.vsize {
+            for x in 0..self.hsize {
+                let mut random = Rng::for_pixel(self.seed, x, y);
+                let mut color = Color::new(0.0, 0.0, 0.0);
+                for k in 0..count {
+                    let (dx, dy) = match pattern {
+                        SamplingPattern::Grid => (
+                            ((k % samples) as f64 + 0.5) / samples as f64,
+                            ((k / samples) as f64 + 0.5) / samples as f64,
+                        ),
+                        SamplingPattern::Jittered => (
+                            ((k % samples) as f64 + random.next_f64()) / samples as f64,
+                            ((k / samples) as f64 + random.next_f64()) / samples as f64,
+                        ),
+                        SamplingPattern::Halton => (halton(k + 1, 2), halton(k + 1, 3)),
+                    };
+                    let ray = self.ray_for_subpixel(x, y, dx, dy);
+                    color += world.color_at_within_pooled(&ray, self.near, self.far, &pool);
+                }
+                image.write_pixel(x as usize, y as usize, color * (1.0 / count as f64));
+            }
+        }
+
+        image
+    }
+
+    // Samples the four corners of each pixel and only splits it into
+    // quadrants while the corners differ by more than threshold in some
+    // channel, up to max_depth times, so extra rays go to the edges.
+    pub fn render_adaptive(&self, world: &World, threshold: f64, max_depth: u32) -> Canvas {
+        let mut image = Canvas::new(self.hsize, self.vsize);
+        let pool = IntersectionPool::new();
+
+        for y in 0..self.vsize {
+            for x in 0..self.hsize {
+                let (color, _) = self.adaptive_pixel(world, &pool, x, y, threshold, max_depth);
+                image.write_pixel(x as usize, y as usize, color);
+            }
+        }
+
+        image
+    }
+
+    // Returns the pixel color together with the number of rays cast for it.
+    fn adaptive_pixel<'a>(
+        &self,
+        world: &'a World,
+        pool: &IntersectionPool<'a>,
+        px: i32,
+        py: i32,
+        threshold: f64,
+        max_depth: u32,
+    ) -> (Color, u32) {
+        let mut rays = 0;
+        let mut sample = |dx: f64, dy: f64| {
+            rays += 1;
+            let ray = self.ray_for_subpixel(px, py, dx, dy);
+            world.color_at_within_pooled(&ray, self.near, self.far, pool)
+        };
+        let corners = [
+            sample(0.0, 0.0),
+            sample(1.0, 0.0),
+            sample(0.0, 1.0),
+            sample(1.0, 1.0),
+        ];
+        let color = refine(
+            &mut sample,
+            (0.0, 0.0, 1.0, 1.0),
+            corners,
+            threshold,
+            max_depth,
+        );
+        (color, rays)
     }
 
     pub fn render(&self, world: &World) -> Canvas {
+        self.render_with_progress(world, |_, _| {})
+    }
+
+    pub fn render_with_progress(&self, world: &World, on_row: impl FnMut(i32, i32)) -> Canvas {
+        self.render_rows(world, &IntersectionPool::new(), on_row)
+    }
+
+    pub fn render_pooled<'a>(&self, world: &'a World, pool: &IntersectionPool<'a>) -> Canvas {
+        self.render_rows(world, pool, |_, _| {})
+    }
+
+    fn render_rows<'a>(
+        &self,
+        world: &'a World,
+        pool: &IntersectionPool<'a>,
+        mut on_row: impl FnMut(i32, i32),
+    ) -> Canvas {
         let mut image = Canvas::new(self.hsize, self.vsize);
 
         for y in 0..self.vsize {
             for x in 0..self.hsize {
                 let ray = self.ray_for_pixel(x, y);
-                let color = world.color_at(&ray, RAY_LIMIT);
+                let color = world.color_at_within_pooled(&ray, self.near, self.far, pool);
                 image.write_pixel(x as usize, y as usize, color);
             }
+            on_row(y + 1, self.vsize);
+        }
+
+        image
+    }
+
+    // The rectangle is clamped to the image, so the canvas covers the part
+    // of it that lies on the image, and is empty when nothing does or the
+    // corners are inverted.
+    pub fn render_region(&self, world: &World, x0: i32, y0: i32, x1: i32, y1: i32) -> Canvas {
+        let x0 = x0.clamp(0, self.hsize);
+        let y0 = y0.clamp(0, self.vsize);
+        let x1 = x1.clamp(x0, self.hsize);
+        let y1 = y1.clamp(y0, self.vsize);
+        let pool = IntersectionPool::new();
+        let mut image = Canvas::new(x1 - x0, y1 - y0);
+
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let ray = self.ray_for_pixel(x, y);
+                let color = world.color_at_within_pooled(&ray, self.near, self.far, &pool);
+                image.write_pixel((x - x0) as usize, (y - y0) as usize, color);
+            }
         }
 
         image
     }
+
+    // A run of pixels in row-major order, so callers that paint the image
+    // progressively can ask for batches instead of single pixels. The run is
+    // cut short at the end of the image.
+    pub fn render_chunk(&self, world: &World, start_index: usize, count: usize) -> Vec<Color> {
+        let pool = IntersectionPool::new();
+        let total = (self.hsize * self.vsize) as usize;
+        let width = self.hsize as usize;
+
+        (start_index.min(total)..start_index.saturating_add(count).min(total))
+            .map(|index| {
+                let ray = self.ray_for_pixel((index % width) as i32, (index / width) as i32);
+                world.color_at_within_pooled(&ray, self.near, self.far, &pool)
+            })
+            .collect()
+    }
+
+    pub fn render_stereo(&self, world: &World, eye_separation: f64) -> (Canvas, Canvas) {
+        let eye = |offset: f64| {
+            let mut camera = Camera::new(self.hsize, self.vsize, self.field_of_view);
+            camera.transform = self.transform.translate(offset, 0.0, 0.0);
+            camera.near = self.near;
+            camera.far = self.far;
+            camera.render(world)
+        };
+        (eye(-eye_separation / 2.0), eye(eye_separation / 2.0))
+    }
+}
+
+// Corners are ordered top left, top right, bottom left, bottom right.
+fn refine(
+    sample: &mut impl FnMut(f64, f64) -> Color,
+    (x0, y0, x1, y1): (f64, f64, f64, f64),
+    corners: [Color; 4],
+    threshold: f64,
+    depth: u32,
+) -> Color {
+    let spread = |channel: fn(&Color) -> f64| {
+        let values = corners.iter().map(channel);
+        let max = values.clone().fold(f64::NEG_INFINITY, f64::max);
+        let min = values.fold(f64::INFINITY, f64::min);
+        max - min
+    };
+    let difference = spread(|c| c.red)
+        .max(spread(|c| c.green))
+        .max(spread(|c| c.blue));
+    if depth == 0 || difference <= threshold {
+        return (corners[0] + corners[1] + corners[2] + corners[3]) * 0.25;
+    }
+
+    let [top_left, top_right, bottom_left, bottom_right] = corners;
+    let (mx, my) = ((x0 + x1) / 2.0, (y0 + y1) / 2.0);
+    let top = sample(mx, y0);
+    let left = sample(x0, my);
+    let center = sample(mx, my);
+    let right = sample(x1, my);
+    let bottom = sample(mx, y1);
+    let quadrants = [
+        ((x0, y0, mx, my), [top_left, top, left, center]),
+        ((mx, y0, x1, my), [top, top_right, center, right]),
+        ((x0, my, mx, y1), [left, center, bottom_left, bottom]),
+        ((mx, my, x1, y1), [center, right, bottom, bottom_right]),
+    ];
+    let mut color = Color::new(0.0, 0.0, 0.0);
+    for (bounds, corners) in quadrants.iter() {
+        color += refine(sample, *bounds, *corners, threshold, depth - 1);
+    }
+    color * 0.25
+}
+
+fn halton(index: u32, base: u32) -> f64 {
+    let mut result = 0.0;
+    let mut fraction = 1.0;
+    let mut index = index;
+    while index > 0 {
+        fraction /= base as f64;
+        result += fraction * (index % base) as f64;
+        index /= base;
+    }
+    result
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{camera::Camera, World};
+    use crate::{
+        camera::{halton, Camera, CameraConfig, SamplingPattern},
+        intersections::IntersectionPool,
+        materials::Material,
+        object::Object,
+        shapes::planes::Plane,
+        World,
+    };
+    use canvas::canvas_to_ppm;
     use colors::Color;
     use core::f64::consts::PI;
     use float_cmp::ApproxEq;
     use matrices::IDENTITY;
+    use std::rc::Rc;
     use transformations::{view_transform, MatrixTransformations};
-    use tuples::{point, vector};
+    use tuples::{normalize, point, vector};
 
     #[test]
     fn constructing_a_camera() {
@@ -88,6 +506,21 @@ mod tests {
         assert_eq!(c.vsize, vsize);
         assert_eq!(c.field_of_view, field_of_view);
         assert_eq!(c.transform, IDENTITY);
+        assert_eq!(c.near, 0.0);
+        assert_eq!(c.far, f64::INFINITY);
+    }
+
+    #[test]
+    fn degenerate_camera_parameters_are_clamped() {
+        let c = Camera::new(0, -3, PI / 2.0);
+        assert_eq!((c.hsize, c.vsize), (1, 1));
+        assert!(c.pixel_size.is_finite());
+
+        for fov in &[0.0, -1.0, PI, 4.0] {
+            let c = Camera::new(10, 10, *fov);
+            assert!(c.field_of_view > 0.0 && c.field_of_view < PI);
+            assert!(c.half_width.is_finite() && c.half_width > 0.0);
+        }
     }
 
     #[test]
@@ -126,7 +559,7 @@ mod tests {
         assert_eq!(r.origin, point(0.0, 2.0, -5.0));
         assert_eq!(
             r.direction,
-            vector((2.0 as f64).sqrt() / 2.0, 0.0, -(2.0 as f64).sqrt() / 2.0)
+            vector(2.0_f64.sqrt() / 2.0, 0.0, -2.0_f64.sqrt() / 2.0)
         );
     }
 
@@ -144,4 +577,372 @@ mod tests {
             Color::new(0.38066, 0.47583, 0.2855)
         );
     }
+
+    #[test]
+    fn rendering_quadrant_regions_matches_a_full_render() {
+        let w = World::default();
+        let mut c = Camera::new(11, 9, PI / 2.0);
+        c.transform = view_transform(
+            &point(0.0, 0.0, -5.0),
+            &point(0.0, 0.0, 0.0),
+            &vector(0.0, 1.0, 0.0),
+        );
+        let full = c.render(&w);
+        let mut tiled = canvas::Canvas::new(11, 9);
+        for &(x0, y0, x1, y1) in &[(0, 0, 5, 4), (5, 0, 11, 4), (0, 4, 5, 9), (5, 4, 11, 9)] {
+            let region = c.render_region(&w, x0, y0, x1, y1);
+            assert_eq!((region.width, region.height), (x1 - x0, y1 - y0));
+            tiled.blit(&region, x0, y0);
+        }
+        assert_eq!(tiled.pixels, full.pixels);
+    }
+
+    #[test]
+    fn regions_are_clamped_to_the_image() {
+        let w = World::default();
+        let c = default_world_camera();
+        let full = c.render(&w);
+        let clipped = c.render_region(&w, 8, -3, 20, 2);
+        assert_eq!((clipped.width, clipped.height), (3, 2));
+        assert_eq!(clipped.pixel_at(0, 0), full.pixel_at(8, 0));
+        assert_eq!(clipped.pixel_at(2, 1), full.pixel_at(10, 1));
+        let inverted = c.render_region(&w, 5, 5, 2, 7);
+        assert_eq!((inverted.width, inverted.height), (0, 2));
+        assert!(c.render_region(&w, 20, 20, 30, 30).is_empty());
+    }
+
+    #[test]
+    fn motion_blur_without_motion_matches_the_static_render() {
+        let mut w = World::default();
+        let moving = Object::new(Box::new(crate::shapes::spheres::Sphere::default())).with_motion(
+            IDENTITY.translate(1.5, 0.0, 0.0).scale(0.5, 0.5, 0.5),
+            IDENTITY.translate(1.5, 0.0, 0.0).scale(0.5, 0.5, 0.5),
+        );
+        w.objects.push(moving);
+        let c = Camera::looking_at(
+            11,
+            11,
+            PI / 2.0,
+            &point(0.0, 0.0, -5.0),
+            &point(0.0, 0.0, 0.0),
+        );
+        assert_eq!(c.render_motion_blur(&w, 4).pixels, c.render(&w).pixels);
+    }
+
+    fn default_world_camera() -> Camera {
+        Camera::looking_at(
+            11,
+            11,
+            PI / 2.0,
+            &point(0.0, 0.0, -5.0),
+            &point(0.0, 0.0, 0.0),
+        )
+    }
+
+    #[test]
+    fn rendered_chunks_concatenate_to_the_full_render() {
+        let w = World::default();
+        let c = default_world_camera();
+        let chunks = (0..121)
+            .step_by(10)
+            .flat_map(|start| c.render_chunk(&w, start, 10))
+            .collect::<Vec<_>>();
+        assert_eq!(chunks, c.render(&w).pixels);
+        assert!(c.render_chunk(&w, 121, 10).is_empty());
+    }
+
+    #[test]
+    fn a_chunk_with_an_unbounded_count_renders_the_rest_of_the_image() {
+        let w = World::default();
+        let c = default_world_camera();
+        let rest = c.render_chunk(&w, 100, usize::MAX);
+        assert_eq!(rest, c.render(&w).pixels[100..]);
+    }
+
+    #[test]
+    fn a_single_grid_sample_matches_the_plain_render() {
+        let w = World::default();
+        let c = default_world_camera();
+        assert_eq!(
+            c.render_antialiased(&w, 1, SamplingPattern::Grid).pixels,
+            c.render(&w).pixels
+        );
+    }
+
+    #[test]
+    fn jittered_sampling_is_reproducible_for_a_seed() {
+        let w = World::default();
+        let jittered = |seed| {
+            let mut c = default_world_camera();
+            c.seed = seed;
+            c.render_antialiased(&w, 2, SamplingPattern::Jittered)
+        };
+        assert_eq!(jittered(7).pixels, jittered(7).pixels);
+        assert_ne!(jittered(7).pixels, jittered(8).pixels);
+    }
+
+    #[test]
+    fn depth_of_field_is_reproducible_for_a_seed() {
+        let w = World::default();
+        let blurred = |seed| {
+            let mut c = default_world_camera();
+            c.aperture = 0.5;
+            c.focal_distance = 2.0;
+            c.seed = seed;
+            canvas_to_ppm(&c.render_dof(&w, 3))
+        };
+        assert_eq!(blurred(1), blurred(1));
+        assert_ne!(blurred(1), blurred(2));
+    }
+
+    #[test]
+    fn halton_sequence_fills_the_unit_interval() {
+        assert_eq!(halton(1, 2), 0.5);
+        assert_eq!(halton(2, 2), 0.25);
+        assert_eq!(halton(3, 2), 0.75);
+        assert_eq!(halton(1, 3), 1.0 / 3.0);
+        assert_eq!(halton(2, 3), 2.0 / 3.0);
+        let w = World::default();
+        let image = default_world_camera().render_antialiased(&w, 2, SamplingPattern::Halton);
+        assert_eq!(image.pixels.len(), 121);
+    }
+
+    #[test]
+    fn adaptive_sampling_keeps_flat_pixels_at_their_corners() {
+        let w = World::default();
+        let c = default_world_camera();
+        let pool = IntersectionPool::new();
+        let (color, rays) = c.adaptive_pixel(&w, &pool, 0, 0, 0.01, 3);
+        assert_eq!(color, Color::new(0.0, 0.0, 0.0));
+        assert_eq!(rays, 4);
+    }
+
+    #[test]
+    fn adaptive_sampling_subdivides_pixels_on_a_sphere_edge() {
+        let w = World::default();
+        let c = default_world_camera();
+        let pool = IntersectionPool::new();
+        let rays: Vec<u32> = (0..c.hsize)
+            .map(|x| c.adaptive_pixel(&w, &pool, x, 5, 0.01, 2).1)
+            .collect();
+        assert!(rays.iter().any(|&n| n > 4));
+        assert!(rays.iter().all(|&n| n <= 4 + 5 + 4 * 5));
+        assert_eq!(rays[0], 4);
+        assert_eq!(c.render_adaptive(&w, 0.01, 2).pixels.len(), 121);
+    }
+
+    #[test]
+    fn a_camera_looking_at_a_point() {
+        let from = point(0.0, 1.5, -5.0);
+        let to = point(0.0, 1.0, 0.0);
+        let c = Camera::looking_at(100, 50, PI / 3.0, &from, &to);
+        let mut expected = Camera::new(100, 50, PI / 3.0);
+        expected.transform = view_transform(&from, &to, &vector(0.0, 1.0, 0.0));
+        assert_eq!(c, expected);
+    }
+
+    #[test]
+    fn a_near_clipping_plane_reveals_the_object_behind() {
+        let w = World::default();
+        let mut c = Camera::looking_at(
+            11,
+            11,
+            PI / 2.0,
+            &point(0.0, 0.0, -5.0),
+            &point(0.0, 0.0, 0.0),
+        );
+        c.near = 4.2;
+        let image = c.render(&w);
+        // Only the inner sphere is left, lit by ambient alone inside the outer one.
+        assert_eq!(*image.pixel_at(5, 5).unwrap(), Color::new(0.1, 0.1, 0.1));
+    }
+
+    #[test]
+    fn a_far_clipping_plane_shows_the_background() {
+        let w = World::default();
+        let mut c = Camera::looking_at(
+            11,
+            11,
+            PI / 2.0,
+            &point(0.0, 0.0, -5.0),
+            &point(0.0, 0.0, 0.0),
+        );
+        c.far = 3.0;
+        let image = c.render(&w);
+        assert_eq!(*image.pixel_at(5, 5).unwrap(), w.background);
+    }
+
+    #[test]
+    fn rendering_a_stereo_pair_shifts_the_foreground() {
+        let w = World::default();
+        let c = Camera::looking_at(
+            11,
+            11,
+            PI / 2.0,
+            &point(0.0, 0.0, -5.0),
+            &point(0.0, 0.0, 0.0),
+        );
+        let (left, right) = c.render_stereo(&w, 1.0);
+        assert_ne!(left.pixels, right.pixels);
+        assert_ne!(left.pixels, c.render(&w).pixels);
+    }
+
+    #[test]
+    fn a_stereo_pair_without_separation_is_identical() {
+        let w = World::default();
+        let c = Camera::looking_at(
+            11,
+            11,
+            PI / 2.0,
+            &point(0.0, 0.0, -5.0),
+            &point(0.0, 0.0, 0.0),
+        );
+        let (left, right) = c.render_stereo(&w, 0.0);
+        assert_eq!(left.pixels, right.pixels);
+        assert_eq!(left.pixels, c.render(&w).pixels);
+    }
+
+    #[test]
+    fn saving_and_loading_a_camera_configuration() {
+        let mut c = Camera::new(201, 101, PI / 3.0);
+        c.transform = view_transform(
+            &point(1.0, 3.0, 2.0),
+            &point(4.0, -2.0, 8.0),
+            &vector(1.0, 1.0, 0.0),
+        );
+        c.near = 0.5;
+        c.far = 40.0;
+        c.aperture = 0.25;
+        c.focal_distance = 6.0;
+        c.seed = 42;
+        let config = c.to_config();
+        assert_eq!(
+            (
+                config.near,
+                config.far,
+                config.aperture,
+                config.focal_distance,
+                config.seed
+            ),
+            (0.5, 40.0, 0.25, 6.0, 42)
+        );
+        assert_eq!(config.hsize, 201);
+        assert_eq!(config.vsize, 101);
+        assert_eq!(config.field_of_view, PI / 3.0);
+        assert_eq!(config.transform[3], c.transform[0][3]);
+        let loaded = Camera::from_config(&config);
+        assert_eq!(loaded, c);
+        let r1 = c.ray_for_pixel(100, 50);
+        let r2 = loaded.ray_for_pixel(100, 50);
+        assert_eq!(r1.origin, r2.origin);
+        assert_eq!(r1.direction, r2.direction);
+    }
+
+    #[test]
+    fn camera_configuration_transform_is_row_major() {
+        let config = CameraConfig {
+            hsize: 160,
+            vsize: 120,
+            field_of_view: PI / 2.0,
+            transform: [
+                1.0, 0.0, 0.0, 2.0, 0.0, 1.0, 0.0, 3.0, 0.0, 0.0, 1.0, 4.0, 0.0, 0.0, 0.0, 1.0,
+            ],
+            near: 0.0,
+            far: f64::INFINITY,
+            aperture: 0.0,
+            focal_distance: 1.0,
+            seed: 0,
+        };
+        let c = Camera::from_config(&config);
+        assert_eq!(c.transform, IDENTITY.translate(2.0, 3.0, 4.0));
+        assert_eq!(c.to_config(), config);
+    }
+
+    #[test]
+    fn a_camera_defaults_to_a_pinhole() {
+        let c = Camera::new(201, 101, PI / 2.0);
+        assert_eq!(c.aperture, 0.0);
+        for k in 0..4 {
+            let r = c.lens_ray_for_pixel(100, 50, k, 4);
+            let pinhole = c.ray_for_pixel(100, 50);
+            assert_eq!(r.origin, pinhole.origin);
+            assert_eq!(r.direction, pinhole.direction);
+        }
+    }
+
+    #[test]
+    fn lens_rays_converge_on_the_focal_plane() {
+        let mut c = Camera::new(201, 101, PI / 2.0);
+        c.transform = IDENTITY.translate(0.0, -2.0, 5.0).rotate_y(PI / 4.0);
+        c.aperture = 0.5;
+        c.focal_distance = 3.0;
+        let pinhole = c.ray_for_pixel(100, 50);
+        let focus = pinhole.position(3.0);
+        for k in 0..8 {
+            let r = c.lens_ray_for_pixel(100, 50, k, 8);
+            assert_ne!(r.origin, pinhole.origin);
+            assert_eq!(normalize(&(focus - r.origin)), r.direction);
+        }
+    }
+
+    #[test]
+    fn depth_of_field_with_a_pinhole_matches_a_plain_render() {
+        let w = World::default();
+        let c = Camera::looking_at(
+            11,
+            11,
+            PI / 2.0,
+            &point(0.0, 0.0, -5.0),
+            &point(0.0, 0.0, 0.0),
+        );
+        assert_eq!(c.render_dof(&w, 4).pixels, c.render(&w).pixels);
+    }
+
+    #[test]
+    fn rendering_reports_progress_after_each_row() {
+        let w = World::default();
+        let c = Camera::new(5, 7, PI / 2.0);
+        let mut calls = vec![];
+        let image = c.render_with_progress(&w, |done, total| calls.push((done, total)));
+        assert_eq!(calls.len(), 7);
+        assert_eq!(calls[0], (1, 7));
+        assert_eq!(calls.last(), Some(&(7, 7)));
+        assert_eq!(image.pixels, c.render(&w).pixels);
+    }
+
+    #[test]
+    fn rendering_with_a_shared_pool_matches_rendering_without_one() {
+        let mut w = World::default();
+        let mut floor = Object::new(Box::new(Plane::default()));
+        floor.transform = IDENTITY.translate(0.0, -1.0, 0.0);
+        floor.material = Rc::new(Material {
+            reflective: Color::from(0.5),
+            ..Material::default()
+        });
+        w.objects.push(floor);
+        let c = Camera::looking_at(
+            21,
+            21,
+            PI / 2.0,
+            &point(0.0, 1.0, -5.0),
+            &point(0.0, 0.0, 0.0),
+        );
+        let pool = IntersectionPool::new();
+        let image = c.render_pooled(&w, &pool);
+        for y in 0..c.vsize {
+            for x in 0..c.hsize {
+                let ray = c.ray_for_pixel(x, y);
+                assert_eq!(
+                    image.pixel_at(x as usize, y as usize),
+                    Some(&w.color_at_within(&ray, c.near, c.far))
+                );
+            }
+        }
+        // A second frame finds every list it needs in the pool, already
+        // large enough, so it neither creates nor grows any of them.
+        let (lists, capacity) = (pool.free_lists(), pool.free_capacity());
+        assert!(capacity > 0);
+        assert_eq!(c.render_pooled(&w, &pool).pixels, image.pixels);
+        assert_eq!((pool.free_lists(), pool.free_capacity()), (lists, capacity));
+    }
 }
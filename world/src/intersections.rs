@@ -1,11 +1,15 @@
 use crate::object::Object;
+use crate::INTERSECTION_EPSILON;
 use rays::Ray;
-use tuples::{dot, reflect, Tuple};
+use std::cell::RefCell;
+use tuples::{dot, normalize, reflect, Tuple};
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Intersection<'a> {
     pub t: f64,
     pub object: &'a Object,
+    pub u: Option<f64>,
+    pub v: Option<f64>,
 }
 
 pub struct Computations<'a> {
@@ -13,27 +17,73 @@ pub struct Computations<'a> {
     pub object: &'a Object,
     pub point: Tuple,
     pub over_point: Tuple,
+    pub under_point: Tuple,
     pub eyev: Tuple,
     pub normalv: Tuple,
     pub reflectv: Tuple,
     pub n1: f64,
     pub n2: f64,
     pub inside: bool,
+    pub time: f64,
+    pub u: Option<f64>,
+    pub v: Option<f64>,
+}
+
+impl<'a> Computations<'a> {
+    pub fn refracted_ray(&self) -> Option<Ray> {
+        let n_ratio = self.n1 / self.n2;
+        let cos_i = dot(&self.eyev, &self.normalv);
+        let sin2_t = n_ratio.powf(2.0) * (1.0 - cos_i.powf(2.0));
+
+        if sin2_t > 1.0 {
+            return None;
+        }
+
+        let cos_t = (1.0 - sin2_t).sqrt();
+        let direction = self.normalv * (n_ratio * cos_i - cos_t) - self.eyev * n_ratio;
+        Some(Ray::new(self.under_point, direction).with_time(self.time))
+    }
+
+    // How the light arriving at the hit divides between the reflected and
+    // the refracted ray. The two parts always add up to one.
+    pub fn fresnel_split(&self) -> (f64, f64) {
+        let reflectance = schlick(self);
+        (reflectance, 1.0 - reflectance)
+    }
 }
 
 impl<'a> Intersection<'a> {
     pub fn new(t: f64, object: &'a Object) -> Intersection<'a> {
-        Intersection { t, object }
+        Intersection {
+            t,
+            object,
+            u: None,
+            v: None,
+        }
+    }
+
+    pub fn with_uv(t: f64, object: &'a Object, u: f64, v: f64) -> Intersection<'a> {
+        Intersection {
+            t,
+            object,
+            u: Some(u),
+            v: Some(v),
+        }
     }
 
     pub fn prepare_computations(
         &self,
         ray: &Ray,
         relative_intersections: &[&Intersection],
-    ) -> Computations {
+    ) -> Computations<'a> {
         let world_point = ray.position(self.t);
         let eyev = -ray.direction;
-        let mut normalv = self.object.normal_at(&world_point);
+        let mut normalv = self
+            .object
+            .normal_at_time(&world_point, self.u, self.v, ray.time);
+        if let Some(perturb) = &self.object.material.normal_perturbation {
+            normalv = normalize(&perturb(&world_point, &normalv));
+        }
         let mut inside = false;
 
         if dot(&normalv, &eyev) < 0.0 {
@@ -43,18 +93,116 @@ impl<'a> Intersection<'a> {
 
         let reflectv = reflect(&ray.direction, &normalv);
 
+        let (n1, n2) = self.refractive_indices(relative_intersections);
+        let uv = self
+            .object
+            .shape
+            .uv_map(&self.object.world_to_object(&world_point));
+
         Computations {
             t: self.t,
             object: self.object,
             point: world_point,
-            over_point: world_point + normalv * 0.0001,
+            over_point: world_point + normalv * INTERSECTION_EPSILON,
+            under_point: world_point - normalv * INTERSECTION_EPSILON,
             eyev,
             normalv,
             reflectv,
-            n1: 0.0,
-            n2: 0.0,
+            n1,
+            n2,
             inside,
+            time: ray.time,
+            u: uv.map(|(u, _)| u),
+            v: uv.map(|(_, v)| v),
+        }
+    }
+
+    // Walks the intersections in t order keeping track of the objects the
+    // ray is currently inside of, so the indices on both sides of this hit
+    // can be read off the innermost container. Empty space is 1.0.
+    fn refractive_indices(&self, relative_intersections: &[&Intersection]) -> (f64, f64) {
+        let mut n1 = 1.0;
+        let mut n2 = 1.0;
+        let mut containers: Vec<&Object> = vec![];
+
+        for intersection in relative_intersections {
+            let is_hit = *intersection == self;
+
+            if is_hit {
+                if let Some(object) = containers.last() {
+                    n1 = object.material.refractive_index;
+                }
+            }
+
+            match containers
+                .iter()
+                .position(|o| o.includes(intersection.object))
+            {
+                Some(index) => {
+                    containers.remove(index);
+                }
+                None => containers.push(intersection.object),
+            }
+
+            if is_hit {
+                if let Some(object) = containers.last() {
+                    n2 = object.material.refractive_index;
+                }
+                break;
+            }
+        }
+
+        (n1, n2)
+    }
+}
+
+pub fn schlick(comps: &Computations) -> f64 {
+    let mut cos = dot(&comps.eyev, &comps.normalv);
+
+    if comps.n1 > comps.n2 {
+        let n = comps.n1 / comps.n2;
+        let sin2_t = n.powf(2.0) * (1.0 - cos.powf(2.0));
+
+        if sin2_t > 1.0 {
+            return 1.0;
         }
+
+        cos = (1.0 - sin2_t).sqrt();
+    }
+
+    let r0 = ((comps.n1 - comps.n2) / (comps.n1 + comps.n2)).powf(2.0);
+    r0 + (1.0 - r0) * (1.0 - cos).powf(5.0)
+}
+
+// Scratch intersection lists shared by every ray of a render. Lists are
+// handed out empty and keep their capacity when given back.
+#[derive(Default)]
+pub struct IntersectionPool<'a> {
+    free: RefCell<Vec<Vec<Intersection<'a>>>>,
+}
+
+impl<'a> IntersectionPool<'a> {
+    pub fn new() -> IntersectionPool<'a> {
+        IntersectionPool::default()
+    }
+
+    pub fn take(&self) -> Vec<Intersection<'a>> {
+        self.free.borrow_mut().pop().unwrap_or_default()
+    }
+
+    pub fn give_back(&self, mut intersections: Vec<Intersection<'a>>) {
+        intersections.clear();
+        self.free.borrow_mut().push(intersections);
+    }
+
+    // How many lists are waiting to be reused, and how many intersections
+    // they can hold between them without reallocating.
+    pub fn free_lists(&self) -> usize {
+        self.free.borrow().len()
+    }
+
+    pub fn free_capacity(&self) -> usize {
+        self.free.borrow().iter().map(|list| list.capacity()).sum()
     }
 }
 
@@ -64,42 +212,68 @@ pub fn hit<'a>(intersections: &'a [Intersection]) -> Option<&'a Intersection<'a>
         .filter(|x| x.t.is_sign_positive())
         .collect();
 
-    if positive_intersections.len() == 0 {
+    if positive_intersections.is_empty() {
         return None;
     }
 
     positive_intersections.sort_by(|a, b| a.t.partial_cmp(&b.t).unwrap());
-    Some(&positive_intersections[0])
+    Some(positive_intersections[0])
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::intersections::{hit, Intersection};
+    use crate::intersections::{hit, schlick, Intersection, IntersectionPool};
     use crate::materials::Material;
     use crate::object::Object;
     use crate::shapes::{planes::Plane, spheres::Sphere, test::TestShape};
+    use float_cmp::ApproxEq;
     use matrices::IDENTITY;
     use rays::Ray;
     use std::rc::Rc;
     use transformations::MatrixTransformations;
-    use tuples::{point, vector};
+    use tuples::{point, vector, EPSILON};
 
     #[test]
     fn intersection_encapsulates_t_and_object() {
         let shape = TestShape::default();
         let o = Object::new(Box::new(shape));
-        let i = Intersection { t: 3.5, object: &o };
+        let i = Intersection::new(3.5, &o);
         assert_eq!(i.t, 3.5);
         assert_eq!(i.object, &o);
     }
 
+    #[test]
+    fn computations_carry_sphere_texture_coordinates() {
+        let o = Object::new(Box::new(Sphere::default()))
+            .with_transform(IDENTITY.scale(2.0, 2.0, 2.0).translate(0.0, 0.0, 3.0));
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        let i = Intersection::new(6.0, &o);
+        let comps = i.prepare_computations(&r, &[&i]);
+        assert!(comps.u.unwrap().approx_eq(0.0, (0.0001, 2)));
+        assert!(comps.v.unwrap().approx_eq(0.5, (0.0001, 2)));
+
+        let top = Ray::new(point(0.0, 10.0, 3.0), vector(0.0, -1.0, 0.0));
+        let i = Intersection::new(8.0, &o);
+        let comps = i.prepare_computations(&top, &[&i]);
+        assert!(comps.v.unwrap().approx_eq(1.0, (0.0001, 2)));
+    }
+
+    #[test]
+    fn computations_have_no_texture_coordinates_without_a_mapping() {
+        let o = Object::new(Box::new(Plane::default()));
+        let r = Ray::new(point(0.0, 1.0, 0.0), vector(0.0, -1.0, 0.0));
+        let i = Intersection::new(1.0, &o);
+        let comps = i.prepare_computations(&r, &[&i]);
+        assert_eq!((comps.u, comps.v), (None, None));
+    }
+
     #[test]
     fn aggregating_intersections() {
         let shape = TestShape::default();
         let o = Object::new(Box::new(shape));
-        let i1 = Intersection { t: 1.0, object: &o };
-        let i2 = Intersection { t: 2.0, object: &o };
-        let xs = vec![i1, i2];
+        let i1 = Intersection::new(1.0, &o);
+        let i2 = Intersection::new(2.0, &o);
+        let xs = [i1, i2];
         assert_eq!(xs[0].t, 1.0);
         assert_eq!(xs[1].t, 2.0);
     }
@@ -108,8 +282,8 @@ mod tests {
     fn hit_when_all_intersections_have_positive_t() {
         let shape = TestShape::default();
         let o = Object::new(Box::new(shape));
-        let i1 = Intersection { t: 1.0, object: &o };
-        let i2 = Intersection { t: 2.0, object: &o };
+        let i1 = Intersection::new(1.0, &o);
+        let i2 = Intersection::new(2.0, &o);
         let xs = vec![i1, i2];
         let i = hit(&xs);
         assert_eq!(i.unwrap(), &xs[0]);
@@ -119,11 +293,8 @@ mod tests {
     fn hit_when_some_intersections_have_negative_t() {
         let shape = TestShape::default();
         let o = Object::new(Box::new(shape));
-        let i1 = Intersection {
-            t: -1.0,
-            object: &o,
-        };
-        let i2 = Intersection { t: 1.0, object: &o };
+        let i1 = Intersection::new(-1.0, &o);
+        let i2 = Intersection::new(1.0, &o);
         let xs = vec![i1, i2];
         let i = hit(&xs);
         assert_eq!(i.unwrap(), &xs[1]);
@@ -133,14 +304,8 @@ mod tests {
     fn hit_when_all_intersections_have_negative_t() {
         let shape = TestShape::default();
         let o = Object::new(Box::new(shape));
-        let i1 = Intersection {
-            t: -2.0,
-            object: &o,
-        };
-        let i2 = Intersection {
-            t: -1.0,
-            object: &o,
-        };
+        let i1 = Intersection::new(-2.0, &o);
+        let i2 = Intersection::new(-1.0, &o);
         let xs = vec![i1, i2];
         let i = hit(&xs);
         assert!(i.is_none());
@@ -150,13 +315,10 @@ mod tests {
     fn hit_is_always_the_lowest_nonnegative_intersection() {
         let shape = TestShape::default();
         let o = Object::new(Box::new(shape));
-        let i1 = Intersection { t: 5.0, object: &o };
-        let i2 = Intersection { t: 7.0, object: &o };
-        let i3 = Intersection {
-            t: -3.0,
-            object: &o,
-        };
-        let i4 = Intersection { t: 2.0, object: &o };
+        let i1 = Intersection::new(5.0, &o);
+        let i2 = Intersection::new(7.0, &o);
+        let i3 = Intersection::new(-3.0, &o);
+        let i4 = Intersection::new(2.0, &o);
         let xs = vec![i1, i2, i3, i4];
         let i = hit(&xs);
         assert_eq!(i.unwrap(), &xs[3]);
@@ -168,13 +330,13 @@ mod tests {
         let o = Object::new(Box::new(shape));
         let r = Ray::new(
             point(0.0, 1.0, -1.0),
-            vector(0.0, -(2.0 as f64).sqrt() / 2.0, (2.0 as f64).sqrt() / 2.0),
+            vector(0.0, -2.0_f64.sqrt() / 2.0, 2.0_f64.sqrt() / 2.0),
         );
-        let i = Intersection::new((2.0 as f64).sqrt(), &o);
+        let i = Intersection::new(2.0_f64.sqrt(), &o);
         let comps = i.prepare_computations(&r, &[&i]);
         assert_eq!(
             comps.reflectv,
-            vector(0.0, (2.0 as f64).sqrt() / 2.0, (2.0 as f64).sqrt() / 2.0)
+            vector(0.0, 2.0_f64.sqrt() / 2.0, 2.0_f64.sqrt() / 2.0)
         );
     }
 
@@ -224,4 +386,81 @@ mod tests {
         run_scenario(4, 2.5, 1.5);
         run_scenario(5, 1.5, 1.0);
     }
+
+    #[test]
+    fn n1_and_n2_default_to_empty_space_outside_any_container() {
+        let o = Sphere::glass();
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        let i = Intersection::new(4.0, &o);
+        let comps = i.prepare_computations(&r, &[]);
+        assert_eq!(comps.n1, 1.0);
+        assert_eq!(comps.n2, 1.0);
+        let xs = [Intersection::new(4.0, &o), Intersection::new(6.0, &o)];
+        let comps = xs[1].prepare_computations(&r, &xs.iter().collect::<Vec<_>>());
+        assert_eq!(comps.n1, 1.5);
+        assert_eq!(comps.n2, 1.0);
+    }
+
+    #[test]
+    fn schlick_approximation_under_total_internal_reflection() {
+        let shape = Sphere::glass();
+        let r = Ray::new(point(0.0, 0.0, 2.0_f64.sqrt() / 2.0), vector(0.0, 1.0, 0.0));
+        let xs = [
+            Intersection::new(-(2.0_f64.sqrt()) / 2.0, &shape),
+            Intersection::new(2.0_f64.sqrt() / 2.0, &shape),
+        ];
+        let comps = xs[1].prepare_computations(&r, &xs.iter().collect::<Vec<_>>());
+        assert_eq!(schlick(&comps), 1.0);
+    }
+
+    #[test]
+    fn schlick_approximation_with_a_perpendicular_viewing_angle() {
+        let shape = Sphere::glass();
+        let r = Ray::new(point(0.0, 0.0, 0.0), vector(0.0, 1.0, 0.0));
+        let xs = [
+            Intersection::new(-1.0, &shape),
+            Intersection::new(1.0, &shape),
+        ];
+        let comps = xs[1].prepare_computations(&r, &xs.iter().collect::<Vec<_>>());
+        assert!(schlick(&comps).approx_eq(0.04, (0.0001, 2)));
+    }
+
+    #[test]
+    fn fresnel_split_conserves_energy_and_grows_toward_grazing_angles() {
+        let shape = Sphere::glass();
+        let mut previous = 0.0;
+        for step in 0..10 {
+            let y = step as f64 * 0.11;
+            let r = Ray::new(point(0.0, y, -2.0), vector(0.0, 0.0, 1.0));
+            let xs = shape.intersect(&r);
+            let comps = xs[0].prepare_computations(&r, &xs.iter().collect::<Vec<_>>());
+            let (reflectance, transmittance) = comps.fresnel_split();
+            assert!((reflectance + transmittance - 1.0).abs() < EPSILON);
+            assert!(reflectance > previous);
+            previous = reflectance;
+        }
+    }
+
+    #[test]
+    fn schlick_approximation_with_small_angle_and_n2_greater_than_n1() {
+        let shape = Sphere::glass();
+        let r = Ray::new(point(0.0, 0.99, -2.0), vector(0.0, 0.0, 1.0));
+        let xs = [Intersection::new(1.8589, &shape)];
+        let comps = xs[0].prepare_computations(&r, &xs.iter().collect::<Vec<_>>());
+        assert!(schlick(&comps).approx_eq(0.48873, (0.0001, 2)));
+    }
+
+    #[test]
+    fn a_pool_reuses_lists_that_are_given_back() {
+        let s = Object::new(Box::new(Sphere::default()));
+        let pool = IntersectionPool::new();
+        let mut xs = pool.take();
+        xs.push(Intersection::new(1.0, &s));
+        pool.give_back(xs);
+        assert_eq!(pool.free_lists(), 1);
+        let xs = pool.take();
+        assert!(xs.is_empty());
+        assert!(xs.capacity() >= 1);
+        assert_eq!(pool.free_lists(), 0);
+    }
 }
@@ -1,103 +1,558 @@
+pub mod bounds;
 pub mod camera;
 pub mod intersections;
 pub mod materials;
+pub mod obj;
 pub mod object;
 pub mod patterns;
+pub mod rng;
 pub mod shapes;
 
-use crate::intersections::{hit, Computations, Intersection};
+use crate::camera::Camera;
+use crate::intersections::{hit, Computations, Intersection, IntersectionPool};
 use crate::materials::Material;
 use crate::object::Object;
+use crate::patterns::texture_map::{spherical_map, UvImagePattern, UvPattern};
 use crate::shapes::spheres::Sphere;
+use canvas::Canvas;
 use colors::Color;
-use lights::PointLight;
-use matrices::IDENTITY;
+use core::f64::consts::PI;
+use lights::{Light, PointLight};
+use matrices::{is_invertible, IDENTITY};
 use rays::Ray;
 use std::rc::Rc;
 use transformations::MatrixTransformations;
-use tuples::{magnitude, normalize, point, Tuple};
+use tuples::{cross, magnitude, normalize, point, vector, Tuple, EPSILON};
 
 pub const RAY_LIMIT: u32 = 5;
+// Shared by the shapes for rays parallel to a surface, and by the hit
+// computations for how far secondary rays start off the surface.
+pub const INTERSECTION_EPSILON: f64 = 0.0001;
+pub const CONTRIBUTION_EPSILON: f64 = 0.0001;
 
 pub struct World {
-    pub light_source: Option<PointLight>,
+    pub lights: Vec<Box<dyn Light>>,
     pub objects: Vec<Object>,
+    pub shadow_samples: u32,
+    pub shadow_radius: f64,
+    pub background: Color,
+    pub sky: Option<(Color, Color)>,
+    pub environment: Option<UvImagePattern>,
+    pub max_depth: u32,
+    pub transparent_shadows: bool,
+    pub fog_color: Color,
+    pub fog_density: f64,
 }
 
 impl World {
     pub fn new() -> World {
         World {
-            light_source: None,
+            lights: vec![],
             objects: vec![],
+            shadow_samples: 1,
+            shadow_radius: 0.0,
+            background: Color::new(0.0, 0.0, 0.0),
+            sky: None,
+            environment: None,
+            max_depth: RAY_LIMIT,
+            transparent_shadows: false,
+            fog_color: Color::new(0.0, 0.0, 0.0),
+            fog_density: 0.0,
         }
     }
 
-    pub fn intersect(&self, ray: &Ray) -> Vec<Intersection> {
+    pub fn remove_object(&mut self, id: u64) -> Option<Object> {
+        let index = self.objects.iter().position(|object| object.id == id)?;
+        Some(self.objects.remove(index))
+    }
+
+    pub fn intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
         let mut result = vec![];
-        for object in self.objects.iter() {
-            if let Some(intersections) = object.intersect(ray) {
-                result.extend_from_slice(&intersections);
+        self.intersect_into(ray, &mut result);
+        result
+    }
+
+    // Which objects a ray hits and at which t, in order, for debugging scenes.
+    pub fn intersect_debug(&self, ray: &Ray) -> Vec<(u64, f64)> {
+        self.intersect(ray)
+            .iter()
+            .map(|intersection| (intersection.object.id, intersection.t))
+            .collect()
+    }
+
+    // Problems that would otherwise show up as NaNs or black pixels in the
+    // render, one message per issue.
+    pub fn validate(&self) -> Vec<String> {
+        let mut warnings = vec![];
+        for (index, object) in self.objects.iter().enumerate() {
+            let m = &object.transform;
+            if !is_invertible(m) {
+                warnings.push(format!("object {}: transform is not invertible", index));
+            }
+            for (axis, name) in ["x", "y", "z"].iter().enumerate() {
+                let scale = magnitude(&vector(m[0][axis], m[1][axis], m[2][axis]));
+                if scale < EPSILON {
+                    warnings.push(format!("object {}: {} axis is scaled to zero", index, name));
+                }
             }
+            if object.material.refractive_index < 1.0 {
+                warnings.push(format!(
+                    "object {}: refractive index {} is less than 1",
+                    index, object.material.refractive_index
+                ));
+            }
+        }
+        warnings
+    }
+
+    // The computations for the nearest hit, as intersect, hit and
+    // prepare_computations would give them.
+    pub fn nearest_computations(&self, ray: &Ray) -> Option<Computations<'_>> {
+        self.nearest_visible(ray, 0.0, f64::INFINITY, &mut vec![])
+    }
+
+    // Finds the nearest hit between near and far with a single scan. Only
+    // a transparent hit needs the sorted intersections in front of it, to
+    // work out the refractive indices on either side; an opaque one never
+    // uses them.
+    fn nearest_visible<'a>(
+        &'a self,
+        ray: &Ray,
+        near: f64,
+        far: f64,
+        intersections: &mut Vec<Intersection<'a>>,
+    ) -> Option<Computations<'a>> {
+        intersections.clear();
+        for object in self.objects.iter() {
+            object.intersect_into(ray, intersections);
+        }
+        let is_visible = |i: &Intersection| i.t.is_sign_positive() && i.t >= near && i.t <= far;
+        let nearest = intersections
+            .iter()
+            .filter(|i| is_visible(i))
+            .fold(None, |nearest: Option<&Intersection>, i| match nearest {
+                Some(n) if n.t <= i.t => Some(n),
+                _ => Some(i),
+            })?
+            .clone();
+
+        if nearest
+            .object
+            .material
+            .transparency
+            .is_black(CONTRIBUTION_EPSILON)
+        {
+            return Some(nearest.prepare_computations(ray, &[&nearest]));
+        }
+
+        intersections.retain(|i| i.t < nearest.t + INTERSECTION_EPSILON);
+        intersections.sort_by(|a, b| a.t.partial_cmp(&b.t).unwrap());
+        order_coincident_intersections(intersections);
+        let visible = intersections.iter().find(|i| is_visible(i))?.clone();
+        Some(visible.prepare_computations(ray, &intersections.iter().collect::<Vec<_>>()))
+    }
+
+    // Clears and refills a caller-owned buffer so hot loops can reuse its
+    // allocation across rays.
+    pub fn intersect_into<'a>(&'a self, ray: &Ray, result: &mut Vec<Intersection<'a>>) {
+        result.clear();
+        for object in self.objects.iter() {
+            object.intersect_into(ray, result);
         }
         result.sort_by(|Intersection { t: ta, .. }, Intersection { t: tb, .. }| {
             ta.partial_cmp(tb).unwrap()
         });
-        result
+        order_coincident_intersections(result);
     }
 
     pub fn shade_hit(&self, comps: &Computations, remaining: u32) -> Color {
-        let surface = comps.object.material.lightning(
-            &comps.object,
-            &self.light_source.as_ref().unwrap(),
-            &comps.point,
-            &comps.eyev,
-            &comps.normalv,
-            self.is_shadowed(&comps.over_point),
-        );
-        let reflected = self.reflected_color(comps, remaining);
-        surface + reflected
+        self.shade_hit_pooled(
+            comps,
+            remaining,
+            LightSelection::All,
+            &IntersectionPool::new(),
+        )
     }
 
-    pub fn color_at(&self, ray: &Ray, remaining: u32) -> Color {
-        let intersections = self.intersect(ray);
+    fn shade_hit_pooled<'a>(
+        &'a self,
+        comps: &Computations,
+        remaining: u32,
+        lights: LightSelection,
+        pool: &IntersectionPool<'a>,
+    ) -> Color {
+        let mut surface = lights.unlit(comps.object.material.emissive);
+        // Without any light there is nothing to shadow or shade, so fall back to the ambient term.
+        if self.lights.is_empty() {
+            let material = &comps.object.material;
+            surface +=
+                lights.unlit(material.base_color(comps.object, &comps.point) * material.ambient);
+        }
+        for (index, light) in self.lights.iter().enumerate() {
+            if !lights.includes(index) {
+                continue;
+            }
+            let light = light.as_ref();
+            surface += comps.object.material.lightning(
+                comps.object,
+                light,
+                &comps.point,
+                &comps.eyev,
+                &comps.normalv,
+                1.0 - self.shadow_occlusion_pooled(&comps.over_point, light, comps.time, pool),
+            );
+        }
+        let reflected = self.reflected_color_pooled(comps, remaining, lights, pool);
+        let refracted = self.refracted_color_pooled(comps, remaining, lights, pool);
 
-        match hit(&intersections) {
-            Some(intersection) => {
-                let comps = intersection.prepare_computations(ray, &[intersection]);
-                self.shade_hit(&comps, remaining)
+        let material = &comps.object.material;
+        let color = if !material.reflective.is_black(CONTRIBUTION_EPSILON)
+            && !material.transparency.is_black(CONTRIBUTION_EPSILON)
+        {
+            let (reflectance, transmittance) = comps.fresnel_split();
+            surface + reflected * reflectance + refracted * transmittance
+        } else {
+            surface + reflected + refracted
+        };
+
+        match material.horizon_fade {
+            Some((start, end)) => {
+                let fade = ((comps.t - start) / (end - start)).clamp(0.0, 1.0);
+                color.lerp(&lights.unlit(self.background), fade)
             }
-            None => Color::new(0.0, 0.0, 0.0),
+            None => color,
+        }
+    }
+
+    pub fn color_at(&self, ray: &Ray) -> Color {
+        self.color_at_within(ray, 0.0, f64::INFINITY)
+    }
+
+    pub fn color_at_within(&self, ray: &Ray, near: f64, far: f64) -> Color {
+        self.color_at_within_pooled(ray, near, far, &IntersectionPool::new())
+    }
+
+    pub fn color_at_within_pooled<'a>(
+        &'a self,
+        ray: &Ray,
+        near: f64,
+        far: f64,
+        pool: &IntersectionPool<'a>,
+    ) -> Color {
+        self.trace(ray, near, far, self.max_depth, LightSelection::All, pool)
+    }
+
+    fn trace<'a>(
+        &'a self,
+        ray: &Ray,
+        near: f64,
+        far: f64,
+        remaining: u32,
+        lights: LightSelection,
+        pool: &IntersectionPool<'a>,
+    ) -> Color {
+        let mut intersections = pool.take();
+        let visible = self.nearest_visible(ray, near, far, &mut intersections);
+        pool.give_back(intersections);
+
+        match visible {
+            Some(comps) => {
+                let color = self.shade_hit_pooled(&comps, remaining, lights, pool);
+                self.apply_fog(color, comps.t * magnitude(&ray.direction), lights)
+            }
+            None => self.apply_fog(lights.unlit(self.sky_color(ray)), f64::INFINITY, lights),
+        }
+    }
+
+    // An environment image wraps around the scene as an equirectangular map
+    // looked up by the ray direction. Otherwise, with a sky the background
+    // is a gradient from the first color straight down to the second
+    // straight up.
+    fn sky_color(&self, ray: &Ray) -> Color {
+        if let Some(environment) = &self.environment {
+            let (u, v) = spherical_map(&ray.direction);
+            return environment.uv_pattern_at(u, v);
         }
+        match self.sky {
+            Some((bottom, top)) => {
+                let t = 0.5 * (normalize(&ray.direction).y + 1.0);
+                bottom.lerp(&top, t)
+            }
+            None => self.background,
+        }
+    }
+
+    // Exponential fog: the farther the hit, the closer the color gets to
+    // fog_color. Rays that miss everything are fully fogged.
+    fn apply_fog(&self, color: Color, distance: f64, lights: LightSelection) -> Color {
+        if self.fog_density <= 0.0 {
+            return color;
+        }
+        let fog = 1.0 - (-self.fog_density * distance).exp();
+        color.lerp(&lights.unlit(self.fog_color), fog)
+    }
+
+    pub fn color_at_batch(&self, rays: &[Ray]) -> Vec<Color> {
+        rays.iter().map(|ray| self.color_at(ray)).collect()
+    }
+
+    // Fraction of shadow rays that are blocked on their way to the light.
+    // With more than one sample the light position is spread over a disk of
+    // shadow_radius facing the point, which softens the shadow edges. Lights
+    // without a position, such as directional ones, always cast hard shadows.
+    pub fn shadow_occlusion(&self, point: &Tuple, light: &dyn Light) -> f64 {
+        self.shadow_occlusion_pooled(point, light, 0.0, &IntersectionPool::new())
     }
 
-    pub fn is_shadowed(&self, point: &Tuple) -> bool {
-        let v = self.light_source.as_ref().unwrap().position - *point;
-        let distance = magnitude(&v);
-        let direction = normalize(&v);
+    fn shadow_occlusion_pooled<'a>(
+        &'a self,
+        point: &Tuple,
+        light: &dyn Light,
+        time: f64,
+        pool: &IntersectionPool<'a>,
+    ) -> f64 {
+        let axis = light.direction_from(point);
+        let distance = light.distance_from(point);
 
-        let ray = Ray {
-            origin: *point,
-            direction,
+        if self.shadow_samples <= 1 || self.shadow_radius == 0.0 || distance.is_infinite() {
+            return self.blocked_fraction_pooled(point, &axis, distance, time, pool);
+        }
+
+        let light_position = *point + axis * distance;
+        let helper = if axis.x.abs() < 0.9 {
+            vector(1.0, 0.0, 0.0)
+        } else {
+            vector(0.0, 1.0, 0.0)
         };
+        let u = normalize(&cross(&axis, &helper));
+        let v = cross(&axis, &u);
+        let golden_angle = PI * (3.0 - 5.0_f64.sqrt());
 
-        let intersections = self.intersect(&ray);
+        let occluded: f64 = (0..self.shadow_samples)
+            .map(|k| {
+                let r = self.shadow_radius * ((k as f64 + 0.5) / self.shadow_samples as f64).sqrt();
+                let theta = k as f64 * golden_angle;
+                let sample = light_position + u * (r * theta.cos()) + v * (r * theta.sin());
+                let to_sample = sample - *point;
+                let direction = normalize(&to_sample);
+                self.blocked_fraction_pooled(point, &direction, magnitude(&to_sample), time, pool)
+            })
+            .sum();
 
-        match hit(&intersections) {
-            Some(intersection) => intersection.t < distance,
-            None => false,
+        occluded / self.shadow_samples as f64
+    }
+
+    pub fn is_shadowed(&self, point: &Tuple, light: &dyn Light) -> bool {
+        self.blocked_fraction_pooled(
+            point,
+            &light.direction_from(point),
+            light.distance_from(point),
+            0.0,
+            &IntersectionPool::new(),
+        ) > 0.0
+    }
+
+    // How much of the light is blocked along the ray towards it. Opaque
+    // occluders block everything; with transparent_shadows every surface
+    // crossed on the way only blocks (1 - transparency) of what is left.
+    fn blocked_fraction_pooled<'a>(
+        &'a self,
+        point: &Tuple,
+        direction: &Tuple,
+        distance: f64,
+        time: f64,
+        pool: &IntersectionPool<'a>,
+    ) -> f64 {
+        let ray = Ray::new(*point, *direction).with_time(time);
+
+        let mut intersections = pool.take();
+        self.intersect_into(&ray, &mut intersections);
+        let blocked = if self.transparent_shadows {
+            let transmitted = intersections
+                .iter()
+                .filter(|i| i.t.is_sign_positive() && i.t < distance)
+                .map(|i| {
+                    let transparency = i.object.material.transparency;
+                    ((transparency.red + transparency.green + transparency.blue) / 3.0)
+                        .clamp(0.0, 1.0)
+                })
+                .product::<f64>();
+            1.0 - transmitted
+        } else {
+            match hit(&intersections) {
+                Some(intersection) if intersection.t < distance => 1.0,
+                _ => 0.0,
+            }
+        };
+        pool.give_back(intersections);
+        blocked
+    }
+
+    // What a single light adds to the render. Together with the unlit
+    // contribution, the renders of every light add up to the full render.
+    pub fn render_light_contribution(&self, camera: &Camera, light_index: usize) -> Canvas {
+        self.render_selection(camera, LightSelection::Only(light_index))
+    }
+
+    // The part of the render no light is responsible for: emission, the
+    // background and sky seen on misses, and fog.
+    pub fn render_unlit_contribution(&self, camera: &Camera) -> Canvas {
+        self.render_selection(camera, LightSelection::Unlit)
+    }
+
+    fn render_selection(&self, camera: &Camera, lights: LightSelection) -> Canvas {
+        let pool = IntersectionPool::new();
+        let mut image = Canvas::new(camera.hsize, camera.vsize);
+        for y in 0..camera.vsize {
+            for x in 0..camera.hsize {
+                let ray = camera.ray_for_pixel(x, y);
+                let color =
+                    self.trace(&ray, camera.near, camera.far, self.max_depth, lights, &pool);
+                image.write_pixel(x as usize, y as usize, color);
+            }
         }
+        image
     }
 
     pub fn reflected_color(&self, comps: &Computations, remaining: u32) -> Color {
-        if remaining <= 0 || comps.object.material.reflective == 0.0 {
+        self.reflected_color_pooled(
+            comps,
+            remaining,
+            LightSelection::All,
+            &IntersectionPool::new(),
+        )
+    }
+
+    fn reflected_color_pooled<'a>(
+        &'a self,
+        comps: &Computations,
+        remaining: u32,
+        lights: LightSelection,
+        pool: &IntersectionPool<'a>,
+    ) -> Color {
+        if remaining == 0
+            || comps
+                .object
+                .material
+                .reflective
+                .is_black(CONTRIBUTION_EPSILON)
+        {
             return Color::new(0.0, 0.0, 0.0);
         }
 
-        let reflect_ray = Ray::new(comps.over_point, comps.reflectv);
-        let color = self.color_at(&reflect_ray, remaining - 1);
+        let reflect_ray = Ray::new(comps.over_point, comps.reflectv).with_time(comps.time);
+        let color = self.trace(
+            &reflect_ray,
+            0.0,
+            f64::INFINITY,
+            remaining - 1,
+            lights,
+            pool,
+        );
 
         color * comps.object.material.reflective
     }
+
+    pub fn refracted_color(&self, comps: &Computations, remaining: u32) -> Color {
+        self.refracted_color_pooled(
+            comps,
+            remaining,
+            LightSelection::All,
+            &IntersectionPool::new(),
+        )
+    }
+
+    fn refracted_color_pooled<'a>(
+        &'a self,
+        comps: &Computations,
+        remaining: u32,
+        lights: LightSelection,
+        pool: &IntersectionPool<'a>,
+    ) -> Color {
+        if remaining == 0
+            || comps
+                .object
+                .material
+                .transparency
+                .is_black(CONTRIBUTION_EPSILON)
+        {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+
+        match comps.refracted_ray() {
+            Some(refract_ray) => {
+                self.trace(
+                    &refract_ray,
+                    0.0,
+                    f64::INFINITY,
+                    remaining - 1,
+                    lights,
+                    pool,
+                ) * comps.object.material.transparency
+            }
+            None => Color::new(0.0, 0.0, 0.0),
+        }
+    }
+}
+
+// Which lights shade the surfaces a ray reaches. Emission, misses and the
+// fog and fade targets do not come from any light, so a single light's
+// selection leaves them out and the unlit one keeps only them; that way the
+// per-light renders and the unlit one add up to the full render.
+#[derive(Debug, Clone, Copy)]
+enum LightSelection {
+    All,
+    Only(usize),
+    Unlit,
+}
+
+impl LightSelection {
+    fn includes(self, index: usize) -> bool {
+        match self {
+            LightSelection::All => true,
+            LightSelection::Only(solo) => solo == index,
+            LightSelection::Unlit => false,
+        }
+    }
+
+    fn unlit(self, color: Color) -> Color {
+        match self {
+            LightSelection::Only(_) => Color::new(0.0, 0.0, 0.0),
+            LightSelection::All | LightSelection::Unlit => color,
+        }
+    }
+}
+
+// Objects with flush faces, such as a glass holding water, are left and
+// entered at the same t. Every hit is kept, but within a run of near-equal
+// hits the entries are moved ahead of the exits, so the refraction walk goes
+// from one medium straight into the next and each object is still entered
+// and left once.
+fn order_coincident_intersections<'a>(intersections: &mut [Intersection<'a>]) {
+    let mut containers: Vec<&'a Object> = vec![];
+    let mut start = 0;
+    while start < intersections.len() {
+        let mut end = start + 1;
+        while end < intersections.len()
+            && intersections[end].t - intersections[end - 1].t < INTERSECTION_EPSILON
+        {
+            end += 1;
+        }
+        let run = &mut intersections[start..end];
+        run.sort_by_key(|i| containers.iter().any(|o| o.includes(i.object)));
+        for intersection in run.iter() {
+            match containers
+                .iter()
+                .position(|o| o.includes(intersection.object))
+            {
+                Some(index) => {
+                    containers.remove(index);
+                }
+                None => containers.push(intersection.object),
+            }
+        }
+        start = end;
+    }
 }
 
 impl Default for World {
@@ -108,41 +563,57 @@ impl Default for World {
         };
         let s1 = Sphere::default();
         let mut o1 = Object::new(Box::new(s1));
-        let mut material = Material::default();
-        material.color = Color::new(0.8, 1.0, 0.6);
-        material.diffuse = 0.7;
-        material.specular = 0.2;
-        o1.material = Rc::new(material);
+        o1.material = Rc::new(Material {
+            color: Color::new(0.8, 1.0, 0.6),
+            diffuse: 0.7,
+            specular: 0.2,
+            ..Material::default()
+        });
         let s2 = Sphere::default();
         let mut o2 = Object::new(Box::new(s2));
         o2.transform = IDENTITY.scale(0.5, 0.5, 0.5);
         World {
-            light_source: Some(light),
+            lights: vec![Box::new(light)],
             objects: vec![o1, o2],
+            shadow_samples: 1,
+            shadow_radius: 0.0,
+            background: Color::new(0.0, 0.0, 0.0),
+            sky: None,
+            environment: None,
+            max_depth: RAY_LIMIT,
+            transparent_shadows: false,
+            fog_color: Color::new(0.0, 0.0, 0.0),
+            fog_density: 0.0,
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::intersections::Intersection;
-    use crate::materials::Material;
+    use crate::camera::Camera;
+    use crate::intersections::{hit, Intersection};
+    use crate::materials::{Material, NormalPerturbation};
     use crate::object::Object;
-    use crate::shapes::{planes::Plane, spheres::Sphere};
+    use crate::patterns::{
+        checkers::CheckersPatternShape, test::TestPatternShape, texture_map::UvImagePattern,
+        Pattern,
+    };
+    use crate::shapes::{planes::Plane, slabs::Slab, spheres::Sphere, test::TestShape};
     use crate::{World, RAY_LIMIT};
+    use canvas::Canvas;
     use colors::Color;
-    use lights::PointLight;
+    use lights::{DirectionalLight, PointLight};
     use matrices::IDENTITY;
     use rays::Ray;
     use std::rc::Rc;
     use transformations::MatrixTransformations;
-    use tuples::{point, vector};
+    use tuples::{dot, normalize, point, vector};
 
     #[test]
     fn creating_a_world() {
         let w = World::new();
         assert_eq!(w.objects, vec![]);
-        assert_eq!(w.light_source, None);
+        assert!(w.lights.is_empty());
     }
 
     #[test]
@@ -152,7 +623,12 @@ mod tests {
             position: point(-10.0, 10.0, -10.0),
             intensity: Color::new(1.0, 1.0, 1.0),
         };
-        assert_eq!(w.light_source, Some(light));
+        assert_eq!(w.lights.len(), 1);
+        assert_eq!(w.lights[0].intensity(), light.intensity);
+        assert_eq!(
+            w.lights[0].direction_from(&point(0.0, 0.0, 0.0)),
+            normalize(&(light.position - point(0.0, 0.0, 0.0)))
+        );
         assert_eq!(w.objects[0].material.color, Color::new(0.8, 1.0, 0.6));
         assert_eq!(w.objects[0].material.diffuse, 0.7);
         assert_eq!(w.objects[0].material.specular, 0.2);
@@ -162,10 +638,7 @@ mod tests {
     #[test]
     fn intersect_a_world_with_a_ray() {
         let w = World::default();
-        let r = Ray {
-            origin: point(0.0, 0.0, -5.0),
-            direction: vector(0.0, 0.0, 1.0),
-        };
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
         let xs = w.intersect(&r);
         assert_eq!(xs[0].t, 4.0);
         assert_eq!(xs[1].t, 4.5);
@@ -173,106 +646,452 @@ mod tests {
         assert_eq!(xs[3].t, 6.0);
     }
 
+    #[test]
+    fn debugging_the_intersections_of_a_ray() {
+        let w = World::default();
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        let outer = w.objects[0].id;
+        let inner = w.objects[1].id;
+        assert_eq!(
+            w.intersect_debug(&r),
+            vec![(outer, 4.0), (inner, 4.5), (inner, 5.5), (outer, 6.0)]
+        );
+    }
+
+    #[test]
+    fn intersecting_into_a_used_buffer_replaces_its_contents() {
+        let w = World::default();
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        let mut buffer = vec![Intersection::new(-1.0, &w.objects[1])];
+        w.intersect_into(&r, &mut buffer);
+        assert_eq!(buffer, w.intersect(&r));
+        let other = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 1.0, 0.0));
+        w.intersect_into(&other, &mut buffer);
+        assert!(buffer.is_empty());
+    }
+
     #[test]
     fn shading_and_intersection() {
         let w = World::default();
-        let r = Ray {
-            origin: point(0.0, 0.0, -5.0),
-            direction: vector(0.0, 0.0, 1.0),
-        };
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
         let object = &w.objects[0];
-        let i = Intersection { t: 4.0, object };
+        let i = Intersection::new(4.0, object);
         let comps = i.prepare_computations(&r, &[&i]);
         let c = w.shade_hit(&comps, RAY_LIMIT);
         assert_eq!(c, Color::new(0.38066, 0.47583, 0.2855));
     }
 
+    #[test]
+    fn validating_a_world_reports_degenerate_objects() {
+        let mut w = World::default();
+        assert!(w.validate().is_empty());
+        w.objects[1].transform = IDENTITY.scale(1.0, 0.0, 1.0);
+        w.objects[0].material = Rc::new(Material {
+            refractive_index: 0.5,
+            ..Material::default()
+        });
+        assert_eq!(
+            w.validate(),
+            vec![
+                "object 0: refractive index 0.5 is less than 1",
+                "object 1: transform is not invertible",
+                "object 1: y axis is scaled to zero",
+            ]
+        );
+    }
+
+    #[test]
+    fn nearest_computations_match_the_full_pipeline() {
+        let mut w = World::default();
+        w.objects[0].material = Rc::new(Material::glass());
+        let rays = [
+            Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0)),
+            Ray::new(point(0.0, 0.0, 0.0), vector(0.0, 0.0, 1.0)),
+            Ray::new(point(0.0, 0.0, 0.75), vector(0.0, 0.0, -1.0)),
+            Ray::new(point(0.0, 0.9, -5.0), vector(0.0, 0.0, 1.0)),
+            Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 1.0, 0.0)),
+        ];
+        for r in rays.iter() {
+            let xs = w.intersect(r);
+            let expected =
+                hit(&xs).map(|i| i.prepare_computations(r, &xs.iter().collect::<Vec<_>>()));
+            let fused = w.nearest_computations(r);
+            assert_eq!(fused.is_some(), expected.is_some());
+            if let (Some(fused), Some(expected)) = (fused, expected) {
+                assert_eq!(fused.object, expected.object);
+                assert_eq!(fused.t, expected.t);
+                assert_eq!(fused.point, expected.point);
+                assert_eq!(fused.normalv, expected.normalv);
+                if fused.object == &w.objects[0] {
+                    assert_eq!((fused.n1, fused.n2), (expected.n1, expected.n2));
+                }
+                assert_eq!(fused.inside, expected.inside);
+            }
+        }
+    }
+
+    #[test]
+    fn removing_an_object_by_id() {
+        let mut w = World::default();
+        let id = w.objects[1].id;
+        let removed = w.remove_object(id).unwrap();
+        assert_eq!(removed.id, id);
+        assert_eq!(w.objects.len(), 1);
+        assert_ne!(w.objects[0].id, id);
+        assert!(w.remove_object(id).is_none());
+    }
+
     #[test]
     fn color_when_a_ray_misses() {
         let w = World::default();
-        let r = Ray {
-            origin: point(0.0, 0.0, -5.0),
-            direction: vector(0.0, 1.0, 0.0),
-        };
-        let c = w.color_at(&r, RAY_LIMIT);
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 1.0, 0.0));
+        let c = w.color_at(&r);
         assert_eq!(c, Color::new(0.0, 0.0, 0.0));
     }
 
     #[test]
     fn color_when_ray_hits() {
         let w = World::default();
-        let r = Ray {
-            origin: point(0.0, 0.0, -5.0),
-            direction: vector(0.0, 0.0, 1.0),
-        };
-        let c = w.color_at(&r, RAY_LIMIT);
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        let c = w.color_at(&r);
         assert_eq!(c, Color::new(0.38066, 0.47583, 0.2855));
     }
 
     #[test]
     fn color_with_an_intersection_behind_the_ray() {
         let mut w = World::default();
-        let mut material = Material::default();
-        material.ambient = 1.0;
+        let material = Material {
+            ambient: 1.0,
+            ..Material::default()
+        };
         let material = Rc::new(material);
         w.objects[0].material = Rc::clone(&material);
         w.objects[1].material = Rc::clone(&material);
         let inner = &w.objects[1];
-        let r = Ray {
-            origin: point(0.0, 0.0, 0.75),
-            direction: vector(0.0, 0.0, -1.0),
-        };
-        let c = w.color_at(&r, RAY_LIMIT);
+        let r = Ray::new(point(0.0, 0.0, 0.75), vector(0.0, 0.0, -1.0));
+        let c = w.color_at(&r);
         assert_eq!(c, inner.material.color);
     }
 
+    #[test]
+    fn color_at_batch_matches_individual_color_at_calls() {
+        let w = World::default();
+        let rays = [
+            Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 1.0, 0.0)),
+            Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0)),
+            Ray::new(point(0.0, 0.0, 0.75), vector(0.0, 0.0, -1.0)),
+        ];
+        let colors = w.color_at_batch(&rays);
+        assert_eq!(colors.len(), 3);
+        for (ray, color) in rays.iter().zip(colors.iter()) {
+            assert_eq!(color, &w.color_at(ray));
+        }
+    }
+
+    #[test]
+    fn color_when_a_ray_misses_shows_the_background() {
+        let w = World {
+            background: Color::new(0.5, 0.7, 1.0),
+            ..World::default()
+        };
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 1.0, 0.0));
+        assert_eq!(w.color_at(&r), Color::new(0.5, 0.7, 1.0));
+    }
+
+    #[test]
+    fn an_environment_map_is_sampled_by_the_ray_direction() {
+        let mut image = Canvas::new(5, 5);
+        for y in 0..5 {
+            for x in 0..5 {
+                image.write_pixel(x, y, Color::new(x as f64 / 4.0, y as f64 / 4.0, 1.0));
+            }
+        }
+        let w = World {
+            environment: Some(UvImagePattern { canvas: image }),
+            ..World::new()
+        };
+        let up = Ray::new(point(0.0, 0.0, 0.0), vector(0.0, 1.0, 0.0));
+        let forward = Ray::new(point(0.0, 0.0, 0.0), vector(0.0, 0.0, 1.0));
+        assert_eq!(w.color_at(&up).green, 0.0);
+        assert_eq!(w.color_at(&forward), Color::new(0.5, 0.5, 1.0));
+    }
+
+    #[test]
+    fn a_sky_gradient_follows_the_ray_direction() {
+        let w = World {
+            sky: Some((Color::new(1.0, 1.0, 1.0), Color::new(0.5, 0.7, 1.0))),
+            ..World::new()
+        };
+        let up = Ray::new(point(0.0, 0.0, 0.0), vector(0.0, 1.0, 0.0));
+        let down = Ray::new(point(0.0, 0.0, 0.0), vector(0.0, -1.0, 0.0));
+        let level = Ray::new(point(0.0, 0.0, 0.0), vector(0.0, 0.0, 1.0));
+        assert_eq!(w.color_at(&up), Color::new(0.5, 0.7, 1.0));
+        assert_eq!(w.color_at(&down), Color::new(1.0, 1.0, 1.0));
+        assert_eq!(w.color_at(&level), Color::new(0.75, 0.85, 1.0));
+    }
+
+    #[test]
+    fn a_mirror_reflects_the_background() {
+        let mirror = |sky| {
+            let mut w = World {
+                background: Color::new(0.2, 0.4, 0.6),
+                sky,
+                ..World::new()
+            };
+            w.objects.push(
+                Object::new(Box::new(Plane::default())).with_material(Material {
+                    reflective: Color::from(1.0),
+                    ambient: 0.0,
+                    ..Material::default()
+                }),
+            );
+            w
+        };
+        let r = Ray::new(point(0.0, 1.0, -1.0), normalize(&vector(0.0, -1.0, 1.0)));
+        assert_eq!(mirror(None).color_at(&r), Color::new(0.2, 0.4, 0.6));
+        let sky = mirror(Some((Color::new(0.0, 0.0, 0.0), Color::new(1.0, 1.0, 1.0))));
+        let t = 0.5 * (2.0_f64.sqrt() / 2.0 + 1.0);
+        assert_eq!(sky.color_at(&r), Color::new(t, t, t));
+    }
+
+    #[test]
+    fn a_checkered_floor_fades_into_the_background_at_the_horizon() {
+        let floor_world = |horizon_fade| {
+            let mut w = World {
+                background: Color::new(0.5, 0.7, 1.0),
+                ..World::default()
+            };
+            let mut floor = Object::new(Box::new(Plane::default()));
+            floor.material = Rc::new(Material {
+                pattern: Some(Pattern::new(Box::new(CheckersPatternShape {
+                    a: Color::new(1.0, 1.0, 1.0),
+                    b: Color::new(0.0, 0.0, 0.0),
+                }))),
+                horizon_fade,
+                ..Material::default()
+            });
+            w.objects = vec![floor];
+            w
+        };
+        let faded = floor_world(Some((10.0, 100.0)));
+        let unfaded = floor_world(None);
+
+        let near = Ray::new(point(0.0, 1.0, 0.0), normalize(&vector(0.0, -1.0, 0.5)));
+        assert_eq!(faded.color_at(&near), unfaded.color_at(&near));
+
+        let grazing = Ray::new(point(0.0, 1.0, 0.0), normalize(&vector(0.0, -1.0, 55.0)));
+        let surface = unfaded.color_at(&grazing);
+        let fade = (55.0_f64.hypot(1.0) - 10.0) / 90.0;
+        let expected = surface * (1.0 - fade) + faded.background * fade;
+        assert_eq!(faded.color_at(&grazing), expected);
+
+        let distant = Ray::new(point(0.0, 1.0, 0.0), normalize(&vector(0.0, -1.0, 500.0)));
+        assert_eq!(faded.color_at(&distant), faded.background);
+    }
+
+    #[test]
+    fn emissive_surface_in_full_shadow_still_glows() {
+        let mut w = World::new();
+        w.lights = vec![Box::new(PointLight::new(
+            point(0.0, 0.0, -10.0),
+            Color::new(1.0, 1.0, 1.0),
+        ))];
+        let blocker = Object::new(Box::new(Sphere::default()));
+        let mut glowing = Object::new(Box::new(Sphere::default())).with_material(Material {
+            emissive: Color::new(0.8, 0.4, 0.0),
+            ..Material::default()
+        });
+        glowing.set_transform(IDENTITY.translate(0.0, 0.0, 10.0));
+        w.objects = vec![blocker, glowing];
+        let r = Ray::new(point(0.0, 0.0, 5.0), vector(0.0, 0.0, 1.0));
+        let i = Intersection::new(4.0, &w.objects[1]);
+        let comps = i.prepare_computations(&r, &[&i]);
+        assert!(w.is_shadowed(&comps.over_point, w.lights[0].as_ref()));
+        let c = w.shade_hit(&comps, RAY_LIMIT);
+        assert!(c.red >= 0.8 && c.green >= 0.4);
+        assert_eq!(c, Color::new(0.8, 0.4, 0.0) + Color::new(0.1, 0.1, 0.1));
+    }
+
+    #[test]
+    fn emissive_surface_glows_without_any_light() {
+        let mut w = World::new();
+        w.objects.push(
+            Object::new(Box::new(Sphere::default())).with_material(Material {
+                emissive: Color::new(0.0, 1.0, 0.0),
+                ambient: 0.0,
+                ..Material::default()
+            }),
+        );
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        assert_eq!(w.color_at(&r), Color::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn a_world_without_lights_renders_the_ambient_term() {
+        let mut w = World::new();
+        w.objects.push(
+            Object::new(Box::new(Sphere::default()))
+                .with_material(Material::default().with_color(Color::new(0.8, 1.0, 0.6))),
+        );
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        assert_eq!(w.color_at(&r), Color::new(0.08, 0.1, 0.06));
+        let miss = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 1.0, 0.0));
+        assert_eq!(w.color_at(&miss), Color::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn per_light_renders_add_up_to_the_full_render() {
+        let mut w = World::default();
+        w.lights.push(Box::new(PointLight::new(
+            point(10.0, 10.0, -10.0),
+            Color::new(0.5, 0.25, 0.5),
+        )));
+        w.background = Color::new(0.2, 0.3, 0.4);
+        w.fog_color = Color::new(0.5, 0.5, 0.5);
+        w.fog_density = 0.05;
+        w.objects[0].material = Rc::new(Material {
+            color: Color::new(0.8, 1.0, 0.6),
+            diffuse: 0.7,
+            specular: 0.2,
+            emissive: Color::new(0.3, 0.1, 0.0),
+            horizon_fade: Some((3.0, 6.0)),
+            ..Material::default()
+        });
+        let c = Camera::looking_at(
+            11,
+            11,
+            std::f64::consts::FRAC_PI_2,
+            &point(0.0, 0.5, -5.0),
+            &point(0.0, 0.0, 0.0),
+        );
+        let full = c.render(&w);
+        let first = w.render_light_contribution(&c, 0);
+        let second = w.render_light_contribution(&c, 1);
+        let unlit = w.render_unlit_contribution(&c);
+        assert_ne!(first.pixels, full.pixels);
+        assert_eq!(first.pixels[0], Color::new(0.0, 0.0, 0.0));
+        for i in 0..full.pixels.len() {
+            assert_eq!(
+                first.pixels[i] + second.pixels[i] + unlit.pixels[i],
+                full.pixels[i]
+            );
+        }
+        assert_eq!(c.render(&w).pixels, full.pixels);
+        Rc::make_mut(&mut w.objects[0].material).emissive = Color::new(0.0, 0.0, 0.0);
+        assert_ne!(w.render_unlit_contribution(&c).pixels, unlit.pixels);
+    }
+
     #[test]
     fn no_shadow_when_nothing_is_collinear_with_point_and_light() {
         let w = World::default();
         let p = point(0.0, 10.0, 0.0);
-        assert!(!w.is_shadowed(&p));
+        assert!(!w.is_shadowed(&p, w.lights[0].as_ref()));
+    }
+
+    #[test]
+    fn normal_perturbation_changes_the_shading_normal() {
+        let shade = |normal_perturbation| {
+            let mut w = World::default();
+            w.objects[0].material = Rc::new(Material {
+                color: Color::new(0.8, 1.0, 0.6),
+                diffuse: 0.7,
+                specular: 0.0,
+                normal_perturbation,
+                ..Material::default()
+            });
+            let r = Ray::new(point(0.0, 0.3, -5.0), vector(0.0, 0.0, 1.0));
+            w.color_at(&r)
+        };
+        let plain = shade(None);
+        let no_op: NormalPerturbation = Rc::new(|_, normal| *normal);
+        assert_eq!(shade(Some(no_op)), plain);
+        let bumpy: NormalPerturbation =
+            Rc::new(|p, normal| *normal + vector((p.y * 10.0).sin(), 0.0, 0.0));
+        assert_ne!(shade(Some(bumpy)), plain);
+    }
+
+    #[test]
+    fn fog_blends_distant_hits_towards_the_fog_color() {
+        let mut w = World::default();
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        let clear = w.color_at(&r);
+        w.fog_color = Color::new(0.5, 0.5, 0.5);
+        assert_eq!(w.color_at(&r), clear);
+        w.fog_density = 10.0;
+        assert!(w.color_at(&r).approx_eq(&w.fog_color, 1e-6));
+        let miss = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 1.0, 0.0));
+        assert_eq!(w.color_at(&miss), w.fog_color);
+        w.fog_density = 0.1;
+        let fog = 1.0 - (-0.4_f64).exp();
+        assert_eq!(
+            w.color_at(&r),
+            clear * (1.0 - fog) + Color::new(0.5, 0.5, 0.5) * fog
+        );
+    }
+
+    #[test]
+    fn transparent_occluders_cast_partial_shadows() {
+        let mut w = World::default();
+        let p = point(10.0, -10.0, 10.0);
+        w.objects[0].material = Rc::new(Material {
+            transparency: Color::from(0.5),
+            ..Material::glass()
+        });
+        w.objects[1].material = Rc::new(Material::glass());
+        assert_eq!(w.shadow_occlusion(&p, w.lights[0].as_ref()), 1.0);
+        w.transparent_shadows = true;
+        let occlusion = w.shadow_occlusion(&p, w.lights[0].as_ref());
+        assert!(occlusion > 0.0 && occlusion < 1.0);
+        assert_eq!(occlusion, 0.75);
+        assert!(w.is_shadowed(&p, w.lights[0].as_ref()));
+    }
+
+    #[test]
+    fn directional_light_is_blocked_by_objects_at_any_distance() {
+        let mut w = World::default();
+        let sun = DirectionalLight::new(vector(0.0, -1.0, 0.0), Color::new(1.0, 1.0, 1.0));
+        w.objects[0].set_transform(IDENTITY.translate(0.0, 1000.0, 0.0));
+        assert!(w.is_shadowed(&point(0.0, 0.0, 0.0), &sun));
+        assert!(!w.is_shadowed(&point(5.0, 0.0, 0.0), &sun));
+        w.shadow_samples = 16;
+        w.shadow_radius = 1.0;
+        assert_eq!(w.shadow_occlusion(&point(0.0, 0.0, 0.0), &sun), 1.0);
     }
 
     #[test]
     fn shadow_when_an_object_is_between_the_point_and_the_light() {
         let w = World::default();
         let p = point(10.0, -10.0, 10.0);
-        assert!(w.is_shadowed(&p));
+        assert!(w.is_shadowed(&p, w.lights[0].as_ref()));
     }
 
     #[test]
     fn no_shadow_when_an_object_is_behind_the_light() {
         let w = World::default();
         let p = point(-20.0, 20.0, -20.0);
-        assert!(!w.is_shadowed(&p));
+        assert!(!w.is_shadowed(&p, w.lights[0].as_ref()));
     }
 
     #[test]
     fn no_shadow_when_an_object_is_behind_the_point() {
         let w = World::default();
         let p = point(-2.0, 2.0, -2.0);
-        assert!(!w.is_shadowed(&p));
+        assert!(!w.is_shadowed(&p, w.lights[0].as_ref()));
     }
 
     #[test]
     fn shade_hit_is_given_an_intersection_in_shadow() {
         let mut w = World::new();
-        w.light_source = Some(PointLight {
+        w.lights = vec![Box::new(PointLight {
             position: point(0.0, 0.0, -10.0),
             intensity: Color::new(1.0, 1.0, 1.0),
-        });
+        })];
         let o1 = Object::new(Box::new(Sphere::default()));
         let mut o2 = Object::new(Box::new(Sphere::default()));
         o2.transform = IDENTITY.translate(0.0, 0.0, 10.0);
-        let r = Ray {
-            origin: point(0.0, 0.0, 5.0),
-            direction: vector(0.0, 0.0, 1.0),
-        };
+        let r = Ray::new(point(0.0, 0.0, 5.0), vector(0.0, 0.0, 1.0));
         w.objects = vec![o1, o2];
-        let i = Intersection {
-            t: 4.0,
-            object: &w.objects[1],
-        };
+        let i = Intersection::new(4.0, &w.objects[1]);
         let comps = i.prepare_computations(&r, &[&i]);
         let c = w.shade_hit(&comps, RAY_LIMIT);
         assert_eq!(c, Color::new(0.1, 0.1, 0.1));
@@ -282,8 +1101,10 @@ mod tests {
     fn reflected_color_for_a_nonreflective_material() {
         let mut w = World::default();
         let r = Ray::new(point(0.0, 0.0, 0.0), vector(0.0, 0.0, 1.0));
-        let mut material = Material::default();
-        material.ambient = 1.0;
+        let material = Material {
+            ambient: 1.0,
+            ..Material::default()
+        };
         let material = Rc::new(material);
         w.objects[1].material = Rc::clone(&material);
         let i = Intersection::new(1.0, &w.objects[1]);
@@ -298,37 +1119,112 @@ mod tests {
     fn reflected_color_for_a_reflective_material() {
         let mut w = World::default();
         let mut plane = Object::new(Box::new(Plane::default()));
-        let mut material = Material::default();
-        material.reflective = 0.5;
+        let material = Material {
+            reflective: Color::from(0.5),
+            ..Material::default()
+        };
         plane.material = Rc::new(material);
         plane.transform = IDENTITY.translate(0.0, -1.0, 0.0);
         w.objects.push(plane);
         let r = Ray::new(
             point(0.0, 0.0, -3.0),
-            vector(0.0, -(2.0 as f64).sqrt() / 2.0, (2.0 as f64).sqrt() / 2.0),
+            vector(0.0, -2.0_f64.sqrt() / 2.0, 2.0_f64.sqrt() / 2.0),
         );
-        let i = Intersection::new((2.0 as f64).sqrt(), &w.objects[2]);
+        let i = Intersection::new(2.0_f64.sqrt(), &w.objects[2]);
         let comps = i.prepare_computations(&r, &[&i]);
         assert_eq!(
             w.reflected_color(&comps, RAY_LIMIT),
-            Color::new(0.19032, 0.2379, 0.14274)
+            Color::new(0.19035, 0.23793, 0.14276)
         );
     }
 
+    #[test]
+    fn a_reflective_floor_does_not_reflect_itself() {
+        let mut w = World::default();
+        let floor = Object::new(Box::new(Plane::default())).with_material(Material {
+            reflective: Color::from(1.0),
+            ..Material::default()
+        });
+        w.objects
+            .push(floor.with_transform(IDENTITY.translate(0.0, -1.0, 0.0)));
+        for origin in &[point(0.0, 0.0, -3.0), point(1.0e5, 0.0, -1.0e5)] {
+            for slope in &[1.0, 0.1, 0.001] {
+                let r = Ray::new(*origin, normalize(&vector(0.3, -slope, 1.0)));
+                let xs = w.intersect(&r);
+                let floor_hit = xs.iter().find(|i| i.object == &w.objects[2]).unwrap();
+                let comps = floor_hit.prepare_computations(&r, &xs.iter().collect::<Vec<_>>());
+                let reflected = Ray::new(comps.over_point, comps.reflectv);
+                let xs = w.intersect(&reflected);
+                assert!(hit(&xs).is_none_or(|i| i.object != &w.objects[2]));
+            }
+        }
+    }
+
+    #[test]
+    fn reflected_color_for_a_red_mirror() {
+        let mut w = World::default();
+        let mut plane = Object::new(Box::new(Plane::default()));
+        let material = Material {
+            reflective: Color::new(1.0, 0.0, 0.0),
+            ..Material::default()
+        };
+        plane.material = Rc::new(material);
+        plane.transform = IDENTITY.translate(0.0, -1.0, 0.0);
+        w.objects.push(plane);
+        let r = Ray::new(
+            point(0.0, 0.0, -3.0),
+            vector(0.0, -2.0_f64.sqrt() / 2.0, 2.0_f64.sqrt() / 2.0),
+        );
+        let i = Intersection::new(2.0_f64.sqrt(), &w.objects[2]);
+        let comps = i.prepare_computations(&r, &[&i]);
+        assert_eq!(
+            w.reflected_color(&comps, RAY_LIMIT),
+            Color::new(0.3807, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn reflected_color_skips_recursion_for_a_negligible_reflective_factor() {
+        let mut w = World::default();
+        let mut plane = Object::new(Box::new(Plane::default()));
+        let material = Material {
+            reflective: Color::from(1e-9),
+            ..Material::default()
+        };
+        plane.material = Rc::new(material);
+        plane.transform = IDENTITY.translate(0.0, -1.0, 0.0);
+        w.objects.push(plane);
+        w.objects.push(Object::new(Box::new(TestShape::default())));
+        let r = Ray::new(
+            point(0.0, 0.0, -3.0),
+            vector(0.0, -2.0_f64.sqrt() / 2.0, 2.0_f64.sqrt() / 2.0),
+        );
+        let i = Intersection::new(2.0_f64.sqrt(), &w.objects[2]);
+        let comps = i.prepare_computations(&r, &[&i]);
+        assert_eq!(
+            w.reflected_color(&comps, RAY_LIMIT),
+            Color::new(0.0, 0.0, 0.0)
+        );
+        let probe = w.objects[3].shape_as::<TestShape>().unwrap();
+        assert_eq!(probe.intersect_calls.get(), 0);
+    }
+
     #[test]
     fn shade_hit_for_a_reflective_material() {
         let mut w = World::default();
         let mut plane = Object::new(Box::new(Plane::default()));
-        let mut material = Material::default();
-        material.reflective = 0.5;
+        let material = Material {
+            reflective: Color::from(0.5),
+            ..Material::default()
+        };
         plane.material = Rc::new(material);
         plane.transform = IDENTITY.translate(0.0, -1.0, 0.0);
         w.objects.push(plane);
         let r = Ray::new(
             point(0.0, 0.0, -3.0),
-            vector(0.0, -(2.0 as f64).sqrt() / 2.0, (2.0 as f64).sqrt() / 2.0),
+            vector(0.0, -2.0_f64.sqrt() / 2.0, 2.0_f64.sqrt() / 2.0),
         );
-        let i = Intersection::new((2.0 as f64).sqrt(), &w.objects[2]);
+        let i = Intersection::new(2.0_f64.sqrt(), &w.objects[2]);
         let comps = i.prepare_computations(&r, &[&i]);
         assert_eq!(
             w.shade_hit(&comps, RAY_LIMIT),
@@ -339,13 +1235,15 @@ mod tests {
     #[test]
     fn color_at_with_manually_reflective_surfaces() {
         let mut w = World::new();
-        w.light_source = Some(PointLight::new(
+        w.lights = vec![Box::new(PointLight::new(
             point(0.0, 0.0, 0.0),
             Color::new(1.0, 1.0, 1.0),
-        ));
+        ))];
         let material = {
-            let mut m = Material::default();
-            m.reflective = 0.5;
+            let m = Material {
+                reflective: Color::from(0.5),
+                ..Material::default()
+            };
             Rc::new(m)
         };
         let mut lower = Object::new(Box::new(Plane::default()));
@@ -357,24 +1255,305 @@ mod tests {
         upper.transform = IDENTITY.translate(0.0, 1.0, 0.0);
         w.objects.push(upper);
         let r = Ray::new(point(0.0, 0.0, 0.0), vector(0.0, 1.0, 0.0));
-        w.color_at(&r, RAY_LIMIT);
+        let reflected = w.color_at(&r);
+
+        w.max_depth = 0;
+        let surface = w.color_at(&r);
+        assert_eq!(surface, Color::new(1.9, 1.9, 1.9));
+        assert!(reflected.red > surface.red);
     }
 
     #[test]
     fn reflected_color_at_the_maximum_recursive_depth() {
         let mut w = World::default();
         let mut plane = Object::new(Box::new(Plane::default()));
-        let mut material = Material::default();
-        material.reflective = 0.5;
+        let material = Material {
+            reflective: Color::from(0.5),
+            ..Material::default()
+        };
         plane.material = Rc::new(material);
         plane.transform = IDENTITY.translate(0.0, -1.0, 0.0);
         w.objects.push(plane);
         let r = Ray::new(
             point(0.0, 0.0, -3.0),
-            vector(0.0, -(2.0 as f64).sqrt() / 2.0, (2.0 as f64).sqrt() / 2.0),
+            vector(0.0, -2.0_f64.sqrt() / 2.0, 2.0_f64.sqrt() / 2.0),
         );
-        let i = Intersection::new((2.0 as f64).sqrt(), &w.objects[2]);
+        let i = Intersection::new(2.0_f64.sqrt(), &w.objects[2]);
         let comps = i.prepare_computations(&r, &[&i]);
         assert_eq!(w.reflected_color(&comps, 0), Color::new(0.0, 0.0, 0.0));
     }
+
+    #[test]
+    fn refracted_color_with_an_opaque_surface() {
+        let w = World::default();
+        let shape = &w.objects[0];
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        let xs = [Intersection::new(4.0, shape), Intersection::new(6.0, shape)];
+        let comps = xs[0].prepare_computations(&r, &xs.iter().collect::<Vec<_>>());
+        assert_eq!(
+            w.refracted_color(&comps, RAY_LIMIT),
+            Color::new(0.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn refracted_color_at_the_maximum_recursive_depth() {
+        let mut w = World::default();
+        w.objects[0].material = Rc::new(Material::glass());
+        let shape = &w.objects[0];
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        let xs = [Intersection::new(4.0, shape), Intersection::new(6.0, shape)];
+        let comps = xs[0].prepare_computations(&r, &xs.iter().collect::<Vec<_>>());
+        assert_eq!(w.refracted_color(&comps, 0), Color::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn refracted_color_under_total_internal_reflection() {
+        let mut w = World::default();
+        w.objects[0].material = Rc::new(Material::glass());
+        let shape = &w.objects[0];
+        let r = Ray::new(point(0.0, 0.0, 2.0_f64.sqrt() / 2.0), vector(0.0, 1.0, 0.0));
+        let xs = [
+            Intersection::new(-(2.0_f64.sqrt()) / 2.0, shape),
+            Intersection::new(2.0_f64.sqrt() / 2.0, shape),
+        ];
+        let comps = xs[1].prepare_computations(&r, &xs.iter().collect::<Vec<_>>());
+        assert_eq!(
+            w.refracted_color(&comps, RAY_LIMIT),
+            Color::new(0.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn refracted_color_with_a_refracted_ray() {
+        let mut w = World::default();
+        w.objects[0].material = Rc::new(Material {
+            color: Color::new(0.8, 1.0, 0.6),
+            ambient: 1.0,
+            diffuse: 0.7,
+            specular: 0.2,
+            pattern: Some(Pattern::new(Box::new(TestPatternShape {}))),
+            ..Material::default()
+        });
+        w.objects[1].material = Rc::new(Material::glass());
+        let a = &w.objects[0];
+        let b = &w.objects[1];
+        let r = Ray::new(point(0.0, 0.0, 0.1), vector(0.0, 1.0, 0.0));
+        let xs = [
+            Intersection::new(-0.9899, a),
+            Intersection::new(-0.4899, b),
+            Intersection::new(0.4899, b),
+            Intersection::new(0.9899, a),
+        ];
+        let comps = xs[2].prepare_computations(&r, &xs.iter().collect::<Vec<_>>());
+        assert_eq!(
+            w.refracted_color(&comps, RAY_LIMIT),
+            Color::new(0.0, 0.99888, 0.04725)
+        );
+    }
+
+    #[test]
+    fn shade_hit_with_a_transparent_material() {
+        let mut w = World::default();
+        let mut floor = Object::new(Box::new(Plane::default()));
+        floor.transform = IDENTITY.translate(0.0, -1.0, 0.0);
+        floor.material = Rc::new(Material {
+            transparency: Color::from(0.5),
+            refractive_index: 1.5,
+            ..Material::default()
+        });
+        w.objects.push(floor);
+        let mut ball = Object::new(Box::new(Sphere::default()));
+        ball.transform = IDENTITY.translate(0.0, -3.5, -0.5);
+        ball.material = Rc::new(Material {
+            color: Color::new(1.0, 0.0, 0.0),
+            ambient: 0.5,
+            ..Material::default()
+        });
+        w.objects.push(ball);
+        let r = Ray::new(
+            point(0.0, 0.0, -3.0),
+            vector(0.0, -(2.0_f64.sqrt()) / 2.0, 2.0_f64.sqrt() / 2.0),
+        );
+        let xs = [Intersection::new(2.0_f64.sqrt(), &w.objects[2])];
+        let comps = xs[0].prepare_computations(&r, &xs.iter().collect::<Vec<_>>());
+        assert_eq!(
+            w.shade_hit(&comps, RAY_LIMIT),
+            Color::new(0.93642, 0.68642, 0.68642)
+        );
+    }
+
+    #[test]
+    fn coincident_planes_keep_a_hit_for_each_object() {
+        let mut w = World::new();
+        for _ in 0..2 {
+            w.objects.push(
+                Object::new(Box::new(Plane::default()))
+                    .with_transform(IDENTITY.translate(0.0, -1.0, 0.0)),
+            );
+        }
+        let r = Ray::new(point(0.0, 0.0, 0.0), vector(0.0, -1.0, 0.0));
+        let xs = w.intersect(&r);
+        assert_eq!(xs.iter().map(|i| i.t).collect::<Vec<_>>(), vec![1.0, 1.0]);
+        assert_eq!(xs[0].object, &w.objects[0]);
+        assert_eq!(xs[1].object, &w.objects[1]);
+    }
+
+    #[test]
+    fn refractive_indices_across_flush_transparent_objects() {
+        let mut w = World::new();
+        w.objects
+            .push(Object::new(Box::new(Slab::new(1.0))).with_material(Material::glass()));
+        w.objects.push(
+            Object::new(Box::new(Slab::new(1.0)))
+                .with_transform(IDENTITY.translate(0.0, -2.0, 0.0))
+                .with_material(Material {
+                    refractive_index: 1.333,
+                    ..Material::glass()
+                }),
+        );
+        let r = Ray::new(point(0.0, 5.0, 0.0), vector(0.0, -1.0, 0.0));
+        let xs = w.intersect(&r);
+        let relative = xs.iter().collect::<Vec<_>>();
+        let expected = [
+            (4.0, 1.0, 1.5),
+            (6.0, 1.5, 1.333),
+            (6.0, 1.333, 1.333),
+            (8.0, 1.333, 1.0),
+        ];
+        assert_eq!(xs.len(), expected.len());
+        for (x, &(t, n1, n2)) in xs.iter().zip(expected.iter()) {
+            let comps = x.prepare_computations(&r, &relative);
+            assert_eq!((comps.t, comps.n1, comps.n2), (t, n1, n2));
+        }
+    }
+
+    #[test]
+    fn distinct_surfaces_are_preserved_when_merging_intersections() {
+        let mut w = World::default();
+        let mut plane = Object::new(Box::new(Plane::default()));
+        plane.material = Rc::new(Material::glass());
+        w.objects.push(plane);
+        let r = Ray::new(point(0.0, 5.0, 0.0), vector(0.0, -1.0, 0.0));
+        let xs = w.intersect(&r);
+        assert_eq!(
+            xs.iter().map(|i| i.t).collect::<Vec<_>>(),
+            vec![4.0, 4.5, 5.0, 5.5, 6.0]
+        );
+        assert_eq!(xs[2].object, &w.objects[2]);
+
+        let tangent = Ray::new(point(0.0, 1.0, -5.0), vector(0.0, 0.0, 1.0));
+        let xs = w.intersect(&tangent);
+        assert_eq!(xs.iter().map(|i| i.t).collect::<Vec<_>>(), vec![5.0, 5.0]);
+    }
+
+    #[test]
+    fn shade_hit_with_a_reflective_transparent_material() {
+        let mut w = World::default();
+        let mut floor = Object::new(Box::new(Plane::default()));
+        floor.transform = IDENTITY.translate(0.0, -1.0, 0.0);
+        floor.material = Rc::new(Material {
+            reflective: Color::from(0.5),
+            transparency: Color::from(0.5),
+            refractive_index: 1.5,
+            ..Material::default()
+        });
+        w.objects.push(floor);
+        let mut ball = Object::new(Box::new(Sphere::default()));
+        ball.transform = IDENTITY.translate(0.0, -3.5, -0.5);
+        ball.material = Rc::new(Material {
+            color: Color::new(1.0, 0.0, 0.0),
+            ambient: 0.5,
+            ..Material::default()
+        });
+        w.objects.push(ball);
+        let r = Ray::new(
+            point(0.0, 0.0, -3.0),
+            vector(0.0, -(2.0_f64.sqrt()) / 2.0, 2.0_f64.sqrt() / 2.0),
+        );
+        let xs = [Intersection::new(2.0_f64.sqrt(), &w.objects[2])];
+        let comps = xs[0].prepare_computations(&r, &xs.iter().collect::<Vec<_>>());
+        assert_eq!(
+            w.shade_hit(&comps, RAY_LIMIT),
+            Color::new(0.93391, 0.69643, 0.69243)
+        );
+    }
+
+    #[test]
+    fn hard_shadows_are_fully_on_or_off() {
+        let mut w = World::new();
+        w.lights = vec![Box::new(PointLight::new(
+            point(0.0, 10.0, 0.0),
+            Color::new(1.0, 1.0, 1.0),
+        ))];
+        let mut ball = Object::new(Box::new(Sphere::default()));
+        ball.transform = IDENTITY.translate(0.0, 5.0, 0.0);
+        w.objects.push(ball);
+        assert_eq!(
+            w.shadow_occlusion(&point(0.0, 0.0, 0.0), w.lights[0].as_ref()),
+            1.0
+        );
+        assert_eq!(
+            w.shadow_occlusion(&point(2.2, 0.0, 0.0), w.lights[0].as_ref()),
+            0.0
+        );
+        w.shadow_radius = 1.0;
+        assert_eq!(
+            w.shadow_occlusion(&point(2.2, 0.0, 0.0), w.lights[0].as_ref()),
+            0.0
+        );
+        w.shadow_radius = 0.0;
+        w.shadow_samples = 16;
+        assert_eq!(
+            w.shadow_occlusion(&point(2.2, 0.0, 0.0), w.lights[0].as_ref()),
+            0.0
+        );
+    }
+
+    #[test]
+    fn soft_shadows_are_fractional_near_the_shadow_boundary() {
+        let mut w = World::new();
+        w.lights = vec![Box::new(PointLight::new(
+            point(0.0, 10.0, 0.0),
+            Color::new(1.0, 1.0, 1.0),
+        ))];
+        let mut ball = Object::new(Box::new(Sphere::default()));
+        ball.transform = IDENTITY.translate(0.0, 5.0, 0.0);
+        w.objects.push(ball);
+        w.shadow_samples = 16;
+        w.shadow_radius = 1.0;
+        let penumbra = w.shadow_occlusion(&point(2.2, 0.0, 0.0), w.lights[0].as_ref());
+        assert!(penumbra > 0.0 && penumbra < 1.0);
+        assert_eq!(
+            w.shadow_occlusion(&point(0.0, 0.0, 0.0), w.lights[0].as_ref()),
+            1.0
+        );
+        assert_eq!(
+            w.shadow_occlusion(&point(6.0, 0.0, 0.0), w.lights[0].as_ref()),
+            0.0
+        );
+    }
+
+    #[test]
+    fn refracted_rays_exiting_a_glass_sphere_swap_n1_and_n2() {
+        let mut w = World::new();
+        w.objects.push(Sphere::glass());
+        let r = Ray::new(point(0.0, 0.1, -5.0), vector(0.0, 0.0, 1.0));
+        let xs = w.intersect(&r);
+        let entry = hit(&xs)
+            .unwrap()
+            .prepare_computations(&r, &xs.iter().collect::<Vec<_>>());
+        assert!(!entry.inside);
+        assert_eq!((entry.n1, entry.n2), (1.0, 1.5));
+
+        let inner = entry.refracted_ray().unwrap();
+        let xs = w.intersect(&inner);
+        let exit = hit(&xs)
+            .unwrap()
+            .prepare_computations(&inner, &xs.iter().collect::<Vec<_>>());
+        assert!(exit.inside);
+        assert_eq!((exit.n1, exit.n2), (1.5, 1.0));
+        assert!(dot(&exit.normalv, &inner.direction) < 0.0);
+        assert!(exit.point.z > 0.0);
+    }
 }
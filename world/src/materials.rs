@@ -1,45 +1,124 @@
 use crate::{object::Object, patterns::Pattern};
 use colors::Color;
-use lights::PointLight;
-use tuples::{dot, normalize, reflect, Tuple};
-use uuid::Uuid;
+use lights::Light;
+use std::rc::Rc;
+use tuples::{dot, reflect, Tuple};
 
+// A shininess of 0 would turn every lit point into a full highlight
+// (x^0 == 1), so anything below this is treated as a matte surface.
+pub const SHININESS_EPSILON: f64 = 0.0001;
+
+// Bump mapping hook: takes the surface point and the geometric normal and
+// returns the normal used for shading.
+pub type NormalPerturbation = Rc<dyn Fn(&Tuple, &Tuple) -> Tuple>;
+
+#[derive(Clone)]
 pub struct Material {
-    pub id: Uuid,
     pub color: Color,
     pub ambient: f64,
     pub diffuse: f64,
     pub specular: f64,
     pub shininess: f64,
-    pub reflective: f64,
-    pub transparency: f64,
+    pub reflective: Color,
+    pub transparency: Color,
+    pub emissive: Color,
     pub refractive_index: f64,
     pub pattern: Option<Pattern>,
+    pub horizon_fade: Option<(f64, f64)>,
+    pub normal_perturbation: Option<NormalPerturbation>,
+}
+
+// Patterns and normal perturbations are trait objects and are not part of
+// the saved configuration.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MaterialConfig {
+    pub color: Color,
+    pub ambient: f64,
+    pub diffuse: f64,
+    pub specular: f64,
+    pub shininess: f64,
+    pub reflective: Color,
+    pub transparency: Color,
+    pub emissive: Color,
+    pub refractive_index: f64,
+    pub horizon_fade: Option<(f64, f64)>,
 }
 
 impl Material {
     pub fn glass() -> Material {
-        let mut m = Material::default();
-        m.transparency = 1.0;
-        m.refractive_index = 1.5;
-        m
+        Material {
+            transparency: Color::from(1.0),
+            refractive_index: 1.5,
+            ..Material::default()
+        }
+    }
+
+    pub fn with_color(self, color: Color) -> Material {
+        Material { color, ..self }
+    }
+
+    pub fn with_diffuse(self, diffuse: f64) -> Material {
+        Material { diffuse, ..self }
+    }
+
+    pub fn with_specular(self, specular: f64) -> Material {
+        Material { specular, ..self }
+    }
+
+    pub fn with_reflective(self, reflective: Color) -> Material {
+        Material { reflective, ..self }
+    }
+
+    pub fn from_config(config: &MaterialConfig) -> Material {
+        Material {
+            color: config.color,
+            ambient: config.ambient,
+            diffuse: config.diffuse,
+            specular: config.specular,
+            shininess: config.shininess,
+            reflective: config.reflective,
+            transparency: config.transparency,
+            emissive: config.emissive,
+            refractive_index: config.refractive_index,
+            horizon_fade: config.horizon_fade,
+            ..Material::default()
+        }
+    }
+
+    pub fn to_config(&self) -> MaterialConfig {
+        MaterialConfig {
+            color: self.color,
+            ambient: self.ambient,
+            diffuse: self.diffuse,
+            specular: self.specular,
+            shininess: self.shininess,
+            reflective: self.reflective,
+            transparency: self.transparency,
+            emissive: self.emissive,
+            refractive_index: self.refractive_index,
+            horizon_fade: self.horizon_fade,
+        }
+    }
+
+    pub fn base_color(&self, object: &Object, point: &Tuple) -> Color {
+        match &self.pattern {
+            Some(pattern) => pattern.pattern_at_object(object, point),
+            None => self.color,
+        }
     }
 
     pub fn lightning(
         &self,
         object: &Object,
-        light: &PointLight,
+        light: &dyn Light,
         point: &Tuple,
         eyev: &Tuple,
         normalv: &Tuple,
-        in_shadow: bool,
+        light_intensity: f64,
     ) -> Color {
-        let base_color = match &self.pattern {
-            Some(pattern) => pattern.pattern_at_object(&object, &point),
-            None => self.color,
-        };
-        let effective_color = base_color * light.intensity;
-        let lightv = normalize(&(light.position - *point));
+        let effective_color = self.base_color(object, point) * light.intensity();
+        let lightv = light.direction_from(point);
         let ambient = effective_color * self.ambient;
         let light_dot_normal = dot(&lightv, normalv);
         let mut diffuse = Color::new(0.0, 0.0, 0.0);
@@ -50,44 +129,74 @@ impl Material {
             let reflectv = reflect(&-lightv, normalv);
             let reflect_dot_eye = dot(&reflectv, eyev);
 
-            if reflect_dot_eye > 0.0 {
+            if reflect_dot_eye > 0.0 && self.shininess >= SHININESS_EPSILON {
                 let factor = reflect_dot_eye.powf(self.shininess);
-                specular = light.intensity * self.specular * factor;
+                specular = light.intensity() * self.specular * factor;
             }
         }
-        if in_shadow {
-            return ambient;
-        }
-        ambient + diffuse + specular
+        ambient + (diffuse + specular) * light_intensity
     }
 }
 
 impl Default for Material {
     fn default() -> Self {
         Material {
-            id: Uuid::new_v4(),
             color: Color::new(1.0, 1.0, 1.0),
             ambient: 0.1,
             diffuse: 0.9,
             specular: 0.9,
             shininess: 200.0,
-            reflective: 0.0,
-            transparency: 0.0,
+            reflective: Color::new(0.0, 0.0, 0.0),
+            transparency: Color::new(0.0, 0.0, 0.0),
+            emissive: Color::new(0.0, 0.0, 0.0),
             refractive_index: 1.0,
             pattern: None,
+            horizon_fade: None,
+            normal_perturbation: None,
         }
     }
 }
 
+// Patterns are trait objects, so two materials only compare their pattern
+// transforms, and normal perturbations only by whether they are set.
 impl PartialEq for Material {
     fn eq(&self, other: &Self) -> bool {
-        self.id == other.id
+        let same_pattern = match (&self.pattern, &other.pattern) {
+            (Some(a), Some(b)) => a.transform == b.transform,
+            (None, None) => true,
+            _ => false,
+        };
+        same_pattern
+            && self.color == other.color
+            && self.ambient == other.ambient
+            && self.diffuse == other.diffuse
+            && self.specular == other.specular
+            && self.shininess == other.shininess
+            && self.reflective == other.reflective
+            && self.transparency == other.transparency
+            && self.emissive == other.emissive
+            && self.refractive_index == other.refractive_index
+            && self.horizon_fade == other.horizon_fade
+            && self.normal_perturbation.is_some() == other.normal_perturbation.is_some()
     }
 }
 
 impl std::fmt::Debug for Material {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
-        write!(f, "Material {}", self.id)
+        f.debug_struct("Material")
+            .field("color", &self.color)
+            .field("ambient", &self.ambient)
+            .field("diffuse", &self.diffuse)
+            .field("specular", &self.specular)
+            .field("shininess", &self.shininess)
+            .field("reflective", &self.reflective)
+            .field("transparency", &self.transparency)
+            .field("emissive", &self.emissive)
+            .field("refractive_index", &self.refractive_index)
+            .field("pattern", &self.pattern)
+            .field("horizon_fade", &self.horizon_fade)
+            .field("normal_perturbation", &self.normal_perturbation.is_some())
+            .finish()
     }
 }
 
@@ -96,12 +205,40 @@ mod tests {
     use crate::{
         materials::Material,
         object::Object,
-        patterns::{stripes::StripesPatternShape, Pattern},
+        patterns::{
+            stripes::{Axis, StripesPatternShape},
+            Pattern,
+        },
         shapes::spheres::Sphere,
     };
     use colors::Color;
-    use lights::PointLight;
-    use tuples::{point, vector};
+    use lights::{DirectionalLight, PointLight};
+    use tuples::{normalize, point, vector};
+
+    #[test]
+    fn materials_compare_by_value() {
+        assert_eq!(Material::default(), Material::default());
+        assert_ne!(Material::default(), Material::default().with_diffuse(0.5));
+        let patterned = || Material {
+            pattern: Some(Pattern::new(Box::new(StripesPatternShape {
+                a: Color::new(1.0, 1.0, 1.0),
+                b: Color::new(0.0, 0.0, 0.0),
+                smoothness: 0.0,
+                axis: Axis::X,
+            }))),
+            ..Material::default()
+        };
+        assert_eq!(patterned(), patterned());
+        assert_ne!(patterned(), Material::default());
+    }
+
+    #[test]
+    fn material_debug_output_shows_its_fields() {
+        let output = format!("{:?}", Material::glass());
+        assert!(output.contains("refractive_index: 1.5"));
+        assert!(output.contains("pattern: None"));
+        assert_eq!(output, format!("{:?}", Material::glass()));
+    }
 
     #[test]
     fn default_material() {
@@ -111,7 +248,39 @@ mod tests {
         assert_eq!(m.diffuse, 0.9);
         assert_eq!(m.specular, 0.9);
         assert_eq!(m.shininess, 200.0);
-        assert_eq!(m.reflective, 0.0);
+        assert_eq!(m.reflective, Color::new(0.0, 0.0, 0.0));
+        assert_eq!(m.emissive, Color::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn directional_light_shades_a_plane_evenly() {
+        let object = Object::new(Box::new(Sphere::default()));
+        let m = Material {
+            specular: 0.0,
+            ..Material::default()
+        };
+        let eyev = vector(0.0, 1.0, 0.0);
+        let normalv = vector(0.0, 1.0, 0.0);
+        let sun = DirectionalLight::new(vector(1.0, -1.0, 0.0), Color::new(1.0, 1.0, 1.0));
+        let expected = m.lightning(&object, &sun, &point(0.0, 0.0, 0.0), &eyev, &normalv, 1.0);
+        for p in &[
+            point(5.0, 0.0, 0.0),
+            point(-30.0, 0.0, 12.0),
+            point(0.0, 0.0, 100.0),
+        ] {
+            assert_eq!(
+                m.lightning(&object, &sun, p, &eyev, &normalv, 1.0),
+                expected
+            );
+        }
+        let diffuse = 0.9 * normalize(&vector(1.0, 1.0, 0.0)).y;
+        assert_eq!(expected, Color::from(0.1 + diffuse));
+
+        let bulb = PointLight::new(point(0.0, 1.0, 0.0), Color::new(1.0, 1.0, 1.0));
+        assert_ne!(
+            m.lightning(&object, &bulb, &point(0.0, 0.0, 0.0), &eyev, &normalv, 1.0),
+            m.lightning(&object, &bulb, &point(5.0, 0.0, 0.0), &eyev, &normalv, 1.0)
+        );
     }
 
     #[test]
@@ -125,7 +294,7 @@ mod tests {
             position: point(0.0, 0.0, -10.0),
             intensity: Color::new(1.0, 1.0, 1.0),
         };
-        let result = m.lightning(&object, &light, &position, &eyev, &normalv, false);
+        let result = m.lightning(&object, &light, &position, &eyev, &normalv, 1.0);
         assert_eq!(result, Color::new(1.9, 1.9, 1.9));
     }
 
@@ -134,13 +303,13 @@ mod tests {
         let object = Object::new(Box::new(Sphere::default()));
         let m = Material::default();
         let position = point(0.0, 0.0, 0.0);
-        let eyev = vector(0.0, (2.0 as f64).sqrt() / 2.0, -(2.0 as f64).sqrt() / 2.0);
+        let eyev = vector(0.0, 2.0_f64.sqrt() / 2.0, -2.0_f64.sqrt() / 2.0);
         let normalv = vector(0.0, 0.0, -1.0);
         let light = PointLight {
             position: point(0.0, 0.0, -10.0),
             intensity: Color::new(1.0, 1.0, 1.0),
         };
-        let result = m.lightning(&object, &light, &position, &eyev, &normalv, false);
+        let result = m.lightning(&object, &light, &position, &eyev, &normalv, 1.0);
         assert_eq!(result, Color::new(1.0, 1.0, 1.0));
     }
 
@@ -155,7 +324,7 @@ mod tests {
             position: point(0.0, 10.0, -10.0),
             intensity: Color::new(1.0, 1.0, 1.0),
         };
-        let result = m.lightning(&object, &light, &position, &eyev, &normalv, false);
+        let result = m.lightning(&object, &light, &position, &eyev, &normalv, 1.0);
         assert_eq!(result, Color::new(0.7364, 0.7364, 0.7364));
     }
 
@@ -164,16 +333,63 @@ mod tests {
         let object = Object::new(Box::new(Sphere::default()));
         let m = Material::default();
         let position = point(0.0, 0.0, 0.0);
-        let eyev = vector(0.0, -(2.0 as f64).sqrt() / 2.0, -(2.0 as f64).sqrt() / 2.0);
+        let eyev = vector(0.0, -2.0_f64.sqrt() / 2.0, -2.0_f64.sqrt() / 2.0);
         let normalv = vector(0.0, 0.0, -1.0);
         let light = PointLight {
             position: point(0.0, 10.0, -10.0),
             intensity: Color::new(1.0, 1.0, 1.0),
         };
-        let result = m.lightning(&object, &light, &position, &eyev, &normalv, false);
+        let result = m.lightning(&object, &light, &position, &eyev, &normalv, 1.0);
         assert_eq!(result, Color::new(1.6364, 1.6364, 1.6364));
     }
 
+    #[test]
+    fn lighting_with_zero_shininess_is_matte() {
+        let object = Object::new(Box::new(Sphere::default()));
+        let light = PointLight::new(point(0.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
+        let position = point(0.0, 0.0, 0.0);
+        let normalv = vector(0.0, 0.0, -1.0);
+        let matte = Material {
+            shininess: 0.0,
+            ..Material::default()
+        };
+        let no_specular = Material {
+            specular: 0.0,
+            ..Material::default()
+        };
+        for eyev in &[
+            vector(0.0, 0.0, -1.0),
+            vector(0.0, -2.0_f64.sqrt() / 2.0, -2.0_f64.sqrt() / 2.0),
+        ] {
+            assert_eq!(
+                matte.lightning(&object, &light, &position, eyev, &normalv, 1.0),
+                no_specular.lightning(&object, &light, &position, eyev, &normalv, 1.0)
+            );
+        }
+    }
+
+    #[test]
+    fn lighting_with_a_large_shininess_has_a_tight_highlight() {
+        let object = Object::new(Box::new(Sphere::default()));
+        let m = Material {
+            shininess: 10000.0,
+            ..Material::default()
+        };
+        let light = PointLight::new(point(0.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
+        let position = point(0.0, 0.0, 0.0);
+        let normalv = vector(0.0, 0.0, -1.0);
+        let on_reflection = vector(0.0, -2.0_f64.sqrt() / 2.0, -2.0_f64.sqrt() / 2.0);
+        let slightly_off = normalize(&vector(0.0, -0.75, -0.65));
+        assert_eq!(
+            m.lightning(&object, &light, &position, &on_reflection, &normalv, 1.0),
+            Color::new(1.6364, 1.6364, 1.6364)
+        );
+        assert_eq!(
+            m.lightning(&object, &light, &position, &slightly_off, &normalv, 1.0),
+            Color::new(0.7364, 0.7364, 0.7364)
+        );
+    }
+
     #[test]
     fn lightning_with_the_light_behind_the_surface() {
         let object = Object::new(Box::new(Sphere::default()));
@@ -185,7 +401,7 @@ mod tests {
             position: point(0.0, 0.0, 10.0),
             intensity: Color::new(1.0, 1.0, 1.0),
         };
-        let result = m.lightning(&object, &light, &position, &eyev, &normalv, false);
+        let result = m.lightning(&object, &light, &position, &eyev, &normalv, 1.0);
         assert_eq!(result, Color::new(0.1, 0.1, 0.1));
     }
 
@@ -200,40 +416,30 @@ mod tests {
             position: point(0.0, 0.0, -10.0),
             intensity: Color::new(1.0, 1.0, 1.0),
         };
-        let result = m.lightning(&object, &light, &position, &eyev, &normalv, true);
+        let result = m.lightning(&object, &light, &position, &eyev, &normalv, 0.0);
         assert_eq!(result, Color::new(0.1, 0.1, 0.1));
     }
 
     #[test]
     fn lightning_with_a_pattern_applied() {
         let object = Object::new(Box::new(Sphere::default()));
-        let mut m = Material::default();
-        m.pattern = Some(Pattern::new(Box::new(StripesPatternShape {
-            a: Color::new(1.0, 1.0, 1.0),
-            b: Color::new(0.0, 0.0, 0.0),
-        })));
-        m.ambient = 1.0;
-        m.diffuse = 0.0;
-        m.specular = 0.0;
+        let m = Material {
+            pattern: Some(Pattern::new(Box::new(StripesPatternShape {
+                a: Color::new(1.0, 1.0, 1.0),
+                b: Color::new(0.0, 0.0, 0.0),
+                smoothness: 0.0,
+                axis: Axis::X,
+            }))),
+            ambient: 1.0,
+            diffuse: 0.0,
+            specular: 0.0,
+            ..Material::default()
+        };
         let eyev = vector(0.0, 0.0, -1.0);
         let normalv = vector(0.0, 0.0, -1.0);
         let light = PointLight::new(point(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
-        let c1 = m.lightning(
-            &object,
-            &light,
-            &point(0.9, 0.0, 0.0),
-            &eyev,
-            &normalv,
-            false,
-        );
-        let c2 = m.lightning(
-            &object,
-            &light,
-            &point(1.1, 0.0, 0.0),
-            &eyev,
-            &normalv,
-            false,
-        );
+        let c1 = m.lightning(&object, &light, &point(0.9, 0.0, 0.0), &eyev, &normalv, 1.0);
+        let c2 = m.lightning(&object, &light, &point(1.1, 0.0, 0.0), &eyev, &normalv, 1.0);
         assert_eq!(c1, Color::new(1.0, 1.0, 1.0));
         assert_eq!(c2, Color::new(0.0, 0.0, 0.0));
     }
@@ -241,7 +447,25 @@ mod tests {
     #[test]
     fn transparency_and_refractive_index_for_the_default_material() {
         let m = Material::default();
-        assert_eq!(m.transparency, 0.0);
+        assert_eq!(m.transparency, Color::new(0.0, 0.0, 0.0));
         assert_eq!(m.refractive_index, 1.0);
     }
+
+    #[test]
+    fn material_configuration_round_trip() {
+        let m = Material::glass();
+        let loaded = Material::from_config(&m.to_config());
+        assert_eq!(loaded.to_config(), m.to_config());
+        assert_eq!(loaded.transparency, Color::from(1.0));
+        assert_eq!(loaded.refractive_index, 1.5);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn glass_material_round_trips_through_json() {
+        let config = Material::glass().to_config();
+        let json = serde_json::to_string(&config).unwrap();
+        let loaded: crate::materials::MaterialConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, config);
+    }
 }
@@ -0,0 +1,312 @@
+use crate::object::Object;
+use crate::shapes::{
+    groups::Group,
+    triangles::{SmoothTriangle, Triangle},
+};
+use tuples::{point, vector, Tuple};
+
+pub struct ParsedObj {
+    pub vertices: Vec<Tuple>,
+    pub normals: Vec<Tuple>,
+    pub default_group: Vec<Object>,
+    pub named_groups: Vec<(String, Vec<Object>)>,
+    pub ignored: usize,
+}
+
+impl ParsedObj {
+    pub fn group(&self, name: &str) -> Option<&[Object]> {
+        self.named_groups
+            .iter()
+            .find(|(group_name, _)| group_name == name)
+            .map(|(_, objects)| objects.as_slice())
+    }
+
+    pub fn to_group(self) -> Group {
+        let mut children = self.default_group;
+        for (_, objects) in self.named_groups {
+            children.push(Object::new(Box::new(Group::new(objects))));
+        }
+        Group::new(children)
+    }
+
+    fn current_group(&mut self) -> &mut Vec<Object> {
+        match self.named_groups.last_mut() {
+            Some((_, objects)) => objects,
+            None => &mut self.default_group,
+        }
+    }
+
+    fn parse_coordinates(args: &[&str]) -> Option<(f64, f64, f64)> {
+        if args.len() != 3 {
+            return None;
+        }
+        Some((
+            args[0].parse().ok()?,
+            args[1].parse().ok()?,
+            args[2].parse().ok()?,
+        ))
+    }
+
+    fn parse_face(&mut self, args: &[&str]) -> Option<()> {
+        if args.len() < 3 {
+            return None;
+        }
+        let mut vertices = Vec::with_capacity(args.len());
+        let mut normals = Vec::with_capacity(args.len());
+        for arg in args {
+            let mut fields = arg.split('/');
+            let index = fields.next()?.parse::<usize>().ok()?;
+            vertices.push(*self.vertices.get(index.checked_sub(1)?)?);
+            match fields.nth(1) {
+                Some(field) if !field.is_empty() => {
+                    let index = field.parse::<usize>().ok()?;
+                    normals.push(*self.normals.get(index.checked_sub(1)?)?);
+                }
+                _ => {}
+            }
+        }
+        let smooth = normals.len() == vertices.len();
+        for i in 1..vertices.len() - 1 {
+            let object = if smooth {
+                Object::new(Box::new(SmoothTriangle::new(
+                    vertices[0],
+                    vertices[i],
+                    vertices[i + 1],
+                    normals[0],
+                    normals[i],
+                    normals[i + 1],
+                )))
+            } else {
+                Object::new(Box::new(Triangle::new(
+                    vertices[0],
+                    vertices[i],
+                    vertices[i + 1],
+                )))
+            };
+            self.current_group().push(object);
+        }
+        Some(())
+    }
+}
+
+pub fn parse_obj(text: &str) -> ParsedObj {
+    let mut parsed = ParsedObj {
+        vertices: vec![],
+        normals: vec![],
+        default_group: vec![],
+        named_groups: vec![],
+        ignored: 0,
+    };
+
+    for line in text.lines() {
+        let mut tokens = line.split_whitespace();
+        let command = match tokens.next() {
+            Some(command) => command,
+            None => continue,
+        };
+        let args = tokens.collect::<Vec<_>>();
+        let recognized = match command {
+            "v" => ParsedObj::parse_coordinates(&args)
+                .map(|(x, y, z)| parsed.vertices.push(point(x, y, z))),
+            "vn" => ParsedObj::parse_coordinates(&args)
+                .map(|(x, y, z)| parsed.normals.push(vector(x, y, z))),
+            "f" => parsed.parse_face(&args),
+            "g" if args.len() == 1 => {
+                parsed.named_groups.push((args[0].to_string(), vec![]));
+                Some(())
+            }
+            _ => None,
+        };
+        if recognized.is_none() {
+            parsed.ignored += 1;
+        }
+    }
+
+    parsed
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::obj::parse_obj;
+    use crate::object::Object;
+    use crate::shapes::{
+        groups::Group,
+        triangles::{SmoothTriangle, Triangle},
+    };
+    use tuples::{point, vector};
+
+    fn triangle(object: &Object) -> &Triangle {
+        object.shape_as::<Triangle>().unwrap()
+    }
+
+    #[test]
+    fn ignoring_unrecognized_lines() {
+        let gibberish = r#"There was a young lady named Bright
+who traveled much faster than light.
+She set out one day
+in a relative way,
+and came back the previous night."#;
+        let parsed = parse_obj(gibberish);
+        assert_eq!(parsed.ignored, 5);
+        assert!(parsed.vertices.is_empty());
+    }
+
+    #[test]
+    fn vertex_records() {
+        let file = r#"v -1 1 0
+v -1.0000 0.5000 0.0000
+v 1 0 0
+v 1 1 0"#;
+        let parsed = parse_obj(file);
+        assert_eq!(parsed.vertices[0], point(-1.0, 1.0, 0.0));
+        assert_eq!(parsed.vertices[1], point(-1.0, 0.5, 0.0));
+        assert_eq!(parsed.vertices[2], point(1.0, 0.0, 0.0));
+        assert_eq!(parsed.vertices[3], point(1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn parsing_triangle_faces() {
+        let file = r#"v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+
+f 1 2 3
+f 1 3 4"#;
+        let parsed = parse_obj(file);
+        assert_eq!(parsed.ignored, 0);
+        let t1 = triangle(&parsed.default_group[0]);
+        let t2 = triangle(&parsed.default_group[1]);
+        assert_eq!(t1.p1, parsed.vertices[0]);
+        assert_eq!(t1.p2, parsed.vertices[1]);
+        assert_eq!(t1.p3, parsed.vertices[2]);
+        assert_eq!(t2.p1, parsed.vertices[0]);
+        assert_eq!(t2.p2, parsed.vertices[2]);
+        assert_eq!(t2.p3, parsed.vertices[3]);
+    }
+
+    #[test]
+    fn triangulating_polygons() {
+        let file = r#"v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+v 0 2 0
+
+f 1 2 3 4 5"#;
+        let parsed = parse_obj(file);
+        assert_eq!(parsed.default_group.len(), 3);
+        let t1 = triangle(&parsed.default_group[0]);
+        let t2 = triangle(&parsed.default_group[1]);
+        let t3 = triangle(&parsed.default_group[2]);
+        assert_eq!(t1.p1, parsed.vertices[0]);
+        assert_eq!(t1.p2, parsed.vertices[1]);
+        assert_eq!(t1.p3, parsed.vertices[2]);
+        assert_eq!(t2.p1, parsed.vertices[0]);
+        assert_eq!(t2.p2, parsed.vertices[2]);
+        assert_eq!(t2.p3, parsed.vertices[3]);
+        assert_eq!(t3.p1, parsed.vertices[0]);
+        assert_eq!(t3.p2, parsed.vertices[3]);
+        assert_eq!(t3.p3, parsed.vertices[4]);
+    }
+
+    #[test]
+    fn faces_referencing_missing_vertices_are_ignored() {
+        let parsed = parse_obj("v 0 0 0\nf 1 2 3\nf 0 1 1");
+        assert_eq!(parsed.ignored, 2);
+        assert!(parsed.default_group.is_empty());
+    }
+
+    #[test]
+    fn triangles_in_groups() {
+        let file = r#"v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+
+g FirstGroup
+f 1 2 3
+g SecondGroup
+f 1 3 4"#;
+        let parsed = parse_obj(file);
+        let t1 = triangle(&parsed.group("FirstGroup").unwrap()[0]);
+        let t2 = triangle(&parsed.group("SecondGroup").unwrap()[0]);
+        assert_eq!(t1.p1, parsed.vertices[0]);
+        assert_eq!(t1.p2, parsed.vertices[1]);
+        assert_eq!(t1.p3, parsed.vertices[2]);
+        assert_eq!(t2.p1, parsed.vertices[0]);
+        assert_eq!(t2.p2, parsed.vertices[2]);
+        assert_eq!(t2.p3, parsed.vertices[3]);
+        assert!(parsed.group("ThirdGroup").is_none());
+    }
+
+    #[test]
+    fn converting_an_obj_file_to_a_group() {
+        let file = r#"v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+
+f 1 2 4
+g FirstGroup
+f 1 2 3
+g SecondGroup
+f 1 3 4"#;
+        let group = parse_obj(file).to_group();
+        assert_eq!(group.children.len(), 3);
+        assert!(group.children[0].shape_as::<Triangle>().is_some());
+        for named in group.children[1..].iter() {
+            let named = named.shape_as::<Group>().unwrap();
+            assert_eq!(named.children.len(), 1);
+        }
+    }
+
+    #[test]
+    fn vertex_normal_records() {
+        let file = r#"vn 0 0 1
+vn 0.707 0 -0.707
+vn 1 2 3"#;
+        let parsed = parse_obj(file);
+        assert_eq!(parsed.normals[0], vector(0.0, 0.0, 1.0));
+        assert_eq!(parsed.normals[1], vector(0.707, 0.0, -0.707));
+        assert_eq!(parsed.normals[2], vector(1.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn faces_with_normals() {
+        let file = r#"v 0 1 0
+v -1 0 0
+v 1 0 0
+
+vn -1 0 0
+vn 1 0 0
+vn 0 1 0
+
+f 1//3 2//1 3//2
+f 1/0/3 2/102/1 3/14/2"#;
+        let parsed = parse_obj(file);
+        assert_eq!(parsed.ignored, 0);
+        for object in parsed.default_group.iter() {
+            let t = object.shape_as::<SmoothTriangle>().unwrap();
+            assert_eq!(t.p1, parsed.vertices[0]);
+            assert_eq!(t.p2, parsed.vertices[1]);
+            assert_eq!(t.p3, parsed.vertices[2]);
+            assert_eq!(t.n1, parsed.normals[2]);
+            assert_eq!(t.n2, parsed.normals[0]);
+            assert_eq!(t.n3, parsed.normals[1]);
+        }
+        assert_eq!(parsed.default_group.len(), 2);
+    }
+
+    #[test]
+    fn faces_with_texture_coordinates_only_stay_flat() {
+        let file = r#"v 0 1 0
+v -1 0 0
+v 1 0 0
+
+f 1/1 2/2 3/3"#;
+        let parsed = parse_obj(file);
+        assert_eq!(parsed.ignored, 0);
+        assert!(parsed.default_group[0].shape_as::<Triangle>().is_some());
+    }
+}
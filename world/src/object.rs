@@ -1,57 +1,343 @@
+use crate::bounds::Aabb;
 use crate::intersections::Intersection;
-use crate::materials::Material;
-use crate::shapes::Shape;
-use matrices::{inverse, matrix_tuple_multiply, transpose, IDENTITY};
+use crate::materials::{Material, MaterialConfig};
+use crate::shapes::{planes::Plane, spheres::Sphere, triangles::Triangle, Shape};
+use crate::INTERSECTION_EPSILON;
+use colors::Color;
+use matrices::{inverse, matrix_multiply, matrix_tuple_multiply, transpose, IDENTITY};
 use rays::Ray;
+use std::cell::Cell;
 use std::rc::Rc;
-use tuples::{normalize, Tuple};
-use uuid::Uuid;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tuples::{dot, magnitude, normalize, point, Tuple};
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type"))]
+pub enum ShapeConfig {
+    Sphere,
+    Plane,
+    Triangle { p1: Tuple, p2: Tuple, p3: Tuple },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ObjectConfig {
+    pub shape: ShapeConfig,
+    pub transform: [f64; 16],
+    pub material: MaterialConfig,
+}
 
 pub struct Object {
-    pub id: Uuid,
+    pub id: u64,
     pub transform: [[f64; 4]; 4],
+    parent_transform: Cell<[[f64; 4]; 4]>,
     pub material: Rc<Material>,
     pub shape: Box<dyn Shape>,
+    pub motion: Option<Matrix4Pair>,
+    inverse_cache: Cell<Option<Matrix4Pair>>,
+    world_inverse_cache: Cell<Option<Matrix4Pair>>,
+    propagated: Cell<Option<Matrix4Pair>>,
+}
+
+// A transform together with its inverse. The cache is checked against the
+// current transform so assigning the public field directly stays correct.
+// The same pair type holds the start and end transforms of a moving object,
+// and the parent and own transform last pushed down to children.
+pub type Matrix4Pair = ([[f64; 4]; 4], [[f64; 4]; 4]);
+
+// Ids are handed out in creation order so scenes built the same way get the
+// same ids on every run. Generated ids have the high bit set and explicit
+// ids must not, so the two can never collide.
+const GENERATED_ID_BIT: u64 = 1 << 63;
+static NEXT_OBJECT_ID: AtomicU64 = AtomicU64::new(GENERATED_ID_BIT);
+
+fn cached_inverse(cache: &Cell<Option<Matrix4Pair>>, matrix: [[f64; 4]; 4]) -> [[f64; 4]; 4] {
+    match cache.get() {
+        Some((source, inverse_matrix)) if source == matrix => inverse_matrix,
+        _ => {
+            let inverse_matrix = inverse(&matrix).expect("object transform is not invertible");
+            cache.set(Some((matrix, inverse_matrix)));
+            inverse_matrix
+        }
+    }
 }
 
 impl Object {
-    pub fn intersect(&self, ray: &Ray) -> Option<[Intersection; 2]> {
-        let local_ray = ray.transform(&inverse(&self.transform));
-        let intersections = self.shape.intersects_at(&local_ray);
-        intersections.map(move |xs| {
-            [
-                Intersection {
-                    t: xs[0],
-                    object: self,
-                },
-                Intersection {
-                    t: xs[1],
-                    object: self,
-                },
-            ]
+    pub fn intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        let mut result = vec![];
+        self.intersect_into(ray, &mut result);
+        result
+    }
+
+    // Appends the intersections to a caller-owned buffer instead of
+    // returning a new list, so groups and renders can share one buffer.
+    pub fn intersect_into<'a>(&'a self, ray: &Ray, result: &mut Vec<Intersection<'a>>) {
+        self.propagate_to_children();
+        if self.misses_bounding_sphere(ray) {
+            return;
+        }
+        self.intersect_exact_into(ray, result)
+    }
+
+    fn intersect_exact_into<'a>(&'a self, ray: &Ray, result: &mut Vec<Intersection<'a>>) {
+        let inverse_transform = match self.motion {
+            Some(_) => {
+                inverse(&self.transform_at(ray.time)).expect("object transform is not invertible")
+            }
+            None => self.inverse_transform(),
+        };
+        let local_ray = ray.transform(&inverse_transform);
+        self.shape.intersect_into(self, &local_ray, result)
+    }
+
+    // Cheap test of the ray's line against a sphere around the shape's
+    // bounds in parent space, so no inverse or shape intersection is needed
+    // for objects far away from the ray. Unbounded and moving objects are
+    // always intersected.
+    fn misses_bounding_sphere(&self, ray: &Ray) -> bool {
+        let bounds = self.shape.bounds();
+        let corners = [bounds.min, bounds.max];
+        if self.motion.is_some()
+            || bounds.is_empty()
+            || !corners
+                .iter()
+                .all(|c| c.x.is_finite() && c.y.is_finite() && c.z.is_finite())
+        {
+            return false;
+        }
+
+        // The box around the transformed corners contains the shape however
+        // the transform scales or shears it.
+        let bounds = bounds.transform(&self.transform);
+        let center = point(
+            (bounds.min.x + bounds.max.x) / 2.0,
+            (bounds.min.y + bounds.max.y) / 2.0,
+            (bounds.min.z + bounds.max.z) / 2.0,
+        );
+        let radius = magnitude(&(bounds.max - center)) + INTERSECTION_EPSILON;
+
+        let center_to_ray = ray.origin - center;
+        let a = dot(&ray.direction, &ray.direction);
+        let b = dot(&ray.direction, &center_to_ray);
+        let c = dot(&center_to_ray, &center_to_ray) - radius.powf(2.0);
+        b.powf(2.0) - a * c < 0.0
+    }
+
+    pub fn inverse_transform(&self) -> [[f64; 4]; 4] {
+        cached_inverse(&self.inverse_cache, self.transform)
+    }
+
+    // Moving objects blend their start and end transforms element-wise, so
+    // time 0 is the start transform and time 1 is the end transform.
+    pub fn transform_at(&self, time: f64) -> [[f64; 4]; 4] {
+        match self.motion {
+            Some((start, end)) => {
+                let mut transform = start;
+                for (row, end_row) in transform.iter_mut().zip(end.iter()) {
+                    for (value, end_value) in row.iter_mut().zip(end_row.iter()) {
+                        *value += (end_value - *value) * time;
+                    }
+                }
+                transform
+            }
+            None => self.transform,
+        }
+    }
+
+    pub fn set_transform(&mut self, transform: [[f64; 4]; 4]) {
+        self.transform = transform;
+        cached_inverse(&self.inverse_cache, transform);
+        self.propagate_to_children();
+    }
+
+    pub fn world_transform(&self) -> [[f64; 4]; 4] {
+        matrix_multiply(&self.parent_transform.get(), &self.transform)
+    }
+
+    pub fn parent_transform(&self) -> [[f64; 4]; 4] {
+        self.parent_transform.get()
+    }
+
+    pub(crate) fn set_parent_transform(&self, parent_transform: [[f64; 4]; 4]) {
+        self.parent_transform.set(parent_transform);
+        self.propagate_to_children();
+    }
+
+    // Children of groups and CSGs carry their parents' combined transform.
+    // It is pushed down again whenever this object's transform or its own
+    // parent transform differs from what was last pushed, so assigning the
+    // public transform field directly keeps the children in step.
+    fn propagate_to_children(&self) {
+        let current = (self.parent_transform.get(), self.transform);
+        if self.propagated.get() != Some(current) {
+            self.shape.propagate_transform(&self.world_transform());
+            self.propagated.set(Some(current));
+        }
+    }
+
+    pub fn parent_space_bounds(&self) -> Aabb {
+        match self.motion {
+            Some((start, end)) => {
+                let mut bounds = self.shape.bounds().transform(&start);
+                bounds.add_box(&self.shape.bounds().transform(&end));
+                bounds
+            }
+            None => self.shape.bounds().transform(&self.transform),
+        }
+    }
+
+    pub fn from_config(config: &ObjectConfig) -> Object {
+        let shape: Box<dyn Shape> = match &config.shape {
+            ShapeConfig::Sphere => Box::new(Sphere::default()),
+            ShapeConfig::Plane => Box::new(Plane::default()),
+            ShapeConfig::Triangle { p1, p2, p3 } => Box::new(Triangle::new(*p1, *p2, *p3)),
+        };
+        let mut object = Object::new(shape);
+        let mut transform = IDENTITY;
+        for (row, values) in transform.iter_mut().enumerate() {
+            values.copy_from_slice(&config.transform[row * 4..row * 4 + 4]);
+        }
+        object.set_transform(transform);
+        object.material = Rc::new(Material::from_config(&config.material));
+        object
+    }
+
+    // Only primitive shapes can be saved; groups, CSG and smooth triangles
+    // return None.
+    pub fn to_config(&self) -> Option<ObjectConfig> {
+        let shape = if self.shape_as::<Sphere>().is_some() {
+            ShapeConfig::Sphere
+        } else if self.shape_as::<Plane>().is_some() {
+            ShapeConfig::Plane
+        } else if let Some(triangle) = self.shape_as::<Triangle>() {
+            ShapeConfig::Triangle {
+                p1: triangle.p1,
+                p2: triangle.p2,
+                p3: triangle.p3,
+            }
+        } else {
+            return None;
+        };
+        let mut transform = [0.0; 16];
+        for row in 0..4 {
+            for col in 0..4 {
+                transform[row * 4 + col] = self.transform[row][col];
+            }
+        }
+        Some(ObjectConfig {
+            shape,
+            transform,
+            material: self.material.to_config(),
         })
     }
 
+    pub fn shape_as<T: Shape>(&self) -> Option<&T> {
+        self.shape.as_any().downcast_ref::<T>()
+    }
+
+    pub fn is_shape<T: Shape>(&self) -> bool {
+        self.shape.as_any().is::<T>()
+    }
+
+    pub fn includes(&self, other: &Object) -> bool {
+        self == other || self.shape.includes(other)
+    }
+
     pub fn normal_at(&self, world_point: &Tuple) -> Tuple {
-        let inverse_transform = inverse(&self.transform);
+        self.normal_at_with_uv(world_point, None, None)
+    }
+
+    pub fn normal_at_with_uv(&self, world_point: &Tuple, u: Option<f64>, v: Option<f64>) -> Tuple {
+        self.normal_at_time(world_point, u, v, 0.0)
+    }
+
+    pub fn normal_at_time(
+        &self,
+        world_point: &Tuple,
+        u: Option<f64>,
+        v: Option<f64>,
+        time: f64,
+    ) -> Tuple {
+        if self.motion.is_none() {
+            let local_normal =
+                self.shape
+                    .normal_at_with_uv(&self.world_to_object(world_point), u, v);
+            return self.normal_to_world(&local_normal);
+        }
+        let inverse_transform = inverse(&matrix_multiply(
+            &self.parent_transform.get(),
+            &self.transform_at(time),
+        ))
+        .expect("object transform is not invertible");
         let transpose_inverse_transform = transpose(&inverse_transform);
         let local_point = matrix_tuple_multiply(&inverse_transform, world_point);
-        let local_normal = self.shape.local_normal_at(&local_point);
+        let local_normal = self.shape.normal_at_with_uv(&local_point, u, v);
         let mut world_normal = matrix_tuple_multiply(&transpose_inverse_transform, &local_normal);
         world_normal.w = 0.0;
         normalize(&world_normal)
     }
+
+    // Children of groups carry the combined transform of their parents in
+    // parent_transform, so one inverse covers the whole chain.
+    pub fn world_to_object(&self, world_point: &Tuple) -> Tuple {
+        let inverse_transform = cached_inverse(&self.world_inverse_cache, self.world_transform());
+        matrix_tuple_multiply(&inverse_transform, world_point)
+    }
+
+    pub fn normal_to_world(&self, object_normal: &Tuple) -> Tuple {
+        let inverse_transform = cached_inverse(&self.world_inverse_cache, self.world_transform());
+        let mut world_normal = matrix_tuple_multiply(&transpose(&inverse_transform), object_normal);
+        world_normal.w = 0.0;
+        normalize(&world_normal)
+    }
 }
 
 impl Object {
     pub fn new(shape: Box<dyn Shape>) -> Object {
+        shape.propagate_transform(&IDENTITY);
         Object {
-            id: Uuid::new_v4(),
+            id: NEXT_OBJECT_ID.fetch_add(1, Ordering::Relaxed),
             transform: IDENTITY,
+            parent_transform: Cell::new(IDENTITY),
             material: Rc::new(Material::default()),
             shape,
+            motion: None,
+            inverse_cache: Cell::new(Some((IDENTITY, IDENTITY))),
+            world_inverse_cache: Cell::new(Some((IDENTITY, IDENTITY))),
+            propagated: Cell::new(Some((IDENTITY, IDENTITY))),
         }
     }
+
+    pub fn with_id(mut self, id: u64) -> Object {
+        assert!(
+            id & GENERATED_ID_BIT == 0,
+            "explicit object ids must be below 2^63"
+        );
+        self.id = id;
+        self
+    }
+
+    pub fn with_transform(mut self, transform: [[f64; 4]; 4]) -> Object {
+        self.set_transform(transform);
+        self
+    }
+
+    pub fn with_motion(mut self, start: [[f64; 4]; 4], end: [[f64; 4]; 4]) -> Object {
+        self.set_transform(start);
+        self.motion = Some((start, end));
+        self
+    }
+
+    pub fn with_material(mut self, material: Material) -> Object {
+        self.material = Rc::new(material);
+        self
+    }
+
+    pub fn with_color(mut self, color: Color) -> Object {
+        Rc::make_mut(&mut self.material).color = color;
+        self
+    }
 }
 
 impl PartialEq for Object {
@@ -69,9 +355,16 @@ impl std::fmt::Debug for Object {
 #[cfg(test)]
 mod tests {
     use crate::materials::Material;
-    use crate::object::Object;
-    use crate::shapes::test::TestShape;
-    use matrices::IDENTITY;
+    use crate::object::{Object, ObjectConfig, ShapeConfig};
+    use crate::patterns::{
+        stripes::{Axis, StripesPatternShape},
+        Pattern,
+    };
+    use crate::shapes::{
+        groups::Group, planes::Plane, spheres::Sphere, test::TestShape, triangles::Triangle,
+    };
+    use colors::Color;
+    use matrices::{inverse, IDENTITY};
     use rays::Ray;
     use std::f64::consts::PI;
     use std::rc::Rc;
@@ -98,20 +391,22 @@ mod tests {
         let shape = TestShape::default();
         let o = Object::new(Box::new(shape));
         let m = Material::default();
-        assert_eq!((*o.material).color, m.color);
-        assert_eq!((*o.material).ambient, m.ambient);
-        assert_eq!((*o.material).diffuse, m.diffuse);
-        assert_eq!((*o.material).specular, m.specular);
-        assert_eq!((*o.material).shininess, m.shininess);
-        assert!((*o.material).pattern.is_none());
+        assert_eq!(o.material.color, m.color);
+        assert_eq!(o.material.ambient, m.ambient);
+        assert_eq!(o.material.diffuse, m.diffuse);
+        assert_eq!(o.material.specular, m.specular);
+        assert_eq!(o.material.shininess, m.shininess);
+        assert!(o.material.pattern.is_none());
     }
 
     #[test]
     fn assigning_a_material() {
         let shape = TestShape::default();
         let mut o = Object::new(Box::new(shape));
-        let mut m = Material::default();
-        m.diffuse = 1.0;
+        let m = Material {
+            diffuse: 1.0,
+            ..Material::default()
+        };
         let m = Rc::new(m);
         o.material = Rc::clone(&m);
         assert_eq!(o.material, m);
@@ -119,15 +414,12 @@ mod tests {
 
     #[test]
     fn intersecting_a_scaled_object_with_a_ray() {
-        let r = Ray {
-            origin: point(0.0, 0.0, -5.0),
-            direction: vector(0.0, 0.0, 1.0),
-        };
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
         let shape = TestShape::default();
         let mut o = Object::new(Box::new(shape));
         o.transform = IDENTITY.scale(2.0, 2.0, 2.0);
         o.intersect(&r);
-        let shape = o.shape.as_any().downcast_ref::<TestShape>().unwrap();
+        let shape = o.shape_as::<TestShape>().unwrap();
         assert_eq!(
             shape.saved_ray.borrow().as_ref().unwrap().origin,
             point(0.0, 0.0, -2.5)
@@ -140,15 +432,14 @@ mod tests {
 
     #[test]
     fn intersecting_a_translated_object_with_a_ray() {
-        let r = Ray {
-            origin: point(0.0, 0.0, -5.0),
-            direction: vector(0.0, 0.0, 1.0),
-        };
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
         let shape = TestShape::default();
         let mut o = Object::new(Box::new(shape));
         o.transform = IDENTITY.translate(5.0, 0.0, 0.0);
-        o.intersect(&r);
-        let shape = o.shape.as_any().downcast_ref::<TestShape>().unwrap();
+        // The ray misses the translated shape, so skip the bounding-sphere
+        // reject to observe the transformed ray.
+        o.intersect_exact_into(&r, &mut vec![]);
+        let shape = o.shape_as::<TestShape>().unwrap();
         assert_eq!(
             shape.saved_ray.borrow().as_ref().unwrap().origin,
             point(-5.0, 0.0, -5.0)
@@ -160,6 +451,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn computing_the_normal_on_a_translated_object() {
         let shape = TestShape::default();
         let mut o = Object::new(Box::new(shape));
@@ -173,11 +465,228 @@ mod tests {
         let shape = TestShape::default();
         let mut o = Object::new(Box::new(shape));
         o.transform = IDENTITY.rotate_z(PI / 5.0).scale(1.0, 0.5, 1.0);
-        let n = o.normal_at(&point(
-            0.0,
-            (2.0 as f64).sqrt() / 2.0,
-            -((2.0 as f64).sqrt() / 2.0),
-        ));
+        let n = o.normal_at(&point(0.0, 2.0_f64.sqrt() / 2.0, -(2.0_f64.sqrt() / 2.0)));
         assert_eq!(n, vector(0.0, 0.97014, -0.24254));
     }
+
+    #[test]
+    fn downcasting_an_objects_shape() {
+        let o = Object::new(Box::new(Sphere::default()));
+        assert!(o.shape_as::<Sphere>().is_some());
+        assert!(o.shape_as::<Plane>().is_none());
+    }
+
+    #[test]
+    fn an_object_includes_only_itself() {
+        let o = Object::new(Box::new(TestShape::default()));
+        let other = Object::new(Box::new(TestShape::default()));
+        assert!(o.includes(&o));
+        assert!(!o.includes(&other));
+    }
+
+    #[test]
+    fn object_configuration_records_the_shape_kind() {
+        let mut sphere = Object::new(Box::new(Sphere::default()));
+        sphere.set_transform(IDENTITY.scale(2.0, 2.0, 2.0).translate(0.0, 1.0, 0.0));
+        sphere.material = Rc::new(Material::glass());
+        let plane = Object::new(Box::new(Plane::default()));
+
+        let sphere_config = sphere.to_config().unwrap();
+        assert_eq!(sphere_config.shape, ShapeConfig::Sphere);
+        assert_eq!(plane.to_config().unwrap().shape, ShapeConfig::Plane);
+
+        let loaded = Object::from_config(&sphere_config);
+        assert!(loaded.shape_as::<Sphere>().is_some());
+        assert_eq!(loaded.transform, sphere.transform);
+        assert_eq!(loaded.material.to_config(), sphere.material.to_config());
+        assert!(Object::from_config(&plane.to_config().unwrap())
+            .shape_as::<Plane>()
+            .is_some());
+    }
+
+    #[test]
+    fn triangle_configuration_keeps_its_points() {
+        let triangle = Object::new(Box::new(Triangle::new(
+            point(0.0, 1.0, 0.0),
+            point(-1.0, 0.0, 0.0),
+            point(1.0, 0.0, 0.0),
+        )));
+        let loaded = Object::from_config(&triangle.to_config().unwrap());
+        let t = loaded.shape_as::<Triangle>().unwrap();
+        assert_eq!(t.p1, point(0.0, 1.0, 0.0));
+        assert_eq!(t.p3, point(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn groups_have_no_configuration() {
+        let group = Object::new(Box::new(Group::default()));
+        assert_eq!(group.to_config(), None::<ObjectConfig>);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn object_configuration_round_trips_through_json() {
+        let plane = Object::new(Box::new(Plane::default()));
+        let config = plane.to_config().unwrap();
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(json.contains("\"type\":\"Plane\""));
+        let loaded: ObjectConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, config);
+    }
+
+    #[test]
+    fn building_an_object_in_one_expression() {
+        let o = Object::new(Box::new(Sphere::default()))
+            .with_transform(IDENTITY.translate(1.0, 2.0, 3.0))
+            .with_material(
+                Material::default()
+                    .with_diffuse(0.7)
+                    .with_specular(0.3)
+                    .with_reflective(Color::from(0.5)),
+            )
+            .with_color(Color::new(0.1, 1.0, 0.5));
+        assert_eq!(o.transform, IDENTITY.translate(1.0, 2.0, 3.0));
+        assert_eq!(o.material.diffuse, 0.7);
+        assert_eq!(o.material.specular, 0.3);
+        assert_eq!(o.material.reflective, Color::from(0.5));
+        assert_eq!(o.material.color, Color::new(0.1, 1.0, 0.5));
+    }
+
+    #[test]
+    fn coloring_an_object_with_a_shared_material_leaves_the_other_object_alone() {
+        let a = Object::new(Box::new(Sphere::default())).with_material(Material::glass());
+        let mut b = Object::new(Box::new(Sphere::default()));
+        b.material = a.material.clone();
+        let b = b.with_color(Color::new(1.0, 0.0, 0.0));
+        assert_eq!(a.material.color, Color::new(1.0, 1.0, 1.0));
+        assert_eq!(b.material.color, Color::new(1.0, 0.0, 0.0));
+        assert_eq!(b.material.refractive_index, 1.5);
+    }
+
+    #[test]
+    fn coloring_an_object_with_a_shared_patterned_material_keeps_the_pattern() {
+        let a = Object::new(Box::new(Sphere::default())).with_material(Material {
+            pattern: Some(Pattern::new(Box::new(StripesPatternShape {
+                a: Color::new(1.0, 1.0, 1.0),
+                b: Color::new(0.0, 0.0, 0.0),
+                smoothness: 0.0,
+                axis: Axis::X,
+            }))),
+            normal_perturbation: Some(Rc::new(|_, normal| *normal)),
+            ..Material::default()
+        });
+        let mut b = Object::new(Box::new(Sphere::default()));
+        b.material = a.material.clone();
+        let b = b.with_color(Color::new(1.0, 0.0, 0.0));
+        assert_eq!(b.material.color, Color::new(1.0, 0.0, 0.0));
+        assert!(b.material.normal_perturbation.is_some());
+        assert_eq!(
+            b.material.base_color(&b, &point(1.5, 0.0, 0.0)),
+            Color::new(0.0, 0.0, 0.0)
+        );
+        assert_eq!(a.material.color, Color::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn setting_the_transform_updates_the_cached_inverse() {
+        let mut o = Object::new(Box::new(Sphere::default()));
+        assert_eq!(o.inverse_transform(), IDENTITY);
+        o.set_transform(IDENTITY.scale(2.0, 2.0, 2.0));
+        assert_eq!(
+            o.inverse_transform(),
+            inverse(&IDENTITY.scale(2.0, 2.0, 2.0)).unwrap()
+        );
+        o.transform = IDENTITY.translate(5.0, 0.0, 0.0);
+        assert_eq!(
+            o.inverse_transform(),
+            inverse(&IDENTITY.translate(5.0, 0.0, 0.0)).unwrap()
+        );
+    }
+
+    #[test]
+    fn cached_inverse_gives_the_same_intersections() {
+        let mut o = Object::new(Box::new(Sphere::default()));
+        o.set_transform(IDENTITY.scale(2.0, 2.0, 2.0));
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        let first = o.intersect(&r).iter().map(|i| i.t).collect::<Vec<_>>();
+        let second = o.intersect(&r).iter().map(|i| i.t).collect::<Vec<_>>();
+        assert_eq!(first, vec![3.0, 7.0]);
+        assert_eq!(second, first);
+    }
+
+    #[test]
+    fn a_moving_object_is_intersected_at_the_ray_time() {
+        let o = Object::new(Box::new(Sphere::default()))
+            .with_motion(IDENTITY, IDENTITY.translate(0.0, 0.0, 2.0));
+        let ts = |time: f64| {
+            let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0)).with_time(time);
+            o.intersect(&r).iter().map(|i| i.t).collect::<Vec<_>>()
+        };
+        assert_eq!(ts(0.0), vec![4.0, 6.0]);
+        assert_eq!(ts(0.5), vec![5.0, 7.0]);
+        assert_eq!(
+            o.normal_at_time(&point(0.0, 0.0, 0.0), None, None, 0.5),
+            vector(0.0, 0.0, -1.0)
+        );
+    }
+
+    #[test]
+    fn checking_the_shape_type_of_an_object() {
+        let o = Object::new(Box::new(Sphere::default()));
+        assert!(o.is_shape::<Sphere>());
+        assert!(!o.is_shape::<Plane>());
+    }
+
+    #[test]
+    fn objects_with_the_same_explicit_id_are_equal() {
+        let a = Object::new(Box::new(Sphere::default())).with_id(7);
+        let b = Object::new(Box::new(Plane::default())).with_id(7);
+        assert_eq!(a, b);
+        assert_eq!(a.id, 7);
+        let c = Object::new(Box::new(Sphere::default()));
+        let d = Object::new(Box::new(Sphere::default()));
+        assert_ne!(c, d);
+        assert_ne!(
+            Object::new(Box::new(Sphere::default())).with_id(d.id & !(1 << 63)),
+            d
+        );
+    }
+
+    #[test]
+    fn bounding_sphere_reject_matches_exact_intersections() {
+        let world = crate::World::default();
+        let rays = [
+            Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0)),
+            Ray::new(point(0.0, 0.9, -5.0), vector(0.0, 0.0, 1.0)),
+            Ray::new(point(0.0, 1.1, -5.0), vector(0.0, 0.0, 1.0)),
+            Ray::new(point(3.0, 0.0, -5.0), vector(0.0, 0.0, 1.0)),
+            Ray::new(point(0.0, 0.0, 0.0), vector(1.0, 1.0, 0.0)),
+            Ray::new(point(-5.0, 5.0, 0.0), vector(1.0, -1.0, 0.0)),
+        ];
+        let sheared = Object::new(Box::new(Triangle::new(
+            point(1.0, 1.0, 0.0),
+            point(-1.0, -1.0, 0.0),
+            point(1.0, -1.0, 0.0),
+        )))
+        .with_transform(IDENTITY.skew(1.0, 0.0, 0.0, 0.0, 0.0, 0.0));
+        let skew_ray = Ray::new(point(1.85, 0.9, -5.0), vector(0.0, 0.0, 1.0));
+        assert_eq!(
+            sheared
+                .intersect(&skew_ray)
+                .iter()
+                .map(|i| i.t)
+                .collect::<Vec<_>>(),
+            vec![5.0]
+        );
+        for o in world.objects.iter().chain(std::iter::once(&sheared)) {
+            for r in rays.iter().chain(std::iter::once(&skew_ray)) {
+                let quick = o.intersect(r).iter().map(|i| i.t).collect::<Vec<_>>();
+                let mut exact = vec![];
+                o.intersect_exact_into(r, &mut exact);
+                let exact = exact.iter().map(|i| i.t).collect::<Vec<_>>();
+                assert_eq!(quick, exact);
+            }
+        }
+        assert!(world.objects[0].misses_bounding_sphere(&rays[3]));
+    }
 }
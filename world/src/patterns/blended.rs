@@ -0,0 +1,74 @@
+use crate::patterns::{Pattern, PatternShape};
+use colors::Color;
+use tuples::Tuple;
+
+pub struct BlendedPatternShape {
+    pub a: Pattern,
+    pub b: Pattern,
+}
+
+impl PatternShape for BlendedPatternShape {
+    fn pattern_at(&self, point: &Tuple) -> Color {
+        (self.a.pattern_at(point) + self.b.pattern_at(point)) * 0.5
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::patterns::{
+        blended::BlendedPatternShape,
+        stripes::{Axis, StripesPatternShape},
+        Pattern, PatternShape,
+    };
+    use colors::Color;
+    use core::f64::consts::FRAC_PI_2;
+    use lazy_static::lazy_static;
+    use matrices::IDENTITY;
+    use transformations::MatrixTransformations;
+    use tuples::point;
+
+    lazy_static! {
+        static ref BLACK: Color = Color::new(0.0, 0.0, 0.0);
+        static ref WHITE: Color = Color::new(1.0, 1.0, 1.0);
+    }
+
+    fn plaid() -> BlendedPatternShape {
+        let x_stripes = Pattern::new(Box::new(StripesPatternShape {
+            a: *WHITE,
+            b: *BLACK,
+            smoothness: 0.0,
+            axis: Axis::X,
+        }));
+        let mut z_stripes = Pattern::new(Box::new(StripesPatternShape {
+            a: *WHITE,
+            b: *BLACK,
+            smoothness: 0.0,
+            axis: Axis::X,
+        }));
+        z_stripes.transform = IDENTITY.rotate_y(FRAC_PI_2);
+        BlendedPatternShape {
+            a: x_stripes,
+            b: z_stripes,
+        }
+    }
+
+    #[test]
+    fn blended_pattern_keeps_colors_where_both_agree() {
+        let pattern = plaid();
+        assert_eq!(pattern.pattern_at(&point(0.5, 0.0, -0.5)), *WHITE);
+        assert_eq!(pattern.pattern_at(&point(1.5, 0.0, 0.5)), *BLACK);
+    }
+
+    #[test]
+    fn blended_pattern_is_gray_where_the_patterns_disagree() {
+        let pattern = plaid();
+        assert_eq!(
+            pattern.pattern_at(&point(1.5, 0.0, -0.5)),
+            Color::new(0.5, 0.5, 0.5)
+        );
+        assert_eq!(
+            pattern.pattern_at(&point(0.5, 0.0, 0.5)),
+            Color::new(0.5, 0.5, 0.5)
+        );
+    }
+}
@@ -16,9 +16,28 @@ impl PatternShape for CheckersPatternShape {
     }
 }
 
+// Checkers over the x/z plane only, so a horizontal floor gets a clean
+// board whatever its height.
+pub struct PlanarCheckersPatternShape {
+    pub a: Color,
+    pub b: Color,
+}
+
+impl PatternShape for PlanarCheckersPatternShape {
+    fn pattern_at(&self, point: &Tuple) -> Color {
+        if (point.x.floor() + point.z.floor()).rem_euclid(2.0) == 0.0 {
+            return self.a;
+        }
+        self.b
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use crate::patterns::{checkers::CheckersPatternShape, Pattern};
+    use crate::patterns::{
+        checkers::{CheckersPatternShape, PlanarCheckersPatternShape},
+        Pattern, PatternShape,
+    };
     use colors::Color;
     use lazy_static::lazy_static;
     use tuples::point;
@@ -59,4 +78,20 @@ mod test {
         assert_eq!(pattern.shape.pattern_at(&point(0.0, 0.0, 0.99)), *WHITE);
         assert_eq!(pattern.shape.pattern_at(&point(0.0, 0.0, 1.01)), *BLACK);
     }
+
+    #[test]
+    fn planar_checkers_alternate_across_x_and_z() {
+        let pattern = PlanarCheckersPatternShape {
+            a: *WHITE,
+            b: *BLACK,
+        };
+        for &y in &[0.0, 0.5, 1.5, -2.5] {
+            assert_eq!(pattern.pattern_at(&point(0.5, y, 0.5)), *WHITE);
+            assert_eq!(pattern.pattern_at(&point(1.5, y, 0.5)), *BLACK);
+            assert_eq!(pattern.pattern_at(&point(0.5, y, 1.5)), *BLACK);
+            assert_eq!(pattern.pattern_at(&point(1.5, y, 1.5)), *WHITE);
+            assert_eq!(pattern.pattern_at(&point(-0.5, y, 0.5)), *BLACK);
+            assert_eq!(pattern.pattern_at(&point(-0.5, y, -0.5)), *WHITE);
+        }
+    }
 }
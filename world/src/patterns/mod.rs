@@ -1,30 +1,52 @@
+pub mod blended;
 pub mod checkers;
 pub mod gradient;
+pub mod nested;
+pub mod noise;
+pub mod perturbed;
 pub mod ring;
 pub mod stripes;
+pub mod texture_map;
 
 use crate::object::Object;
 use colors::Color;
 use matrices::{inverse, matrix_tuple_multiply, IDENTITY};
+use std::rc::Rc;
 use tuples::Tuple;
 
+// The shape is reference counted so materials can be cloned cheaply.
+#[derive(Clone)]
 pub struct Pattern {
     pub transform: [[f64; 4]; 4],
-    pub shape: Box<dyn PatternShape>,
+    pub shape: Rc<dyn PatternShape>,
 }
 
 impl Pattern {
     pub fn new(shape: Box<dyn PatternShape>) -> Pattern {
         Pattern {
             transform: IDENTITY,
-            shape,
+            shape: shape.into(),
         }
     }
 
     pub fn pattern_at_object(&self, object: &Object, world_point: &Tuple) -> Color {
-        let object_point = matrix_tuple_multiply(&inverse(&object.transform), &world_point);
-        let pattern_point = matrix_tuple_multiply(&inverse(&self.transform), &object_point);
-        return self.shape.pattern_at(&pattern_point);
+        let object_point = matrix_tuple_multiply(&object.inverse_transform(), world_point);
+        self.pattern_at(&object_point)
+    }
+
+    pub fn pattern_at(&self, point: &Tuple) -> Color {
+        let inverse_transform =
+            inverse(&self.transform).expect("pattern transform is not invertible");
+        let pattern_point = matrix_tuple_multiply(&inverse_transform, point);
+        self.shape.pattern_at(&pattern_point)
+    }
+}
+
+impl std::fmt::Debug for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        f.debug_struct("Pattern")
+            .field("transform", &self.transform)
+            .finish()
     }
 }
 
@@ -32,26 +54,31 @@ pub trait PatternShape {
     fn pattern_at(&self, point: &Tuple) -> Color;
 }
 
+pub mod test {
+    use crate::patterns::PatternShape;
+    use colors::Color;
+    use tuples::Tuple;
+
+    pub struct TestPatternShape {}
+
+    impl PatternShape for TestPatternShape {
+        fn pattern_at(&self, point: &Tuple) -> Color {
+            Color::new(point.x, point.y, point.z)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
         object::Object,
-        patterns::{Pattern, PatternShape},
+        patterns::{test::TestPatternShape, Pattern},
         shapes::spheres::Sphere,
     };
     use colors::Color;
     use matrices::IDENTITY;
     use transformations::MatrixTransformations;
     use tuples::point;
-    use tuples::Tuple;
-
-    struct TestPatternShape {}
-
-    impl PatternShape for TestPatternShape {
-        fn pattern_at(&self, point: &Tuple) -> Color {
-            Color::new(point.x, point.y, point.z)
-        }
-    }
 
     #[test]
     fn default_papttern_transformation() {
@@ -0,0 +1,119 @@
+use crate::patterns::{Pattern, PatternShape};
+use colors::Color;
+use tuples::Tuple;
+
+// The layout decides between the two sub-patterns: where it is black `a` is
+// sampled, where it is white `b` is, and grays mix both. Each sub-pattern is
+// evaluated through its own transform.
+pub struct NestedPatternShape {
+    pub layout: Box<dyn PatternShape>,
+    pub a: Pattern,
+    pub b: Pattern,
+}
+
+impl PatternShape for NestedPatternShape {
+    fn pattern_at(&self, point: &Tuple) -> Color {
+        let weight = self.layout.pattern_at(point).red;
+
+        if weight <= 0.0 {
+            return self.a.pattern_at(point);
+        }
+        if weight >= 1.0 {
+            return self.b.pattern_at(point);
+        }
+        self.a.pattern_at(point) * (1.0 - weight) + self.b.pattern_at(point) * weight
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::patterns::{
+        checkers::CheckersPatternShape,
+        nested::NestedPatternShape,
+        stripes::{Axis, StripesPatternShape},
+        test::TestPatternShape,
+        Pattern, PatternShape,
+    };
+    use colors::Color;
+    use lazy_static::lazy_static;
+    use matrices::IDENTITY;
+    use transformations::MatrixTransformations;
+    use tuples::point;
+
+    lazy_static! {
+        static ref BLACK: Color = Color::new(0.0, 0.0, 0.0);
+        static ref WHITE: Color = Color::new(1.0, 1.0, 1.0);
+        static ref RED: Color = Color::new(1.0, 0.0, 0.0);
+        static ref GREEN: Color = Color::new(0.0, 1.0, 0.0);
+        static ref BLUE: Color = Color::new(0.0, 0.0, 1.0);
+    }
+
+    fn stripes(a: Color, b: Color) -> Pattern {
+        Pattern::new(Box::new(StripesPatternShape {
+            a,
+            b,
+            smoothness: 0.0,
+            axis: Axis::X,
+        }))
+    }
+
+    fn checkers_of_stripes(a: Pattern, b: Pattern) -> NestedPatternShape {
+        NestedPatternShape {
+            layout: Box::new(CheckersPatternShape {
+                a: *BLACK,
+                b: *WHITE,
+            }),
+            a,
+            b,
+        }
+    }
+
+    #[test]
+    fn nested_pattern_selects_a_sub_pattern_per_layout_cell() {
+        let pattern = checkers_of_stripes(stripes(*RED, *GREEN), stripes(*BLUE, *WHITE));
+        assert_eq!(pattern.pattern_at(&point(0.5, 0.0, 0.5)), *RED);
+        assert_eq!(pattern.pattern_at(&point(0.5, 0.0, 1.5)), *BLUE);
+        assert_eq!(pattern.pattern_at(&point(1.5, 0.0, 0.5)), *WHITE);
+        assert_eq!(pattern.pattern_at(&point(1.5, 0.0, 1.5)), *GREEN);
+    }
+
+    #[test]
+    fn sampled_color_depends_on_both_transforms() {
+        let mut inner = stripes(*RED, *GREEN);
+        inner.transform = IDENTITY.scale(0.5, 1.0, 1.0);
+        let mut outer = Pattern::new(Box::new(checkers_of_stripes(inner, stripes(*BLUE, *BLUE))));
+        outer.transform = IDENTITY.translate(0.0, 0.0, 1.0);
+
+        // The outer translation moves the checker cell, the inner scale narrows the stripes.
+        assert_eq!(outer.pattern_at(&point(0.25, 0.0, 0.5)), *BLUE);
+        assert_eq!(outer.pattern_at(&point(0.25, 0.0, 1.5)), *RED);
+        assert_eq!(outer.pattern_at(&point(0.75, 0.0, 1.5)), *GREEN);
+    }
+
+    #[test]
+    fn sub_patterns_are_sampled_through_their_own_transform() {
+        let mut a = Pattern::new(Box::new(TestPatternShape {}));
+        a.transform = IDENTITY.translate(0.5, 0.0, 0.0);
+        let pattern = checkers_of_stripes(a, stripes(*BLACK, *BLACK));
+        assert_eq!(
+            pattern.pattern_at(&point(0.75, 0.5, 0.25)),
+            Color::new(0.25, 0.5, 0.25)
+        );
+    }
+
+    #[test]
+    fn gray_layout_mixes_both_sub_patterns() {
+        let pattern = NestedPatternShape {
+            layout: Box::new(CheckersPatternShape {
+                a: Color::from(0.5),
+                b: Color::from(0.5),
+            }),
+            a: stripes(*RED, *RED),
+            b: stripes(*BLUE, *BLUE),
+        };
+        assert_eq!(
+            pattern.pattern_at(&point(0.0, 0.0, 0.0)),
+            Color::new(0.5, 0.0, 0.5)
+        );
+    }
+}
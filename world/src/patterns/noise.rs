@@ -0,0 +1,124 @@
+// Improved Perlin noise over a permutation table shuffled from a seed, so the
+// same seed always produces the same noise field.
+pub struct Perlin {
+    permutation: [usize; 512],
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Perlin {
+        let mut table: Vec<usize> = (0..256).collect();
+        let mut state = seed ^ 0x9e37_79b9_7f4a_7c15;
+        for i in (1..256).rev() {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            table.swap(i, (state % (i as u64 + 1)) as usize);
+        }
+
+        let mut permutation = [0; 512];
+        for (i, value) in permutation.iter_mut().enumerate() {
+            *value = table[i % 256];
+        }
+        Perlin { permutation }
+    }
+
+    pub fn noise(&self, x: f64, y: f64, z: f64) -> f64 {
+        let xi = (x.floor() as i64 & 255) as usize;
+        let yi = (y.floor() as i64 & 255) as usize;
+        let zi = (z.floor() as i64 & 255) as usize;
+        let x = x - x.floor();
+        let y = y - y.floor();
+        let z = z - z.floor();
+        let u = fade(x);
+        let v = fade(y);
+        let w = fade(z);
+
+        let p = &self.permutation;
+        let a = p[xi] + yi;
+        let aa = p[a] + zi;
+        let ab = p[a + 1] + zi;
+        let b = p[xi + 1] + yi;
+        let ba = p[b] + zi;
+        let bb = p[b + 1] + zi;
+
+        lerp(
+            w,
+            lerp(
+                v,
+                lerp(u, grad(p[aa], x, y, z), grad(p[ba], x - 1.0, y, z)),
+                lerp(
+                    u,
+                    grad(p[ab], x, y - 1.0, z),
+                    grad(p[bb], x - 1.0, y - 1.0, z),
+                ),
+            ),
+            lerp(
+                v,
+                lerp(
+                    u,
+                    grad(p[aa + 1], x, y, z - 1.0),
+                    grad(p[ba + 1], x - 1.0, y, z - 1.0),
+                ),
+                lerp(
+                    u,
+                    grad(p[ab + 1], x, y - 1.0, z - 1.0),
+                    grad(p[bb + 1], x - 1.0, y - 1.0, z - 1.0),
+                ),
+            ),
+        )
+    }
+}
+
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+fn grad(hash: usize, x: f64, y: f64, z: f64) -> f64 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+    let u = if h & 1 == 0 { u } else { -u };
+    let v = if h & 2 == 0 { v } else { -v };
+    u + v
+}
+
+#[cfg(test)]
+mod test {
+    use crate::patterns::noise::Perlin;
+
+    #[test]
+    fn noise_is_zero_on_lattice_points() {
+        let perlin = Perlin::new(7);
+        assert_eq!(perlin.noise(0.0, 0.0, 0.0), 0.0);
+        assert_eq!(perlin.noise(3.0, -2.0, 5.0), 0.0);
+    }
+
+    #[test]
+    fn noise_is_deterministic_for_a_seed() {
+        let a = Perlin::new(42);
+        let b = Perlin::new(42);
+        let c = Perlin::new(43);
+        assert_eq!(a.noise(0.3, 1.7, -2.2), b.noise(0.3, 1.7, -2.2));
+        assert_ne!(a.noise(0.3, 1.7, -2.2), c.noise(0.3, 1.7, -2.2));
+    }
+
+    #[test]
+    fn noise_stays_within_bounds() {
+        let perlin = Perlin::new(1);
+        for i in 0..1000 {
+            let t = i as f64 * 0.173;
+            let n = perlin.noise(t, t * 0.37, -t * 1.31);
+            assert!((-1.5..=1.5).contains(&n));
+        }
+    }
+}
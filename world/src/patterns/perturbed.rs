@@ -0,0 +1,82 @@
+use crate::patterns::{noise::Perlin, PatternShape};
+use colors::Color;
+use tuples::{vector, Tuple};
+
+pub struct PerturbedPatternShape {
+    pub pattern: Box<dyn PatternShape>,
+    pub scale: f64,
+    pub noise: Perlin,
+}
+
+impl PerturbedPatternShape {
+    pub fn new(pattern: Box<dyn PatternShape>, scale: f64, seed: u64) -> PerturbedPatternShape {
+        PerturbedPatternShape {
+            pattern,
+            scale,
+            noise: Perlin::new(seed),
+        }
+    }
+}
+
+impl PatternShape for PerturbedPatternShape {
+    fn pattern_at(&self, point: &Tuple) -> Color {
+        if self.scale == 0.0 {
+            return self.pattern.pattern_at(point);
+        }
+
+        // Offset the lookups for y and z so the three axes jitter independently.
+        let jitter = vector(
+            self.noise.noise(point.x, point.y, point.z),
+            self.noise
+                .noise(point.x + 31.4, point.y + 15.9, point.z + 26.5),
+            self.noise
+                .noise(point.x - 35.8, point.y - 97.9, point.z - 32.3),
+        );
+        self.pattern.pattern_at(&(*point + jitter * self.scale))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::patterns::{
+        perturbed::PerturbedPatternShape,
+        stripes::{Axis, StripesPatternShape},
+        PatternShape,
+    };
+    use colors::Color;
+    use lazy_static::lazy_static;
+    use tuples::point;
+
+    lazy_static! {
+        static ref BLACK: Color = Color::new(0.0, 0.0, 0.0);
+        static ref WHITE: Color = Color::new(1.0, 1.0, 1.0);
+    }
+
+    fn stripes() -> Box<StripesPatternShape> {
+        Box::new(StripesPatternShape {
+            a: *WHITE,
+            b: *BLACK,
+            smoothness: 0.0,
+            axis: Axis::X,
+        })
+    }
+
+    #[test]
+    fn zero_scale_returns_the_underlying_pattern() {
+        let pattern = PerturbedPatternShape::new(stripes(), 0.0, 1);
+        for i in 0..20 {
+            let p = point(i as f64 * 0.13, 0.4, -0.7);
+            assert_eq!(pattern.pattern_at(&p), stripes().pattern_at(&p));
+        }
+    }
+
+    #[test]
+    fn perturbation_moves_the_stripe_boundary() {
+        let pattern = PerturbedPatternShape::new(stripes(), 0.5, 1);
+        let changed = (0..20)
+            .map(|i| point(0.95, i as f64 * 0.37, 0.5))
+            .filter(|p| pattern.pattern_at(p) != stripes().pattern_at(p))
+            .count();
+        assert!(changed > 0);
+    }
+}
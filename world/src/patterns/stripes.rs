@@ -2,23 +2,58 @@ use crate::patterns::PatternShape;
 use colors::Color;
 use tuples::Tuple;
 
+// The axis the stripes alternate along.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Axis {
+    #[default]
+    X,
+    Y,
+    Z,
+}
+
 pub struct StripesPatternShape {
     pub a: Color,
     pub b: Color,
+    pub smoothness: f64,
+    pub axis: Axis,
 }
 
-impl PatternShape for StripesPatternShape {
-    fn pattern_at(&self, point: &Tuple) -> Color {
-        if point.x.floor() % 2.0 == 0.0 {
+impl StripesPatternShape {
+    fn stripe(&self, index: f64) -> Color {
+        if index % 2.0 == 0.0 {
             return self.a;
         }
         self.b
     }
 }
 
+impl PatternShape for StripesPatternShape {
+    fn pattern_at(&self, point: &Tuple) -> Color {
+        let position = match self.axis {
+            Axis::X => point.x,
+            Axis::Y => point.y,
+            Axis::Z => point.z,
+        };
+        let boundary = position.round();
+        let offset = position - boundary;
+        let half_width = self.smoothness / 2.0;
+
+        if offset.abs() >= half_width {
+            return self.stripe(position.floor());
+        }
+
+        let s = (offset + half_width) / self.smoothness;
+        let blend = s * s * (3.0 - 2.0 * s);
+        self.stripe(boundary - 1.0) * (1.0 - blend) + self.stripe(boundary) * blend
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use crate::patterns::{stripes::StripesPatternShape, Pattern};
+    use crate::patterns::{
+        stripes::{Axis, StripesPatternShape},
+        Pattern,
+    };
     use colors::Color;
     use lazy_static::lazy_static;
     use tuples::point;
@@ -33,6 +68,8 @@ mod test {
         let pattern = Pattern::new(Box::new(StripesPatternShape {
             a: *WHITE,
             b: *BLACK,
+            smoothness: 0.0,
+            axis: Axis::X,
         }));
         assert_eq!(pattern.shape.pattern_at(&point(0.0, 0.0, 0.0)), *WHITE);
         assert_eq!(pattern.shape.pattern_at(&point(0.0, 1.0, 0.0)), *WHITE);
@@ -44,6 +81,8 @@ mod test {
         let pattern = Pattern::new(Box::new(StripesPatternShape {
             a: *WHITE,
             b: *BLACK,
+            smoothness: 0.0,
+            axis: Axis::X,
         }));
         assert_eq!(pattern.shape.pattern_at(&point(0.0, 0.0, 0.0)), *WHITE);
         assert_eq!(pattern.shape.pattern_at(&point(0.0, 0.0, 1.0)), *WHITE);
@@ -55,6 +94,8 @@ mod test {
         let pattern = Pattern::new(Box::new(StripesPatternShape {
             a: *WHITE,
             b: *BLACK,
+            smoothness: 0.0,
+            axis: Axis::X,
         }));
         assert_eq!(pattern.shape.pattern_at(&point(0.0, 0.0, 0.0)), *WHITE);
         assert_eq!(pattern.shape.pattern_at(&point(0.9, 0.0, 0.0)), *WHITE);
@@ -63,4 +104,48 @@ mod test {
         assert_eq!(pattern.shape.pattern_at(&point(-1.0, 0.0, 0.0)), *BLACK);
         assert_eq!(pattern.shape.pattern_at(&point(-1.1, 0.0, 0.0)), *WHITE);
     }
+
+    #[test]
+    fn stripe_pattern_alternates_in_y() {
+        let pattern = Pattern::new(Box::new(StripesPatternShape {
+            a: *WHITE,
+            b: *BLACK,
+            smoothness: 0.0,
+            axis: Axis::Y,
+        }));
+        assert_eq!(pattern.shape.pattern_at(&point(0.0, 0.9, 0.0)), *WHITE);
+        assert_eq!(pattern.shape.pattern_at(&point(0.0, 1.0, 0.0)), *BLACK);
+        assert_eq!(pattern.shape.pattern_at(&point(0.0, -0.1, 0.0)), *BLACK);
+        assert_eq!(pattern.shape.pattern_at(&point(1.0, 0.0, 1.0)), *WHITE);
+    }
+
+    #[test]
+    fn stripe_pattern_alternates_in_z() {
+        let pattern = Pattern::new(Box::new(StripesPatternShape {
+            a: *WHITE,
+            b: *BLACK,
+            smoothness: 0.0,
+            axis: Axis::Z,
+        }));
+        assert_eq!(pattern.shape.pattern_at(&point(0.0, 0.0, 0.9)), *WHITE);
+        assert_eq!(pattern.shape.pattern_at(&point(0.0, 0.0, 1.0)), *BLACK);
+        assert_eq!(pattern.shape.pattern_at(&point(0.0, 0.0, -1.1)), *WHITE);
+        assert_eq!(pattern.shape.pattern_at(&point(1.0, 1.0, 0.0)), *WHITE);
+    }
+
+    #[test]
+    fn smooth_stripes_blend_to_the_midpoint_at_a_boundary() {
+        let pattern = Pattern::new(Box::new(StripesPatternShape {
+            a: *WHITE,
+            b: *BLACK,
+            smoothness: 0.2,
+            axis: Axis::X,
+        }));
+        let grey = Color::new(0.5, 0.5, 0.5);
+        assert_eq!(pattern.shape.pattern_at(&point(1.0, 0.0, 0.0)), grey);
+        assert_eq!(pattern.shape.pattern_at(&point(0.0, 0.0, 0.0)), grey);
+        assert_eq!(pattern.shape.pattern_at(&point(0.5, 0.0, 0.0)), *WHITE);
+        assert_eq!(pattern.shape.pattern_at(&point(1.5, 0.0, 0.0)), *BLACK);
+        assert_eq!(pattern.shape.pattern_at(&point(0.89, 0.0, 0.0)), *WHITE);
+    }
 }
@@ -0,0 +1,389 @@
+use crate::patterns::PatternShape;
+use canvas::Canvas;
+use colors::Color;
+use core::f64::consts::PI;
+use tuples::{magnitude, vector, Tuple};
+
+const CAP_EPSILON: f64 = 0.0001;
+
+pub trait UvPattern {
+    fn uv_pattern_at(&self, u: f64, v: f64) -> Color;
+}
+
+pub struct UvCheckersPattern {
+    pub width: f64,
+    pub height: f64,
+    pub a: Color,
+    pub b: Color,
+}
+
+impl UvPattern for UvCheckersPattern {
+    fn uv_pattern_at(&self, u: f64, v: f64) -> Color {
+        if ((u * self.width).floor() + (v * self.height).floor()) % 2.0 == 0.0 {
+            return self.a;
+        }
+        self.b
+    }
+}
+
+// Samples the nearest pixel, with v = 0 at the bottom row of the image.
+pub struct UvImagePattern {
+    pub canvas: Canvas,
+}
+
+impl UvPattern for UvImagePattern {
+    fn uv_pattern_at(&self, u: f64, v: f64) -> Color {
+        let x = (u * (self.canvas.width - 1) as f64).round();
+        let y = ((1.0 - v) * (self.canvas.height - 1) as f64).round();
+        self.canvas
+            .try_color_at(x as i32, y as i32)
+            .unwrap_or_else(|| Color::new(0.0, 0.0, 0.0))
+    }
+}
+
+pub fn spherical_map(point: &Tuple) -> (f64, f64) {
+    let theta = point.x.atan2(point.z);
+    let radius = magnitude(&vector(point.x, point.y, point.z));
+    let phi = (point.y / radius).acos();
+    let raw_u = theta / (2.0 * PI);
+    (1.0 - (raw_u + 0.5), 1.0 - phi / PI)
+}
+
+pub fn cylindrical_map(point: &Tuple) -> (f64, f64) {
+    let u = point.x.atan2(point.z) / (2.0 * PI) + 0.5;
+    let v = point.y.rem_euclid(1.0);
+    (u, v)
+}
+
+pub fn planar_map(point: &Tuple) -> (f64, f64) {
+    (point.x.rem_euclid(1.0), point.z.rem_euclid(1.0))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CubeFace {
+    Left,
+    Right,
+    Front,
+    Back,
+    Up,
+    Down,
+}
+
+// Picks the face of the unit cube the point lies on by its dominant axis
+// and maps the point to that face's 0..1 coordinates.
+pub fn cube_uv(point: &Tuple) -> (CubeFace, f64, f64) {
+    let coord = point.x.abs().max(point.y.abs()).max(point.z.abs());
+    let around = |value: f64| value.rem_euclid(2.0) / 2.0;
+    if coord == point.x {
+        (
+            CubeFace::Right,
+            around(1.0 - point.z),
+            around(point.y + 1.0),
+        )
+    } else if coord == -point.x {
+        (CubeFace::Left, around(point.z + 1.0), around(point.y + 1.0))
+    } else if coord == point.y {
+        (CubeFace::Up, around(point.x + 1.0), around(1.0 - point.z))
+    } else if coord == -point.y {
+        (CubeFace::Down, around(point.x + 1.0), around(point.z + 1.0))
+    } else if coord == point.z {
+        (
+            CubeFace::Front,
+            around(point.x + 1.0),
+            around(point.y + 1.0),
+        )
+    } else {
+        (CubeFace::Back, around(1.0 - point.x), around(point.y + 1.0))
+    }
+}
+
+pub struct CubeMapPatternShape {
+    pub left: Box<dyn UvPattern>,
+    pub right: Box<dyn UvPattern>,
+    pub front: Box<dyn UvPattern>,
+    pub back: Box<dyn UvPattern>,
+    pub up: Box<dyn UvPattern>,
+    pub down: Box<dyn UvPattern>,
+}
+
+impl PatternShape for CubeMapPatternShape {
+    fn pattern_at(&self, point: &Tuple) -> Color {
+        let (face, u, v) = cube_uv(point);
+        let uv_pattern = match face {
+            CubeFace::Left => &self.left,
+            CubeFace::Right => &self.right,
+            CubeFace::Front => &self.front,
+            CubeFace::Back => &self.back,
+            CubeFace::Up => &self.up,
+            CubeFace::Down => &self.down,
+        };
+        uv_pattern.uv_pattern_at(u, v)
+    }
+}
+
+pub type UvMap = fn(&Tuple) -> (f64, f64);
+
+pub struct TextureMapPatternShape {
+    pub uv_pattern: Box<dyn UvPattern>,
+    pub uv_map: UvMap,
+}
+
+impl PatternShape for TextureMapPatternShape {
+    fn pattern_at(&self, point: &Tuple) -> Color {
+        let (u, v) = (self.uv_map)(point);
+        self.uv_pattern.uv_pattern_at(u, v)
+    }
+}
+
+// Wraps a uv pattern around a unit cylinder along the y axis. When the
+// cylinder is closed, points on the caps are mapped with planar uvs.
+pub struct CylinderMapPatternShape {
+    pub uv_pattern: Box<dyn UvPattern>,
+    pub minimum: f64,
+    pub maximum: f64,
+    pub closed: bool,
+}
+
+impl CylinderMapPatternShape {
+    fn is_on_cap(&self, point: &Tuple) -> bool {
+        self.closed
+            && point.x.powf(2.0) + point.z.powf(2.0) < 1.0 - CAP_EPSILON
+            && (point.y >= self.maximum - CAP_EPSILON || point.y <= self.minimum + CAP_EPSILON)
+    }
+}
+
+impl PatternShape for CylinderMapPatternShape {
+    fn pattern_at(&self, point: &Tuple) -> Color {
+        let (u, v) = if self.is_on_cap(point) {
+            planar_map(point)
+        } else {
+            cylindrical_map(point)
+        };
+        self.uv_pattern.uv_pattern_at(u, v)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::patterns::{
+        texture_map::{
+            cube_uv, cylindrical_map, planar_map, spherical_map, CubeFace, CubeMapPatternShape,
+            CylinderMapPatternShape, TextureMapPatternShape, UvCheckersPattern, UvImagePattern,
+            UvPattern,
+        },
+        PatternShape,
+    };
+    use canvas::Canvas;
+    use colors::Color;
+    use float_cmp::ApproxEq;
+    use lazy_static::lazy_static;
+    use tuples::point;
+
+    lazy_static! {
+        static ref BLACK: Color = Color::new(0.0, 0.0, 0.0);
+        static ref WHITE: Color = Color::new(1.0, 1.0, 1.0);
+    }
+
+    fn checkers() -> Box<UvCheckersPattern> {
+        Box::new(UvCheckersPattern {
+            width: 2.0,
+            height: 2.0,
+            a: *BLACK,
+            b: *WHITE,
+        })
+    }
+
+    #[test]
+    fn checker_pattern_in_2d() {
+        let pattern = checkers();
+        assert_eq!(pattern.uv_pattern_at(0.0, 0.0), *BLACK);
+        assert_eq!(pattern.uv_pattern_at(0.5, 0.0), *WHITE);
+        assert_eq!(pattern.uv_pattern_at(0.0, 0.5), *WHITE);
+        assert_eq!(pattern.uv_pattern_at(0.5, 0.5), *BLACK);
+        assert_eq!(pattern.uv_pattern_at(1.0, 1.0), *BLACK);
+    }
+
+    fn checker_image() -> UvImagePattern {
+        let mut canvas = Canvas::new(2, 2);
+        canvas.write_pixel(1, 0, *WHITE);
+        canvas.write_pixel(0, 1, *WHITE);
+        UvImagePattern { canvas }
+    }
+
+    #[test]
+    fn using_a_spherical_mapping_on_a_3d_point() {
+        let scenarios = [
+            (point(0.0, 0.0, -1.0), (0.0, 0.5)),
+            (point(1.0, 0.0, 0.0), (0.25, 0.5)),
+            (point(0.0, 0.0, 1.0), (0.5, 0.5)),
+            (point(-1.0, 0.0, 0.0), (0.75, 0.5)),
+            (point(0.0, 1.0, 0.0), (0.5, 1.0)),
+            (point(0.0, -1.0, 0.0), (0.5, 0.0)),
+            (
+                point(2.0_f64.sqrt() / 2.0, 2.0_f64.sqrt() / 2.0, 0.0),
+                (0.25, 0.75),
+            ),
+        ];
+        for (p, (expected_u, expected_v)) in scenarios.iter() {
+            let (u, v) = spherical_map(p);
+            assert!(u.approx_eq(*expected_u, (0.0001, 2)));
+            assert!(v.approx_eq(*expected_v, (0.0001, 2)));
+        }
+    }
+
+    #[test]
+    fn sampling_an_image_by_uv_coordinates() {
+        let pattern = checker_image();
+        assert_eq!(pattern.uv_pattern_at(0.0, 1.0), *BLACK);
+        assert_eq!(pattern.uv_pattern_at(1.0, 1.0), *WHITE);
+        assert_eq!(pattern.uv_pattern_at(0.2, 0.0), *WHITE);
+        assert_eq!(pattern.uv_pattern_at(0.9, 0.1), *BLACK);
+    }
+
+    #[test]
+    fn texture_map_pattern_samples_the_mapped_uv() {
+        let pattern = TextureMapPatternShape {
+            uv_pattern: Box::new(checker_image()),
+            uv_map: spherical_map,
+        };
+        assert_eq!(pattern.pattern_at(&point(0.0, 0.0, -1.0)), *WHITE);
+        assert_eq!(pattern.pattern_at(&point(0.0, 1.0, 0.0)), *WHITE);
+        assert_eq!(pattern.pattern_at(&point(0.0, 0.0, 1.0)), *BLACK);
+    }
+
+    #[test]
+    fn identifying_the_face_of_a_cube_from_a_point() {
+        let scenarios = [
+            (point(-1.0, 0.5, -0.25), CubeFace::Left),
+            (point(1.1, -0.75, 0.8), CubeFace::Right),
+            (point(0.1, 0.6, 0.9), CubeFace::Front),
+            (point(-0.7, 0.0, -2.0), CubeFace::Back),
+            (point(0.5, 1.0, 0.9), CubeFace::Up),
+            (point(-0.2, -1.3, 1.1), CubeFace::Down),
+        ];
+        for (p, expected) in scenarios.iter() {
+            assert_eq!(cube_uv(p).0, *expected);
+        }
+        assert_eq!(
+            cube_uv(&point(-1.0, 0.5, -0.25)),
+            (CubeFace::Left, 0.375, 0.75)
+        );
+    }
+
+    #[test]
+    fn uv_mapping_each_face_of_a_cube() {
+        let scenarios = [
+            (point(-0.5, 0.5, 1.0), point(0.5, -0.5, 1.0)),
+            (point(0.5, 0.5, -1.0), point(-0.5, -0.5, -1.0)),
+            (point(-1.0, 0.5, -0.5), point(-1.0, -0.5, 0.5)),
+            (point(1.0, 0.5, 0.5), point(1.0, -0.5, -0.5)),
+            (point(-0.5, 1.0, -0.5), point(0.5, 1.0, 0.5)),
+            (point(-0.5, -1.0, 0.5), point(0.5, -1.0, -0.5)),
+        ];
+        for (upper_left, lower_right) in scenarios.iter() {
+            let (_, u, v) = cube_uv(upper_left);
+            assert_eq!((u, v), (0.25, 0.75));
+            let (_, u, v) = cube_uv(lower_right);
+            assert_eq!((u, v), (0.75, 0.25));
+        }
+    }
+
+    #[test]
+    fn cube_map_picks_the_pattern_of_each_face() {
+        let solid = |value: f64| {
+            Box::new(UvCheckersPattern {
+                width: 1.0,
+                height: 1.0,
+                a: Color::from(value),
+                b: Color::from(value),
+            })
+        };
+        let pattern = CubeMapPatternShape {
+            left: solid(0.1),
+            right: solid(0.2),
+            front: solid(0.3),
+            back: solid(0.4),
+            up: solid(0.5),
+            down: solid(0.6),
+        };
+        assert_eq!(pattern.pattern_at(&point(-1.0, 0.0, 0.0)), Color::from(0.1));
+        assert_eq!(pattern.pattern_at(&point(1.0, 0.2, 0.3)), Color::from(0.2));
+        assert_eq!(pattern.pattern_at(&point(0.0, 0.0, 1.0)), Color::from(0.3));
+        assert_eq!(pattern.pattern_at(&point(0.5, 0.5, -1.0)), Color::from(0.4));
+        assert_eq!(pattern.pattern_at(&point(0.0, 1.0, 0.0)), Color::from(0.5));
+        assert_eq!(pattern.pattern_at(&point(0.0, -1.0, 0.0)), Color::from(0.6));
+    }
+
+    #[test]
+    fn using_a_cylindrical_mapping_around_the_circumference() {
+        let scenarios = [
+            (point(0.0, 0.0, 1.0), 0.5),
+            (point(1.0, 0.0, 0.0), 0.75),
+            (point(-1.0, 0.0, 0.0), 0.25),
+            (
+                point(2.0_f64.sqrt() / 2.0, 0.0, 2.0_f64.sqrt() / 2.0),
+                0.625,
+            ),
+            (
+                point(-(2.0_f64.sqrt()) / 2.0, 0.0, -(2.0_f64.sqrt()) / 2.0),
+                0.125,
+            ),
+        ];
+        for (p, expected_u) in scenarios.iter() {
+            let (u, _) = cylindrical_map(p);
+            assert!(u.approx_eq(*expected_u, (0.0001, 2)));
+        }
+    }
+
+    #[test]
+    fn using_a_cylindrical_mapping_along_the_height() {
+        let scenarios = [
+            (point(0.0, 0.0, 1.0), 0.0),
+            (point(0.0, 0.5, 1.0), 0.5),
+            (point(0.0, 1.25, 1.0), 0.25),
+            (point(0.0, -0.25, 1.0), 0.75),
+        ];
+        for (p, expected_v) in scenarios.iter() {
+            let (_, v) = cylindrical_map(p);
+            assert!(v.approx_eq(*expected_v, (0.0001, 2)));
+        }
+    }
+
+    #[test]
+    fn using_a_planar_mapping() {
+        assert_eq!(planar_map(&point(0.25, 0.5, -0.25)), (0.25, 0.75));
+        assert_eq!(planar_map(&point(1.25, 0.0, 0.5)), (0.25, 0.5));
+    }
+
+    #[test]
+    fn cylinder_map_wraps_the_uv_pattern_around_the_sides() {
+        let pattern = CylinderMapPatternShape {
+            uv_pattern: checkers(),
+            minimum: 0.0,
+            maximum: 1.0,
+            closed: true,
+        };
+        assert_eq!(pattern.pattern_at(&point(0.0, 0.25, 1.0)), *WHITE);
+        assert_eq!(pattern.pattern_at(&point(0.0, 0.75, 1.0)), *BLACK);
+        assert_eq!(pattern.pattern_at(&point(-1.0, 0.25, 0.0)), *BLACK);
+    }
+
+    #[test]
+    fn cylinder_map_uses_planar_uvs_on_closed_caps() {
+        let closed = CylinderMapPatternShape {
+            uv_pattern: checkers(),
+            minimum: 0.0,
+            maximum: 1.0,
+            closed: true,
+        };
+        let open = CylinderMapPatternShape {
+            uv_pattern: checkers(),
+            minimum: 0.0,
+            maximum: 1.0,
+            closed: false,
+        };
+        let p = point(-0.5, 1.0, 0.25);
+        assert_eq!(closed.pattern_at(&p), *WHITE);
+        assert_eq!(open.pattern_at(&p), *BLACK);
+    }
+}
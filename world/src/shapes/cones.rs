@@ -0,0 +1,153 @@
+use crate::bounds::Aabb;
+use crate::shapes::{cylinders::within_radius, Shape};
+use rays::Ray;
+use std::any::Any;
+use tuples::{point, vector, Tuple, EPSILON};
+
+// A double-napped cone x² + z² = y² around the y axis, truncated to
+// minimum < y < maximum and optionally capped at both ends.
+pub struct Cone {
+    pub minimum: f64,
+    pub maximum: f64,
+    pub closed: bool,
+}
+
+impl Default for Cone {
+    fn default() -> Cone {
+        Cone {
+            minimum: f64::NEG_INFINITY,
+            maximum: f64::INFINITY,
+            closed: false,
+        }
+    }
+}
+
+impl Cone {
+    pub fn new(minimum: f64, maximum: f64, closed: bool) -> Cone {
+        Cone {
+            minimum,
+            maximum,
+            closed,
+        }
+    }
+
+    fn intersect_caps(&self, ray: &Ray, xs: &mut Vec<f64>) {
+        if !self.closed || ray.direction.y.abs() < EPSILON {
+            return;
+        }
+        for y in &[self.minimum, self.maximum] {
+            let t = (y - ray.origin.y) / ray.direction.y;
+            if within_radius(ray, t, y.abs()) {
+                xs.push(t);
+            }
+        }
+    }
+}
+
+impl Shape for Cone {
+    fn intersects_at(&self, ray: &Ray) -> Vec<f64> {
+        let mut xs = vec![];
+        let (o, d) = (ray.origin, ray.direction);
+        let a = d.x.powf(2.0) - d.y.powf(2.0) + d.z.powf(2.0);
+        let b = 2.0 * o.x * d.x - 2.0 * o.y * d.y + 2.0 * o.z * d.z;
+        let c = o.x.powf(2.0) - o.y.powf(2.0) + o.z.powf(2.0);
+        let mut wall = vec![];
+        if a.abs() < EPSILON {
+            if b.abs() >= EPSILON {
+                wall.push(-c / (2.0 * b));
+            }
+        } else {
+            let discriminant = b.powf(2.0) - 4.0 * a * c;
+            if discriminant >= 0.0 {
+                let t0 = (-b - discriminant.sqrt()) / (2.0 * a);
+                let t1 = (-b + discriminant.sqrt()) / (2.0 * a);
+                wall.push(t0.min(t1));
+                wall.push(t0.max(t1));
+            }
+        }
+        for t in wall {
+            let y = o.y + t * d.y;
+            if self.minimum < y && y < self.maximum {
+                xs.push(t);
+            }
+        }
+        self.intersect_caps(ray, &mut xs);
+        xs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        xs
+    }
+
+    // As with cylinders, the rim belongs to the cap.
+    fn local_normal_at(&self, local_point: &Tuple) -> Tuple {
+        let dist = local_point.x.powf(2.0) + local_point.z.powf(2.0);
+        if dist < self.maximum.powf(2.0) && local_point.y >= self.maximum - EPSILON {
+            return vector(0.0, 1.0, 0.0);
+        }
+        if dist < self.minimum.powf(2.0) && local_point.y <= self.minimum + EPSILON {
+            return vector(0.0, -1.0, 0.0);
+        }
+        let y = dist.sqrt();
+        let y = if local_point.y > 0.0 { -y } else { y };
+        vector(local_point.x, y, local_point.z)
+    }
+
+    fn bounds(&self) -> Aabb {
+        let radius = self.minimum.abs().max(self.maximum.abs());
+        Aabb::new(
+            point(-radius, self.minimum, -radius),
+            point(radius, self.maximum, radius),
+        )
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::shapes::{cones::Cone, Shape};
+    use rays::Ray;
+    use tuples::{normalize, point, vector};
+
+    #[test]
+    fn intersecting_a_cone_with_a_ray() {
+        let c = Cone::default();
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        assert_eq!(c.intersects_at(&r), vec![5.0, 5.0]);
+        let r = Ray::new(point(0.0, 0.0, -1.0), normalize(&vector(0.0, 1.0, 1.0)));
+        let xs = c.intersects_at(&r);
+        assert_eq!(xs.len(), 1);
+        assert!((xs[0] - 0.35355).abs() < 0.0001);
+    }
+
+    #[test]
+    fn intersecting_the_caps_of_a_closed_cone() {
+        let c = Cone::new(-0.5, 0.5, true);
+        let cases = [
+            (point(0.0, 0.0, -5.0), vector(0.0, 1.0, 0.0), 0),
+            (point(0.0, 0.0, -0.25), vector(0.0, 1.0, 1.0), 2),
+            (point(0.0, 0.0, -0.25), vector(0.0, 1.0, 0.0), 4),
+        ];
+        for (origin, direction, count) in cases.iter() {
+            let r = Ray::new(*origin, normalize(direction));
+            assert_eq!(c.intersects_at(&r).len(), *count);
+        }
+    }
+
+    #[test]
+    fn normal_at_the_rim_prefers_the_cap() {
+        let c = Cone::new(-1.0, 1.0, true);
+        assert_eq!(
+            c.local_normal_at(&point(0.999, 1.0, 0.0)),
+            vector(0.0, 1.0, 0.0)
+        );
+        assert_eq!(
+            c.local_normal_at(&point(0.0, -1.0, 0.999)),
+            vector(0.0, -1.0, 0.0)
+        );
+        assert_eq!(
+            c.local_normal_at(&point(0.9, 0.9, 0.0)),
+            vector(0.9, -0.9, 0.0)
+        );
+    }
+}
@@ -0,0 +1,294 @@
+use crate::bounds::Aabb;
+use crate::intersections::Intersection;
+use crate::object::Object;
+use crate::shapes::Shape;
+use rays::Ray;
+use std::any::Any;
+use tuples::Tuple;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+pub struct Csg {
+    pub operation: CsgOperation,
+    pub left: Object,
+    pub right: Object,
+}
+
+impl Csg {
+    pub fn new(operation: CsgOperation, left: Object, right: Object) -> Csg {
+        Csg {
+            operation,
+            left,
+            right,
+        }
+    }
+
+    pub fn filter_intersections<'a>(&self, mut xs: Vec<Intersection<'a>>) -> Vec<Intersection<'a>> {
+        self.retain_allowed(&mut xs, 0);
+        xs
+    }
+
+    // Filters xs[start..] in place, leaving the intersections before start
+    // alone.
+    fn retain_allowed(&self, xs: &mut Vec<Intersection>, start: usize) {
+        let mut inl = false;
+        let mut inr = false;
+        let mut kept = start;
+
+        for i in start..xs.len() {
+            let lhit = self.left.includes(xs[i].object);
+
+            if intersection_allowed(self.operation, lhit, inl, inr) {
+                xs.swap(kept, i);
+                kept += 1;
+            }
+
+            if lhit {
+                inl = !inl;
+            } else {
+                inr = !inr;
+            }
+        }
+
+        xs.truncate(kept);
+    }
+
+    fn intersect_children_into<'a>(&'a self, local_ray: &Ray, result: &mut Vec<Intersection<'a>>) {
+        let start = result.len();
+        self.left.intersect_into(local_ray, result);
+        self.right.intersect_into(local_ray, result);
+        result[start..].sort_by(|a, b| a.t.partial_cmp(&b.t).unwrap());
+        self.retain_allowed(result, start);
+    }
+}
+
+pub fn intersection_allowed(operation: CsgOperation, lhit: bool, inl: bool, inr: bool) -> bool {
+    match operation {
+        CsgOperation::Union => (lhit && !inr) || (!lhit && !inl),
+        CsgOperation::Intersection => (lhit && inr) || (!lhit && inl),
+        CsgOperation::Difference => (lhit && !inr) || (!lhit && inl),
+    }
+}
+
+impl Shape for Csg {
+    fn intersects_at(&self, ray: &Ray) -> Vec<f64> {
+        let mut xs = vec![];
+        self.intersect_children_into(ray, &mut xs);
+        xs.iter().map(|intersection| intersection.t).collect()
+    }
+
+    fn intersect_into<'a>(
+        &'a self,
+        _object: &'a Object,
+        local_ray: &Ray,
+        result: &mut Vec<Intersection<'a>>,
+    ) {
+        self.intersect_children_into(local_ray, result)
+    }
+
+    fn local_normal_at(&self, _local_point: &Tuple) -> Tuple {
+        unreachable!("normals are computed on the children of a csg")
+    }
+
+    fn bounds(&self) -> Aabb {
+        let mut bounds = self.left.parent_space_bounds();
+        bounds.add_box(&self.right.parent_space_bounds());
+        bounds
+    }
+
+    fn propagate_transform(&self, parent_transform: &[[f64; 4]; 4]) {
+        self.left.set_parent_transform(*parent_transform);
+        self.right.set_parent_transform(*parent_transform);
+    }
+
+    fn includes(&self, object: &Object) -> bool {
+        self.left.includes(object) || self.right.includes(object)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::intersections::Intersection;
+    use crate::object::Object;
+    use crate::shapes::{
+        csg::{intersection_allowed, Csg, CsgOperation},
+        spheres::Sphere,
+    };
+    use matrices::IDENTITY;
+    use rays::Ray;
+    use transformations::MatrixTransformations;
+    use tuples::{point, vector};
+
+    fn csg_of(operation: CsgOperation, left: Object, right: Object) -> Object {
+        Object::new(Box::new(Csg::new(operation, left, right)))
+    }
+
+    fn children(object: &Object) -> (&Object, &Object) {
+        let csg = object.shape_as::<Csg>().unwrap();
+        (&csg.left, &csg.right)
+    }
+
+    #[test]
+    fn csg_is_created_with_an_operation_and_two_shapes() {
+        let s1 = Object::new(Box::new(Sphere::default()));
+        let s2 = Object::new(Box::new(Sphere::default()));
+        let (id1, id2) = (s1.id, s2.id);
+        let c = csg_of(CsgOperation::Union, s1, s2);
+        let csg = c.shape_as::<Csg>().unwrap();
+        assert_eq!(csg.operation, CsgOperation::Union);
+        assert_eq!(csg.left.id, id1);
+        assert_eq!(csg.right.id, id2);
+    }
+
+    #[test]
+    fn evaluating_the_rule_for_a_union_operation() {
+        let table = [
+            (true, true, true, false),
+            (true, true, false, true),
+            (true, false, true, false),
+            (true, false, false, true),
+            (false, true, true, false),
+            (false, true, false, false),
+            (false, false, true, true),
+            (false, false, false, true),
+        ];
+        for (lhit, inl, inr, result) in table.iter() {
+            assert_eq!(
+                intersection_allowed(CsgOperation::Union, *lhit, *inl, *inr),
+                *result
+            );
+        }
+    }
+
+    #[test]
+    fn evaluating_the_rule_for_an_intersection_operation() {
+        let table = [
+            (true, true, true, true),
+            (true, true, false, false),
+            (true, false, true, true),
+            (true, false, false, false),
+            (false, true, true, true),
+            (false, true, false, true),
+            (false, false, true, false),
+            (false, false, false, false),
+        ];
+        for (lhit, inl, inr, result) in table.iter() {
+            assert_eq!(
+                intersection_allowed(CsgOperation::Intersection, *lhit, *inl, *inr),
+                *result
+            );
+        }
+    }
+
+    #[test]
+    fn evaluating_the_rule_for_a_difference_operation() {
+        let table = [
+            (true, true, true, false),
+            (true, true, false, true),
+            (true, false, true, false),
+            (true, false, false, true),
+            (false, true, true, true),
+            (false, true, false, true),
+            (false, false, true, false),
+            (false, false, false, false),
+        ];
+        for (lhit, inl, inr, result) in table.iter() {
+            assert_eq!(
+                intersection_allowed(CsgOperation::Difference, *lhit, *inl, *inr),
+                *result
+            );
+        }
+    }
+
+    #[test]
+    fn filtering_a_list_of_intersections() {
+        let cases = [
+            (CsgOperation::Union, 0, 3),
+            (CsgOperation::Intersection, 1, 2),
+            (CsgOperation::Difference, 0, 1),
+        ];
+        for (operation, x0, x1) in cases.iter() {
+            let s1 = Object::new(Box::new(Sphere::default()));
+            let s2 = Object::new(Box::new(Sphere::default()));
+            let c = Csg::new(*operation, s1, s2);
+            let xs = vec![
+                Intersection::new(1.0, &c.left),
+                Intersection::new(2.0, &c.right),
+                Intersection::new(3.0, &c.left),
+                Intersection::new(4.0, &c.right),
+            ];
+            let expected = [xs[*x0].clone(), xs[*x1].clone()];
+            let result = c.filter_intersections(xs);
+            assert_eq!(result.len(), 2);
+            assert_eq!(result[0], expected[0]);
+            assert_eq!(result[1], expected[1]);
+        }
+    }
+
+    #[test]
+    fn a_ray_misses_a_csg_object() {
+        let c = csg_of(
+            CsgOperation::Union,
+            Object::new(Box::new(Sphere::default())),
+            Object::new(Box::new(Sphere::default())),
+        );
+        let r = Ray::new(point(0.0, 2.0, -5.0), vector(0.0, 0.0, 1.0));
+        assert!(c.intersect(&r).is_empty());
+    }
+
+    #[test]
+    fn a_ray_hits_a_union_of_overlapping_spheres() {
+        let s1 = Object::new(Box::new(Sphere::default()));
+        let mut s2 = Object::new(Box::new(Sphere::default()));
+        s2.transform = IDENTITY.translate(0.0, 0.0, 0.5);
+        let c = csg_of(CsgOperation::Union, s1, s2);
+        let (left, right) = children(&c);
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        let xs = c.intersect(&r);
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, 4.0);
+        assert_eq!(xs[0].object, left);
+        assert_eq!(xs[1].t, 6.5);
+        assert_eq!(xs[1].object, right);
+    }
+
+    #[test]
+    fn a_csg_filters_only_the_hits_it_appends() {
+        let s1 = Object::new(Box::new(Sphere::default()));
+        let s2 = Object::new(Box::new(Sphere::default()))
+            .with_transform(IDENTITY.translate(0.0, 0.0, 0.5));
+        let c = csg_of(CsgOperation::Union, s1, s2);
+        let other = Object::new(Box::new(Sphere::default()));
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        let mut xs = vec![Intersection::new(1.0, &other)];
+        c.intersect_into(&r, &mut xs);
+        assert_eq!(
+            xs.iter().map(|i| i.t).collect::<Vec<_>>(),
+            vec![1.0, 4.0, 6.5]
+        );
+        assert_eq!(xs[0].object, &other);
+    }
+
+    #[test]
+    fn a_csg_includes_its_children() {
+        let c = csg_of(
+            CsgOperation::Difference,
+            Object::new(Box::new(Sphere::default())),
+            Object::new(Box::new(Sphere::default())),
+        );
+        let (left, right) = children(&c);
+        let other = Object::new(Box::new(Sphere::default()));
+        assert!(c.includes(left));
+        assert!(c.includes(right));
+        assert!(!c.includes(&other));
+    }
+}
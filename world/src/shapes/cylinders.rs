@@ -0,0 +1,162 @@
+use crate::bounds::Aabb;
+use crate::shapes::Shape;
+use rays::Ray;
+use std::any::Any;
+use tuples::{point, vector, Tuple, EPSILON};
+
+// A unit-radius cylinder around the y axis, truncated to
+// minimum < y < maximum and optionally capped at both ends.
+pub struct Cylinder {
+    pub minimum: f64,
+    pub maximum: f64,
+    pub closed: bool,
+}
+
+impl Default for Cylinder {
+    fn default() -> Cylinder {
+        Cylinder {
+            minimum: f64::NEG_INFINITY,
+            maximum: f64::INFINITY,
+            closed: false,
+        }
+    }
+}
+
+impl Cylinder {
+    pub fn new(minimum: f64, maximum: f64, closed: bool) -> Cylinder {
+        Cylinder {
+            minimum,
+            maximum,
+            closed,
+        }
+    }
+
+    fn intersect_caps(&self, ray: &Ray, xs: &mut Vec<f64>) {
+        if !self.closed || ray.direction.y.abs() < EPSILON {
+            return;
+        }
+        for y in &[self.minimum, self.maximum] {
+            let t = (y - ray.origin.y) / ray.direction.y;
+            if within_radius(ray, t, 1.0) {
+                xs.push(t);
+            }
+        }
+    }
+}
+
+// Whether the ray at t lies within the given radius of the y axis, which is
+// how the caps of cylinders and cones are hit.
+pub(crate) fn within_radius(ray: &Ray, t: f64, radius: f64) -> bool {
+    let x = ray.origin.x + t * ray.direction.x;
+    let z = ray.origin.z + t * ray.direction.z;
+    x.powf(2.0) + z.powf(2.0) <= radius.powf(2.0)
+}
+
+impl Shape for Cylinder {
+    fn intersects_at(&self, ray: &Ray) -> Vec<f64> {
+        let mut xs = vec![];
+        let a = ray.direction.x.powf(2.0) + ray.direction.z.powf(2.0);
+        if a.abs() >= EPSILON {
+            let b = 2.0 * ray.origin.x * ray.direction.x + 2.0 * ray.origin.z * ray.direction.z;
+            let c = ray.origin.x.powf(2.0) + ray.origin.z.powf(2.0) - 1.0;
+            let discriminant = b.powf(2.0) - 4.0 * a * c;
+            if discriminant < 0.0 {
+                return xs;
+            }
+            let t0 = (-b - discriminant.sqrt()) / (2.0 * a);
+            let t1 = (-b + discriminant.sqrt()) / (2.0 * a);
+            for t in &[t0.min(t1), t0.max(t1)] {
+                let y = ray.origin.y + t * ray.direction.y;
+                if self.minimum < y && y < self.maximum {
+                    xs.push(*t);
+                }
+            }
+        }
+        self.intersect_caps(ray, &mut xs);
+        xs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        xs
+    }
+
+    // Points on the rim belong to the cap, so a hit just inside the edge of
+    // a closed cylinder gets the flat cap normal rather than a radial one.
+    fn local_normal_at(&self, local_point: &Tuple) -> Tuple {
+        let dist = local_point.x.powf(2.0) + local_point.z.powf(2.0);
+        if dist < 1.0 && local_point.y >= self.maximum - EPSILON {
+            vector(0.0, 1.0, 0.0)
+        } else if dist < 1.0 && local_point.y <= self.minimum + EPSILON {
+            vector(0.0, -1.0, 0.0)
+        } else {
+            vector(local_point.x, 0.0, local_point.z)
+        }
+    }
+
+    fn bounds(&self) -> Aabb {
+        Aabb::new(
+            point(-1.0, self.minimum, -1.0),
+            point(1.0, self.maximum, 1.0),
+        )
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::shapes::{cylinders::Cylinder, Shape};
+    use rays::Ray;
+    use tuples::{normalize, point, vector};
+
+    #[test]
+    fn a_ray_strikes_a_cylinder() {
+        let c = Cylinder::default();
+        let cases = [
+            (point(1.0, 0.0, -5.0), vector(0.0, 0.0, 1.0), 5.0, 5.0),
+            (point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0), 4.0, 6.0),
+        ];
+        for (origin, direction, t0, t1) in cases.iter() {
+            let r = Ray::new(*origin, normalize(direction));
+            assert_eq!(c.intersects_at(&r), vec![*t0, *t1]);
+        }
+        let miss = Ray::new(point(0.0, 0.0, -5.0), vector(1.0, 1.0, 1.0));
+        assert!(c.intersects_at(&miss).is_empty());
+    }
+
+    #[test]
+    fn intersecting_the_caps_of_a_closed_cylinder() {
+        let c = Cylinder::new(1.0, 2.0, true);
+        let cases = [
+            (point(0.0, 3.0, 0.0), vector(0.0, -1.0, 0.0), 2),
+            (point(0.0, 3.0, -2.0), vector(0.0, -1.0, 2.0), 2),
+            (point(0.0, 4.0, -2.0), vector(0.0, -1.0, 1.0), 2),
+            (point(0.0, 0.0, -2.0), vector(0.0, 1.0, 2.0), 2),
+            (point(0.0, -1.0, -2.0), vector(0.0, 1.0, 1.0), 2),
+        ];
+        for (origin, direction, count) in cases.iter() {
+            let r = Ray::new(*origin, normalize(direction));
+            assert_eq!(c.intersects_at(&r).len(), *count);
+        }
+    }
+
+    #[test]
+    fn normal_at_the_rim_prefers_the_cap() {
+        let c = Cylinder::new(1.0, 2.0, true);
+        assert_eq!(
+            c.local_normal_at(&point(0.999, 2.0, 0.0)),
+            vector(0.0, 1.0, 0.0)
+        );
+        assert_eq!(
+            c.local_normal_at(&point(0.0, 1.0, 0.5)),
+            vector(0.0, -1.0, 0.0)
+        );
+        assert_eq!(
+            c.local_normal_at(&point(1.0, 1.999, 0.0)),
+            vector(1.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            c.local_normal_at(&point(0.0, 1.5, -1.0)),
+            vector(0.0, 0.0, -1.0)
+        );
+    }
+}
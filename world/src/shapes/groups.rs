@@ -0,0 +1,387 @@
+use crate::bounds::Aabb;
+use crate::intersections::Intersection;
+use crate::object::Object;
+use crate::shapes::Shape;
+use rays::Ray;
+use std::any::Any;
+use tuples::Tuple;
+
+#[derive(Default)]
+pub struct Group {
+    pub children: Vec<Object>,
+    bounds: Aabb,
+    child_bounds: Vec<Aabb>,
+}
+
+impl Group {
+    pub fn new(children: Vec<Object>) -> Group {
+        let mut group = Group::default();
+        for child in children {
+            group.add_child(child);
+        }
+        group
+    }
+
+    pub fn add_child(&mut self, child: Object) {
+        let bounds = child.parent_space_bounds();
+        self.bounds.add_box(&bounds);
+        self.child_bounds.push(bounds);
+        self.children.push(child);
+    }
+
+    pub fn divide(&mut self, threshold: usize) {
+        if threshold <= self.children.len() {
+            let (left, right) = self.partition_children();
+            if !left.is_empty() {
+                self.make_subgroup(left);
+            }
+            if !right.is_empty() {
+                self.make_subgroup(right);
+            }
+        }
+        for child in self.children.iter_mut() {
+            child.shape.divide(threshold);
+        }
+    }
+
+    pub fn partition_children(&mut self) -> (Vec<Object>, Vec<Object>) {
+        let (left_bounds, right_bounds) = self.bounds.split();
+        let mut left = vec![];
+        let mut right = vec![];
+        let children = std::mem::take(&mut self.children);
+        let child_bounds = std::mem::take(&mut self.child_bounds);
+        for (child, bounds) in children.into_iter().zip(child_bounds) {
+            if left_bounds.contains_box(&bounds) {
+                left.push(child);
+            } else if right_bounds.contains_box(&bounds) {
+                right.push(child);
+            } else {
+                self.children.push(child);
+                self.child_bounds.push(bounds);
+            }
+        }
+        (left, right)
+    }
+
+    fn make_subgroup(&mut self, children: Vec<Object>) {
+        let parent_transform = children[0].parent_transform();
+        let subgroup = Object::new(Box::new(Group::new(children)));
+        subgroup.set_parent_transform(parent_transform);
+        self.add_child(subgroup);
+    }
+
+    // Appends the children's intersections after whatever result already
+    // holds, sorted among themselves.
+    fn intersect_children_into<'a>(&'a self, local_ray: &Ray, result: &mut Vec<Intersection<'a>>) {
+        if !self.bounds.intersects(local_ray) {
+            return;
+        }
+        let start = result.len();
+        for (child, bounds) in self.children.iter().zip(self.child_bounds.iter()) {
+            if bounds.intersects(local_ray) {
+                child.intersect_into(local_ray, result);
+            }
+        }
+        result[start..].sort_by(|a, b| a.t.partial_cmp(&b.t).unwrap());
+    }
+}
+
+impl Shape for Group {
+    fn intersects_at(&self, ray: &Ray) -> Vec<f64> {
+        let mut xs = vec![];
+        self.intersect_children_into(ray, &mut xs);
+        xs.iter().map(|intersection| intersection.t).collect()
+    }
+
+    fn intersect_into<'a>(
+        &'a self,
+        _object: &'a Object,
+        local_ray: &Ray,
+        result: &mut Vec<Intersection<'a>>,
+    ) {
+        self.intersect_children_into(local_ray, result)
+    }
+
+    fn local_normal_at(&self, _local_point: &Tuple) -> Tuple {
+        unreachable!("normals are computed on the children of a group")
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.bounds
+    }
+
+    fn propagate_transform(&self, parent_transform: &[[f64; 4]; 4]) {
+        for child in self.children.iter() {
+            child.set_parent_transform(*parent_transform);
+        }
+    }
+
+    fn divide(&mut self, threshold: usize) {
+        Group::divide(self, threshold);
+    }
+
+    fn includes(&self, object: &Object) -> bool {
+        self.children.iter().any(|child| child.includes(object))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::intersections::Intersection;
+    use crate::object::Object;
+    use crate::shapes::{groups::Group, spheres::Sphere, test::TestShape, Shape};
+    use core::f64::consts::PI;
+    use matrices::IDENTITY;
+    use rays::Ray;
+    use transformations::MatrixTransformations;
+    use tuples::{point, vector};
+
+    #[test]
+    fn intersecting_a_ray_with_an_empty_group() {
+        let g = Object::new(Box::new(Group::default()));
+        let r = Ray::new(point(0.0, 0.0, 0.0), vector(0.0, 0.0, 1.0));
+        assert!(g.intersect(&r).is_empty());
+    }
+
+    #[test]
+    fn intersecting_a_ray_with_a_nonempty_group() {
+        let s1 = Object::new(Box::new(Sphere::default()));
+        let mut s2 = Object::new(Box::new(Sphere::default()));
+        s2.transform = IDENTITY.translate(0.0, 0.0, -3.0);
+        let mut s3 = Object::new(Box::new(Sphere::default()));
+        s3.transform = IDENTITY.translate(5.0, 0.0, 0.0);
+        let g = Object::new(Box::new(Group::new(vec![s1, s2, s3])));
+        let group = g.shape_as::<Group>().unwrap();
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        let xs = g.intersect(&r);
+        assert_eq!(xs.len(), 4);
+        assert_eq!(xs[0].object, &group.children[1]);
+        assert_eq!(xs[1].object, &group.children[1]);
+        assert_eq!(xs[2].object, &group.children[0]);
+        assert_eq!(xs[3].object, &group.children[0]);
+        assert!(xs.windows(2).all(|pair| pair[0].t <= pair[1].t));
+    }
+
+    #[test]
+    fn a_group_appends_its_hits_to_the_callers_buffer() {
+        let s1 = Object::new(Box::new(Sphere::default()));
+        let s2 = Object::new(Box::new(Sphere::default()))
+            .with_transform(IDENTITY.translate(0.0, 0.0, -3.0));
+        let g = Object::new(Box::new(Group::new(vec![s1, s2])));
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        let mut xs = Vec::with_capacity(8);
+        xs.push(Intersection::new(-1.0, &g));
+        let buffer = xs.as_ptr();
+        g.intersect_into(&r, &mut xs);
+        assert_eq!(xs.as_ptr(), buffer);
+        assert_eq!(
+            xs.iter().map(|i| i.t).collect::<Vec<_>>(),
+            vec![-1.0, 1.0, 3.0, 4.0, 6.0]
+        );
+    }
+
+    #[test]
+    fn intersecting_a_transformed_group() {
+        let mut s = Object::new(Box::new(Sphere::default()));
+        s.transform = IDENTITY.translate(5.0, 0.0, 0.0);
+        let mut g = Object::new(Box::new(Group::new(vec![s])));
+        g.set_transform(IDENTITY.scale(2.0, 2.0, 2.0));
+        let r = Ray::new(point(10.0, 0.0, -10.0), vector(0.0, 0.0, 1.0));
+        assert_eq!(g.intersect(&r).len(), 2);
+    }
+
+    #[test]
+    fn the_group_shape_returns_its_children_hits() {
+        let mut s = Object::new(Box::new(Sphere::default()));
+        s.transform = IDENTITY.translate(0.0, 0.0, -3.0);
+        let g = Group::new(vec![Object::new(Box::new(Sphere::default())), s]);
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        assert_eq!(g.intersects_at(&r), vec![1.0, 3.0, 4.0, 6.0]);
+    }
+
+    #[test]
+    fn a_group_includes_its_children() {
+        let s = Object::new(Box::new(Sphere::default()));
+        let g = Object::new(Box::new(Group::new(vec![s])));
+        let other = Object::new(Box::new(Sphere::default()));
+        let group = g.shape_as::<Group>().unwrap();
+        assert!(g.includes(&group.children[0]));
+        assert!(!g.includes(&other));
+    }
+
+    #[test]
+    fn finding_the_normal_on_a_child_object() {
+        let mut s = Object::new(Box::new(Sphere::default()));
+        s.transform = IDENTITY.translate(5.0, 0.0, 0.0);
+        let mut g2 = Object::new(Box::new(Group::new(vec![s])));
+        g2.set_transform(IDENTITY.scale(1.0, 2.0, 3.0));
+        let mut g1 = Object::new(Box::new(Group::new(vec![g2])));
+        g1.set_transform(IDENTITY.rotate_y(PI / 2.0));
+        let g2 = &g1.shape_as::<Group>().unwrap().children[0];
+        let s = &g2.shape_as::<Group>().unwrap().children[0];
+        let n = s.normal_at(&point(1.7321, 1.1547, -5.5774));
+        assert_eq!(n, vector(0.28570, 0.42854, -0.85716));
+    }
+
+    #[test]
+    fn assigning_a_group_transform_directly_updates_its_children() {
+        let mut g = Object::new(Box::new(Group::new(vec![Object::new(Box::new(
+            Sphere::default(),
+        ))])));
+        g.transform = IDENTITY.translate(0.0, 0.0, 5.0);
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        let xs = g.intersect(&r);
+        assert_eq!(xs.iter().map(|i| i.t).collect::<Vec<_>>(), vec![9.0, 11.0]);
+        let hit = r.position(xs[0].t);
+        assert_eq!(xs[0].object.normal_at(&hit), vector(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn converting_a_point_from_world_to_object_space() {
+        let mut s = Object::new(Box::new(Sphere::default()));
+        s.transform = IDENTITY.translate(5.0, 0.0, 0.0);
+        let mut g2 = Object::new(Box::new(Group::new(vec![s])));
+        g2.set_transform(IDENTITY.scale(2.0, 2.0, 2.0));
+        let mut g1 = Object::new(Box::new(Group::new(vec![g2])));
+        g1.set_transform(IDENTITY.rotate_y(PI / 2.0));
+        let g2 = &g1.shape_as::<Group>().unwrap().children[0];
+        let s = &g2.shape_as::<Group>().unwrap().children[0];
+        assert_eq!(
+            s.world_to_object(&point(-2.0, 0.0, -10.0)),
+            point(0.0, 0.0, -1.0)
+        );
+    }
+
+    #[test]
+    fn converting_a_normal_from_object_to_world_space() {
+        let mut s = Object::new(Box::new(Sphere::default()));
+        s.transform = IDENTITY.translate(5.0, 0.0, 0.0);
+        let mut g2 = Object::new(Box::new(Group::new(vec![s])));
+        g2.set_transform(IDENTITY.scale(1.0, 2.0, 3.0));
+        let mut g1 = Object::new(Box::new(Group::new(vec![g2])));
+        g1.set_transform(IDENTITY.rotate_y(PI / 2.0));
+        let g2 = &g1.shape_as::<Group>().unwrap().children[0];
+        let s = &g2.shape_as::<Group>().unwrap().children[0];
+        let third = 3.0_f64.sqrt() / 3.0;
+        assert_eq!(
+            s.normal_to_world(&vector(third, third, third)),
+            vector(0.28571, 0.42857, -0.85714)
+        );
+    }
+
+    #[test]
+    fn children_are_skipped_when_the_ray_misses_the_group_bounds() {
+        let g = Object::new(Box::new(Group::new(vec![Object::new(Box::new(
+            TestShape::default(),
+        ))])));
+        let child = &g.shape_as::<Group>().unwrap().children[0];
+        let test_shape = child.shape_as::<TestShape>().unwrap();
+
+        let miss = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 1.0, 0.0));
+        g.intersect(&miss);
+        assert_eq!(test_shape.intersect_calls.get(), 0);
+
+        let hit = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        g.intersect(&hit);
+        assert_eq!(test_shape.intersect_calls.get(), 1);
+    }
+
+    #[test]
+    fn bounds_checks_do_not_change_the_intersections() {
+        let mut children = vec![];
+        for i in 0..5 {
+            let mut s = Object::new(Box::new(Sphere::default()));
+            s.transform =
+                IDENTITY
+                    .scale(0.5, 0.5, 0.5)
+                    .translate(i as f64 - 2.0, (i % 2) as f64, i as f64);
+            children.push(s);
+        }
+        let g = Object::new(Box::new(Group::new(children)));
+        let group = g.shape_as::<Group>().unwrap();
+        let rays = [
+            Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0)),
+            Ray::new(point(-5.0, 0.2, 0.0), vector(1.0, 0.0, 0.2)),
+            Ray::new(point(2.0, 1.0, -5.0), vector(0.0, 0.0, 1.0)),
+            Ray::new(point(0.0, 10.0, 0.0), vector(0.0, 0.0, 1.0)),
+        ];
+        for r in rays.iter() {
+            let mut expected: Vec<f64> = group
+                .children
+                .iter()
+                .flat_map(|child| child.intersect(r))
+                .map(|intersection| intersection.t)
+                .collect();
+            expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let actual: Vec<f64> = g.intersect(r).iter().map(|i| i.t).collect();
+            assert_eq!(actual, expected);
+        }
+    }
+
+    fn spheres_along_x(xs: &[f64]) -> Vec<Object> {
+        xs.iter()
+            .map(|x| {
+                let mut s = Object::new(Box::new(Sphere::default()));
+                s.transform = IDENTITY.translate(*x, 0.0, 0.0);
+                s
+            })
+            .collect()
+    }
+
+    #[test]
+    fn partitioning_a_groups_children() {
+        let mut g = Group::new(spheres_along_x(&[-2.0, 2.0, 0.0]));
+        let (left, right) = g.partition_children();
+        assert_eq!(g.children.len(), 1);
+        assert_eq!(left.len(), 1);
+        assert_eq!(right.len(), 1);
+        assert_eq!(left[0].transform, IDENTITY.translate(-2.0, 0.0, 0.0));
+        assert_eq!(right[0].transform, IDENTITY.translate(2.0, 0.0, 0.0));
+        assert_eq!(g.children[0].transform, IDENTITY.translate(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn dividing_six_spheres_into_two_subgroups() {
+        let mut g = Group::new(spheres_along_x(&[-6.0, -4.0, -2.0, 2.0, 4.0, 6.0]));
+        g.divide(4);
+        assert_eq!(g.children.len(), 2);
+        for child in g.children.iter() {
+            let subgroup = child.shape_as::<Group>().unwrap();
+            assert_eq!(subgroup.children.len(), 3);
+        }
+    }
+
+    #[test]
+    fn dividing_a_group_does_not_change_its_intersections() {
+        let xs = [-6.0, -4.0, -2.0, -0.5, 0.5, 2.0, 4.0, 6.0];
+        let mut flat = Object::new(Box::new(Group::new(spheres_along_x(&xs))));
+        flat.set_transform(IDENTITY.rotate_z(PI / 6.0).translate(0.0, 1.0, 0.0));
+        let mut divided = Object::new(Box::new(Group::new(spheres_along_x(&xs))));
+        divided.shape.divide(2);
+        divided.set_transform(IDENTITY.rotate_z(PI / 6.0).translate(0.0, 1.0, 0.0));
+        let mut divided_late = Object::new(Box::new(Group::new(spheres_along_x(&xs))));
+        divided_late.set_transform(IDENTITY.rotate_z(PI / 6.0).translate(0.0, 1.0, 0.0));
+        divided_late.shape.divide(2);
+        let rays = [
+            Ray::new(point(0.0, 1.0, -5.0), vector(0.0, 0.0, 1.0)),
+            Ray::new(point(-10.0, -3.0, 0.0), vector(1.0, 0.5, 0.0)),
+            Ray::new(point(3.0, 3.0, -5.0), vector(0.0, 0.0, 1.0)),
+        ];
+        for r in rays.iter() {
+            let expected: Vec<f64> = flat.intersect(r).iter().map(|i| i.t).collect();
+            for divided in [&divided, &divided_late].iter() {
+                let actual = divided.intersect(r);
+                assert_eq!(actual.iter().map(|i| i.t).collect::<Vec<_>>(), expected);
+                if let Some(i) = actual.first() {
+                    let p = r.position(i.t);
+                    let expected_hit = &flat.intersect(r)[0];
+                    assert_eq!(i.object.normal_at(&p), expected_hit.object.normal_at(&p));
+                }
+            }
+        }
+    }
+}
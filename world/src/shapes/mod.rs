@@ -1,40 +1,92 @@
+pub mod cones;
+pub mod csg;
+pub mod cylinders;
+pub mod groups;
 pub mod planes;
+pub mod rectangles;
+pub mod slabs;
 pub mod spheres;
+pub mod triangles;
 
+use crate::bounds::Aabb;
+use crate::intersections::Intersection;
+use crate::object::Object;
 use rays::Ray;
 use std::any::Any;
 use tuples::Tuple;
 
 pub trait Shape: Any {
-    fn intersects_at(&self, ray: &Ray) -> Option<[f64; 2]>;
+    fn intersects_at(&self, ray: &Ray) -> Vec<f64>;
     fn local_normal_at(&self, local_point: &Tuple) -> Tuple;
+    fn bounds(&self) -> Aabb;
     fn as_any(&self) -> &dyn Any;
+
+    // Appends the intersections to a caller-owned buffer, so a render can
+    // reuse one buffer across rays instead of collecting a list per object.
+    fn intersect_into<'a>(
+        &'a self,
+        object: &'a Object,
+        local_ray: &Ray,
+        result: &mut Vec<Intersection<'a>>,
+    ) {
+        result.extend(
+            self.intersects_at(local_ray)
+                .into_iter()
+                .map(|t| Intersection::new(t, object)),
+        );
+    }
+
+    fn propagate_transform(&self, _parent_transform: &[[f64; 4]; 4]) {}
+
+    fn divide(&mut self, _threshold: usize) {}
+
+    fn includes(&self, _object: &Object) -> bool {
+        false
+    }
+
+    fn normal_at_with_uv(&self, local_point: &Tuple, _u: Option<f64>, _v: Option<f64>) -> Tuple {
+        self.local_normal_at(local_point)
+    }
+
+    // Texture coordinates of a point in object space, for shapes that
+    // have a natural mapping.
+    fn uv_map(&self, _local_point: &Tuple) -> Option<(f64, f64)> {
+        None
+    }
 }
 
 pub mod test {
+    use crate::bounds::Aabb;
     use crate::shapes::Shape;
     use rays::Ray;
     use std::any::Any;
-    use std::cell::RefCell;
-    use tuples::{vector, Tuple};
+    use std::cell::{Cell, RefCell};
+    use tuples::{point, vector, Tuple};
 
     pub struct TestShape {
         pub saved_ray: RefCell<Option<Ray>>,
+        pub intersect_calls: Cell<u32>,
     }
 
     impl Shape for TestShape {
-        fn intersects_at(&self, ray: &Ray) -> Option<[f64; 2]> {
+        fn intersects_at(&self, ray: &Ray) -> Vec<f64> {
+            self.intersect_calls.set(self.intersect_calls.get() + 1);
             *self.saved_ray.borrow_mut() = Some(Ray {
                 origin: ray.origin,
                 direction: ray.direction,
+                time: ray.time,
             });
-            None
+            vec![]
         }
 
         fn local_normal_at(&self, local_point: &Tuple) -> Tuple {
             vector(local_point.x, local_point.y, local_point.z)
         }
 
+        fn bounds(&self) -> Aabb {
+            Aabb::new(point(-1.0, -1.0, -1.0), point(1.0, 1.0, 1.0))
+        }
+
         fn as_any(&self) -> &dyn Any {
             self
         }
@@ -44,6 +96,7 @@ pub mod test {
         fn default() -> TestShape {
             TestShape {
                 saved_ray: RefCell::new(None),
+                intersect_calls: Cell::new(0),
             }
         }
     }
@@ -1,29 +1,32 @@
+use crate::bounds::Aabb;
 use crate::shapes::Shape;
+use crate::INTERSECTION_EPSILON;
 use rays::Ray;
 use std::any::Any;
-use tuples::{vector, Tuple};
+use tuples::{point, vector, Tuple};
 
+#[derive(Default)]
 pub struct Plane {}
 
-impl Default for Plane {
-    fn default() -> Plane {
-        Plane {}
-    }
-}
-
 impl Shape for Plane {
-    fn intersects_at(&self, ray: &Ray) -> Option<[f64; 2]> {
-        if ray.direction.y.abs() < 0.0001 {
-            return None;
+    fn intersects_at(&self, ray: &Ray) -> Vec<f64> {
+        if ray.direction.y.abs() < INTERSECTION_EPSILON {
+            return vec![];
         }
-        let t = -ray.origin.y / ray.direction.y;
-        Some([t, t])
+        vec![-ray.origin.y / ray.direction.y]
     }
 
     fn local_normal_at(&self, _local_point: &Tuple) -> Tuple {
         vector(0.0, 1.0, 0.0)
     }
 
+    fn bounds(&self) -> Aabb {
+        Aabb::new(
+            point(f64::NEG_INFINITY, 0.0, f64::NEG_INFINITY),
+            point(f64::INFINITY, 0.0, f64::INFINITY),
+        )
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -49,44 +52,32 @@ mod test {
     #[test]
     fn intersect_with_a_ray_parallel_to_the_plane() {
         let p = Plane::default();
-        let r = Ray {
-            origin: point(0.0, 10.0, 0.0),
-            direction: vector(0.0, 0.0, 1.0),
-        };
+        let r = Ray::new(point(0.0, 10.0, 0.0), vector(0.0, 0.0, 1.0));
         let xs = p.intersects_at(&r);
-        assert!(xs.is_none());
+        assert!(xs.is_empty());
     }
 
     #[test]
     fn intersect_with_a_coplanar_ray() {
         let p = Plane::default();
-        let r = Ray {
-            origin: point(0.0, 0.0, 0.0),
-            direction: vector(0.0, 0.0, 1.0),
-        };
+        let r = Ray::new(point(0.0, 0.0, 0.0), vector(0.0, 0.0, 1.0));
         let xs = p.intersects_at(&r);
-        assert!(xs.is_none());
+        assert!(xs.is_empty());
     }
 
     #[test]
     fn ray_intersecting_a_plane_from_above() {
         let p = Plane::default();
-        let r = Ray {
-            origin: point(0.0, 1.0, 0.0),
-            direction: vector(0.0, -1.0, 0.0),
-        };
-        let xs = p.intersects_at(&r).unwrap();
+        let r = Ray::new(point(0.0, 1.0, 0.0), vector(0.0, -1.0, 0.0));
+        let xs = p.intersects_at(&r);
         assert_eq!(xs[0], 1.0);
     }
 
     #[test]
     fn ray_intersecting_a_plane_from_below() {
         let p = Plane::default();
-        let r = Ray {
-            origin: point(0.0, -1.0, 0.0),
-            direction: vector(0.0, 1.0, 0.0),
-        };
-        let xs = p.intersects_at(&r).unwrap();
+        let r = Ray::new(point(0.0, -1.0, 0.0), vector(0.0, 1.0, 0.0));
+        let xs = p.intersects_at(&r);
         assert_eq!(xs[0], 1.0);
     }
 }
@@ -0,0 +1,116 @@
+use crate::bounds::Aabb;
+use crate::shapes::Shape;
+use crate::INTERSECTION_EPSILON;
+use rays::Ray;
+use std::any::Any;
+use tuples::{point, vector, Tuple};
+
+// A finite piece of the y = 0 plane.
+pub struct Rectangle {
+    pub xmin: f64,
+    pub xmax: f64,
+    pub zmin: f64,
+    pub zmax: f64,
+}
+
+impl Rectangle {
+    pub fn new(xmin: f64, xmax: f64, zmin: f64, zmax: f64) -> Rectangle {
+        Rectangle {
+            xmin,
+            xmax,
+            zmin,
+            zmax,
+        }
+    }
+}
+
+impl Shape for Rectangle {
+    fn intersects_at(&self, ray: &Ray) -> Vec<f64> {
+        if ray.direction.y.abs() < INTERSECTION_EPSILON {
+            return vec![];
+        }
+        let t = -ray.origin.y / ray.direction.y;
+        let p = ray.position(t);
+        if p.x < self.xmin || p.x > self.xmax || p.z < self.zmin || p.z > self.zmax {
+            return vec![];
+        }
+        vec![t]
+    }
+
+    fn local_normal_at(&self, _local_point: &Tuple) -> Tuple {
+        vector(0.0, 1.0, 0.0)
+    }
+
+    fn bounds(&self) -> Aabb {
+        Aabb::new(
+            point(self.xmin, 0.0, self.zmin),
+            point(self.xmax, 0.0, self.zmax),
+        )
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::shapes::{rectangles::Rectangle, Shape};
+    use rays::Ray;
+    use tuples::{point, vector};
+
+    #[test]
+    fn normal_of_a_rectangle_is_constant_everywhere() {
+        let r = Rectangle::new(-1.0, 1.0, -2.0, 2.0);
+        assert_eq!(
+            r.local_normal_at(&point(0.0, 0.0, 0.0)),
+            vector(0.0, 1.0, 0.0)
+        );
+        assert_eq!(
+            r.local_normal_at(&point(0.5, 0.0, -1.5)),
+            vector(0.0, 1.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn a_ray_hits_inside_the_rectangle() {
+        let r = Rectangle::new(-1.0, 1.0, -2.0, 2.0);
+        for p in &[
+            point(0.0, 1.0, 0.0),
+            point(0.99, 1.0, 1.99),
+            point(-0.99, 1.0, -1.99),
+        ] {
+            let ray = Ray::new(*p, vector(0.0, -1.0, 0.0));
+            assert_eq!(r.intersects_at(&ray), vec![1.0]);
+        }
+    }
+
+    #[test]
+    fn a_ray_misses_just_outside_each_edge() {
+        let r = Rectangle::new(-1.0, 1.0, -2.0, 2.0);
+        for p in &[
+            point(1.01, 1.0, 0.0),
+            point(-1.01, 1.0, 0.0),
+            point(0.0, 1.0, 2.01),
+            point(0.0, 1.0, -2.01),
+        ] {
+            let ray = Ray::new(*p, vector(0.0, -1.0, 0.0));
+            assert!(r.intersects_at(&ray).is_empty());
+        }
+    }
+
+    #[test]
+    fn a_ray_parallel_to_the_rectangle_misses() {
+        let r = Rectangle::new(-1.0, 1.0, -2.0, 2.0);
+        let ray = Ray::new(point(0.0, 1.0, 0.0), vector(0.0, 0.0, 1.0));
+        assert!(r.intersects_at(&ray).is_empty());
+    }
+
+    #[test]
+    fn bounds_of_a_rectangle() {
+        let r = Rectangle::new(-1.0, 1.0, -2.0, 2.0);
+        let b = r.bounds();
+        assert_eq!(b.min, point(-1.0, 0.0, -2.0));
+        assert_eq!(b.max, point(1.0, 0.0, 2.0));
+    }
+}
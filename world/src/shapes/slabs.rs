@@ -0,0 +1,91 @@
+use crate::bounds::Aabb;
+use crate::shapes::Shape;
+use crate::INTERSECTION_EPSILON;
+use rays::Ray;
+use std::any::Any;
+use tuples::{point, vector, Tuple};
+
+// The space between the planes y = -half_thickness and y = half_thickness.
+pub struct Slab {
+    pub half_thickness: f64,
+}
+
+impl Slab {
+    pub fn new(half_thickness: f64) -> Slab {
+        Slab { half_thickness }
+    }
+}
+
+impl Shape for Slab {
+    fn intersects_at(&self, ray: &Ray) -> Vec<f64> {
+        if ray.direction.y.abs() < INTERSECTION_EPSILON {
+            return vec![];
+        }
+        let t1 = (-self.half_thickness - ray.origin.y) / ray.direction.y;
+        let t2 = (self.half_thickness - ray.origin.y) / ray.direction.y;
+        vec![t1.min(t2), t1.max(t2)]
+    }
+
+    fn local_normal_at(&self, local_point: &Tuple) -> Tuple {
+        if local_point.y >= 0.0 {
+            vector(0.0, 1.0, 0.0)
+        } else {
+            vector(0.0, -1.0, 0.0)
+        }
+    }
+
+    fn bounds(&self) -> Aabb {
+        Aabb::new(
+            point(f64::NEG_INFINITY, -self.half_thickness, f64::NEG_INFINITY),
+            point(f64::INFINITY, self.half_thickness, f64::INFINITY),
+        )
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::shapes::{slabs::Slab, Shape};
+    use rays::Ray;
+    use tuples::{point, vector};
+
+    #[test]
+    fn a_ray_passes_through_both_faces_of_a_slab() {
+        let s = Slab::new(0.5);
+        let down = Ray::new(point(0.0, 2.0, 0.0), vector(0.0, -1.0, 0.0));
+        assert_eq!(s.intersects_at(&down), vec![1.5, 2.5]);
+        let up = Ray::new(point(3.0, -2.0, 1.0), vector(0.0, 1.0, 0.0));
+        assert_eq!(s.intersects_at(&up), vec![1.5, 2.5]);
+        let inside = Ray::new(point(0.0, 0.0, 0.0), vector(0.0, 1.0, 0.0));
+        assert_eq!(s.intersects_at(&inside), vec![-0.5, 0.5]);
+    }
+
+    #[test]
+    fn a_ray_parallel_to_a_slab_misses_it() {
+        let s = Slab::new(0.5);
+        for p in &[
+            point(0.0, 0.25, 0.0),
+            point(0.0, 0.5, 0.0),
+            point(0.0, 2.0, 0.0),
+        ] {
+            let ray = Ray::new(*p, vector(1.0, 0.0, 1.0));
+            assert!(s.intersects_at(&ray).is_empty());
+        }
+    }
+
+    #[test]
+    fn normal_of_a_slab_points_out_of_the_nearest_face() {
+        let s = Slab::new(0.5);
+        assert_eq!(
+            s.local_normal_at(&point(1.0, 0.5, 2.0)),
+            vector(0.0, 1.0, 0.0)
+        );
+        assert_eq!(
+            s.local_normal_at(&point(-3.0, -0.5, 0.0)),
+            vector(0.0, -1.0, 0.0)
+        );
+    }
+}
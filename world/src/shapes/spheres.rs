@@ -1,11 +1,15 @@
+use crate::bounds::Aabb;
+use crate::intersections::Intersection;
 use crate::materials::Material;
 use crate::object::Object;
+use crate::patterns::texture_map::spherical_map;
 use crate::shapes::Shape;
 use rays::Ray;
 use std::any::Any;
 use std::rc::Rc;
 use tuples::{dot, point, Tuple};
 
+#[derive(Default)]
 pub struct Sphere {}
 
 impl Sphere {
@@ -15,10 +19,8 @@ impl Sphere {
         object.material = Rc::new(Material::glass());
         object
     }
-}
 
-impl Shape for Sphere {
-    fn intersects_at(&self, ray: &Ray) -> Option<[f64; 2]> {
+    fn hits(ray: &Ray) -> Option<(f64, f64)> {
         let sphere_to_ray = ray.origin - point(0.0, 0.0, 0.0);
         let a = dot(&ray.direction, &ray.direction);
         let b = 2.0 * dot(&ray.direction, &sphere_to_ray);
@@ -32,21 +34,44 @@ impl Shape for Sphere {
         let t1 = (-b - discriminant.sqrt()) / (2.0 * a);
         let t2 = (-b + discriminant.sqrt()) / (2.0 * a);
 
-        Some([t1, t2])
+        Some((t1, t2))
+    }
+}
+
+impl Shape for Sphere {
+    fn intersects_at(&self, ray: &Ray) -> Vec<f64> {
+        match Sphere::hits(ray) {
+            Some((t1, t2)) => vec![t1, t2],
+            None => vec![],
+        }
+    }
+
+    fn intersect_into<'a>(
+        &'a self,
+        object: &'a Object,
+        local_ray: &Ray,
+        result: &mut Vec<Intersection<'a>>,
+    ) {
+        if let Some((t1, t2)) = Sphere::hits(local_ray) {
+            result.push(Intersection::new(t1, object));
+            result.push(Intersection::new(t2, object));
+        }
     }
 
     fn local_normal_at(&self, local_point: &Tuple) -> Tuple {
         *local_point - point(0.0, 0.0, 0.0)
     }
 
-    fn as_any(&self) -> &dyn Any {
-        self
+    fn bounds(&self) -> Aabb {
+        Aabb::new(point(-1.0, -1.0, -1.0), point(1.0, 1.0, 1.0))
     }
-}
 
-impl Default for Sphere {
-    fn default() -> Sphere {
-        Sphere {}
+    fn uv_map(&self, local_point: &Tuple) -> Option<(f64, f64)> {
+        Some(spherical_map(local_point))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
     }
 }
 
@@ -54,6 +79,7 @@ impl Default for Sphere {
 mod tests {
     use crate::shapes::spheres::Sphere;
     use crate::{intersections::Intersection, object::Object};
+    use colors::Color;
     use matrices::IDENTITY;
     use rays::Ray;
     use transformations::MatrixTransformations;
@@ -61,77 +87,59 @@ mod tests {
 
     #[test]
     fn ray_intersects_a_sphere_at_two_points() {
-        let r = Ray {
-            origin: point(0.0, 0.0, -5.0),
-            direction: vector(0.0, 0.0, 1.0),
-        };
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
         let shape = Sphere::default();
         let o = Object::new(Box::new(shape));
-        let xo = o.intersect(&r).unwrap();
+        let xo = o.intersect(&r);
         assert_eq!(xo[0].t, 4.0);
         assert_eq!(xo[1].t, 6.0);
     }
 
     #[test]
     fn ray_intersects_a_sphere_at_a_tangent() {
-        let r = Ray {
-            origin: point(0.0, 1.0, -5.0),
-            direction: vector(0.0, 0.0, 1.0),
-        };
+        let r = Ray::new(point(0.0, 1.0, -5.0), vector(0.0, 0.0, 1.0));
         let shape = Sphere::default();
         let o = Object::new(Box::new(shape));
-        let xo = o.intersect(&r).unwrap();
+        let xo = o.intersect(&r);
         assert_eq!(xo[0].t, 5.0);
         assert_eq!(xo[1].t, 5.0);
     }
 
     #[test]
     fn ray_misses_a_sphere() {
-        let r = Ray {
-            origin: point(0.0, 2.0, -5.0),
-            direction: vector(0.0, 0.0, 1.0),
-        };
+        let r = Ray::new(point(0.0, 2.0, -5.0), vector(0.0, 0.0, 1.0));
         let shape = Sphere::default();
         let o = Object::new(Box::new(shape));
         let xo = o.intersect(&r);
-        assert!(xo.is_none());
+        assert!(xo.is_empty());
     }
 
     #[test]
     fn ray_originates_inside_a_sphere() {
-        let r = Ray {
-            origin: point(0.0, 0.0, 0.0),
-            direction: vector(0.0, 0.0, 1.0),
-        };
+        let r = Ray::new(point(0.0, 0.0, 0.0), vector(0.0, 0.0, 1.0));
         let shape = Sphere::default();
         let o = Object::new(Box::new(shape));
-        let xo = o.intersect(&r).unwrap();
+        let xo = o.intersect(&r);
         assert_eq!(xo[0].t, -1.0);
         assert_eq!(xo[1].t, 1.0);
     }
 
     #[test]
     fn sphere_is_behind_a_ray() {
-        let r = Ray {
-            origin: point(0.0, 0.0, 5.0),
-            direction: vector(0.0, 0.0, 1.0),
-        };
+        let r = Ray::new(point(0.0, 0.0, 5.0), vector(0.0, 0.0, 1.0));
         let shape = Sphere::default();
         let o = Object::new(Box::new(shape));
-        let xo = o.intersect(&r).unwrap();
+        let xo = o.intersect(&r);
         assert_eq!(xo[0].t, -6.0);
         assert_eq!(xo[1].t, -4.0);
     }
 
     #[test]
     fn intersect_sets_the_object_on_the_intersection() {
-        let r = Ray {
-            origin: point(0.0, 0.0, -5.0),
-            direction: vector(0.0, 0.0, 1.0),
-        };
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
         let shape = Sphere::default();
         let o = Object::new(Box::new(shape));
-        let xo = o.intersect(&r).unwrap();
+        let xo = o.intersect(&r);
         assert_eq!(xo[0].object, &o);
         assert_eq!(xo[1].object, &o);
     }
@@ -165,16 +173,16 @@ mod tests {
         let shape = Sphere::default();
         let o = Object::new(Box::new(shape));
         let n = o.normal_at(&point(
-            (3.0 as f64).sqrt() / 3.0,
-            (3.0 as f64).sqrt() / 3.0,
-            (3.0 as f64).sqrt() / 3.0,
+            3.0_f64.sqrt() / 3.0,
+            3.0_f64.sqrt() / 3.0,
+            3.0_f64.sqrt() / 3.0,
         ));
         assert_eq!(
             n,
             vector(
-                (3.0 as f64).sqrt() / 3.0,
-                (3.0 as f64).sqrt() / 3.0,
-                (3.0 as f64).sqrt() / 3.0
+                3.0_f64.sqrt() / 3.0,
+                3.0_f64.sqrt() / 3.0,
+                3.0_f64.sqrt() / 3.0
             )
         );
     }
@@ -184,22 +192,19 @@ mod tests {
         let shape = Sphere::default();
         let o = Object::new(Box::new(shape));
         let n = o.normal_at(&point(
-            (3.0 as f64).sqrt() / 3.0,
-            (3.0 as f64).sqrt() / 3.0,
-            (3.0 as f64).sqrt() / 3.0,
+            3.0_f64.sqrt() / 3.0,
+            3.0_f64.sqrt() / 3.0,
+            3.0_f64.sqrt() / 3.0,
         ));
         assert_eq!(n, normalize(&n));
     }
 
     #[test]
     fn precomputing_the_state_of_an_intersection() {
-        let r = Ray {
-            origin: point(0.0, 0.0, -5.0),
-            direction: vector(0.0, 0.0, 1.0),
-        };
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
         let shape = Sphere::default();
         let o = Object::new(Box::new(shape));
-        let i = Intersection { t: 4.0, object: &o };
+        let i = Intersection::new(4.0, &o);
         let comps = i.prepare_computations(&r, &[&i]);
         assert_eq!(comps.object, &o);
         assert_eq!(comps.point, point(0.0, 0.0, -1.0));
@@ -209,26 +214,20 @@ mod tests {
 
     #[test]
     fn hit_when_an_intersection_occurs_on_the_outside() {
-        let r = Ray {
-            origin: point(0.0, 0.0, -5.0),
-            direction: vector(0.0, 0.0, 1.0),
-        };
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
         let shape = Sphere::default();
         let o = Object::new(Box::new(shape));
-        let i = Intersection { t: 4.0, object: &o };
+        let i = Intersection::new(4.0, &o);
         let comps = i.prepare_computations(&r, &[&i]);
         assert!(!comps.inside);
     }
 
     #[test]
     fn hit_when_an_intersection_occurs_on_the_inside() {
-        let r = Ray {
-            origin: point(0.0, 0.0, 0.0),
-            direction: vector(0.0, 0.0, 1.0),
-        };
+        let r = Ray::new(point(0.0, 0.0, 0.0), vector(0.0, 0.0, 1.0));
         let shape = Sphere::default();
         let o = Object::new(Box::new(shape));
-        let i = Intersection { t: 1.0, object: &o };
+        let i = Intersection::new(1.0, &o);
         let comps = i.prepare_computations(&r, &[&i]);
         assert_eq!(comps.point, point(0.0, 0.0, 1.0));
         assert_eq!(comps.eyev, vector(0.0, 0.0, -1.0));
@@ -238,24 +237,32 @@ mod tests {
 
     #[test]
     fn hit_should_offset_the_point() {
-        let r = Ray {
-            origin: point(0.0, 0.0, -5.0),
-            direction: vector(0.0, 0.0, 1.0),
-        };
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
         let shape = Sphere::default();
         let mut o = Object::new(Box::new(shape));
         o.transform = IDENTITY.translate(0.0, 0.0, 1.0);
-        let i = Intersection { t: 5.0, object: &o };
+        let i = Intersection::new(5.0, &o);
         let comps = i.prepare_computations(&r, &[&i]);
         assert!(comps.over_point.z < -0.0001 / 2.0);
         assert!(comps.point.z > comps.over_point.z);
     }
 
+    #[test]
+    fn under_point_is_offset_below_the_surface() {
+        let r = Ray::new(point(0.0, 0.0, -5.0), vector(0.0, 0.0, 1.0));
+        let mut o = Sphere::glass();
+        o.transform = IDENTITY.translate(0.0, 0.0, 1.0);
+        let i = Intersection::new(5.0, &o);
+        let comps = i.prepare_computations(&r, &[&i]);
+        assert!(comps.under_point.z > 0.0001 / 2.0);
+        assert!(comps.point.z < comps.under_point.z);
+    }
+
     #[test]
     fn helper_for_producing_a_sphere_with_a_glassy_material() {
         let s = Sphere::glass();
         assert_eq!(s.transform, IDENTITY);
-        assert_eq!(s.material.transparency, 1.0);
+        assert_eq!(s.material.transparency, Color::from(1.0));
         assert_eq!(s.material.refractive_index, 1.5);
     }
 }
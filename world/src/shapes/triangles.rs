@@ -0,0 +1,303 @@
+use crate::bounds::Aabb;
+use crate::intersections::Intersection;
+use crate::object::Object;
+use crate::shapes::Shape;
+use crate::INTERSECTION_EPSILON;
+use rays::Ray;
+use std::any::Any;
+use tuples::{cross, dot, normalize, Tuple};
+
+pub struct Triangle {
+    pub p1: Tuple,
+    pub p2: Tuple,
+    pub p3: Tuple,
+    pub e1: Tuple,
+    pub e2: Tuple,
+    pub normal: Tuple,
+}
+
+impl Triangle {
+    pub fn new(p1: Tuple, p2: Tuple, p3: Tuple) -> Triangle {
+        let e1 = p2 - p1;
+        let e2 = p3 - p1;
+        let normal = normalize(&cross(&e2, &e1));
+        Triangle {
+            p1,
+            p2,
+            p3,
+            e1,
+            e2,
+            normal,
+        }
+    }
+}
+
+impl Shape for Triangle {
+    fn intersects_at(&self, ray: &Ray) -> Vec<f64> {
+        intersect_triangle(&self.p1, &self.e1, &self.e2, ray)
+            .map(|(t, _, _)| vec![t])
+            .unwrap_or_default()
+    }
+
+    fn intersect_into<'a>(
+        &'a self,
+        object: &'a Object,
+        local_ray: &Ray,
+        result: &mut Vec<Intersection<'a>>,
+    ) {
+        if let Some((t, _, _)) = intersect_triangle(&self.p1, &self.e1, &self.e2, local_ray) {
+            result.push(Intersection::new(t, object));
+        }
+    }
+
+    fn local_normal_at(&self, _local_point: &Tuple) -> Tuple {
+        self.normal
+    }
+
+    fn bounds(&self) -> Aabb {
+        triangle_bounds(&self.p1, &self.p2, &self.p3)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct SmoothTriangle {
+    pub p1: Tuple,
+    pub p2: Tuple,
+    pub p3: Tuple,
+    pub n1: Tuple,
+    pub n2: Tuple,
+    pub n3: Tuple,
+    pub e1: Tuple,
+    pub e2: Tuple,
+}
+
+impl SmoothTriangle {
+    pub fn new(p1: Tuple, p2: Tuple, p3: Tuple, n1: Tuple, n2: Tuple, n3: Tuple) -> SmoothTriangle {
+        SmoothTriangle {
+            p1,
+            p2,
+            p3,
+            n1,
+            n2,
+            n3,
+            e1: p2 - p1,
+            e2: p3 - p1,
+        }
+    }
+}
+
+impl Shape for SmoothTriangle {
+    fn intersects_at(&self, ray: &Ray) -> Vec<f64> {
+        intersect_triangle(&self.p1, &self.e1, &self.e2, ray)
+            .map(|(t, _, _)| vec![t])
+            .unwrap_or_default()
+    }
+
+    fn intersect_into<'a>(
+        &'a self,
+        object: &'a Object,
+        local_ray: &Ray,
+        result: &mut Vec<Intersection<'a>>,
+    ) {
+        if let Some((t, u, v)) = intersect_triangle(&self.p1, &self.e1, &self.e2, local_ray) {
+            result.push(Intersection::with_uv(t, object, u, v));
+        }
+    }
+
+    fn local_normal_at(&self, local_point: &Tuple) -> Tuple {
+        self.normal_at_with_uv(local_point, None, None)
+    }
+
+    fn bounds(&self) -> Aabb {
+        triangle_bounds(&self.p1, &self.p2, &self.p3)
+    }
+
+    fn normal_at_with_uv(&self, _local_point: &Tuple, u: Option<f64>, v: Option<f64>) -> Tuple {
+        match (u, v) {
+            (Some(u), Some(v)) => self.n2 * u + self.n3 * v + self.n1 * (1.0 - u - v),
+            _ => normalize(&cross(&self.e2, &self.e1)),
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+fn triangle_bounds(p1: &Tuple, p2: &Tuple, p3: &Tuple) -> Aabb {
+    let mut bounds = Aabb::default();
+    bounds.add_point(p1);
+    bounds.add_point(p2);
+    bounds.add_point(p3);
+    bounds
+}
+
+fn intersect_triangle(p1: &Tuple, e1: &Tuple, e2: &Tuple, ray: &Ray) -> Option<(f64, f64, f64)> {
+    let dir_cross_e2 = cross(&ray.direction, e2);
+    let det = dot(e1, &dir_cross_e2);
+    if det.abs() < INTERSECTION_EPSILON {
+        return None;
+    }
+
+    let f = 1.0 / det;
+    let p1_to_origin = ray.origin - *p1;
+    let u = f * dot(&p1_to_origin, &dir_cross_e2);
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let origin_cross_e1 = cross(&p1_to_origin, e1);
+    let v = f * dot(&ray.direction, &origin_cross_e1);
+    if v < 0.0 || (u + v) > 1.0 {
+        return None;
+    }
+
+    Some((f * dot(e2, &origin_cross_e1), u, v))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::intersections::{hit, Intersection};
+    use crate::object::Object;
+    use crate::shapes::{
+        triangles::{SmoothTriangle, Triangle},
+        Shape,
+    };
+    use rays::Ray;
+    use tuples::{point, vector};
+
+    fn test_triangle() -> Triangle {
+        Triangle::new(
+            point(0.0, 1.0, 0.0),
+            point(-1.0, 0.0, 0.0),
+            point(1.0, 0.0, 0.0),
+        )
+    }
+
+    #[test]
+    fn constructing_a_triangle() {
+        let t = test_triangle();
+        assert_eq!(t.p1, point(0.0, 1.0, 0.0));
+        assert_eq!(t.p2, point(-1.0, 0.0, 0.0));
+        assert_eq!(t.p3, point(1.0, 0.0, 0.0));
+        assert_eq!(t.e1, vector(-1.0, -1.0, 0.0));
+        assert_eq!(t.e2, vector(1.0, -1.0, 0.0));
+        assert_eq!(t.normal, vector(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn finding_the_normal_on_a_triangle() {
+        let t = test_triangle();
+        let n1 = t.local_normal_at(&point(0.0, 0.5, 0.0));
+        let n2 = t.local_normal_at(&point(-0.5, 0.75, 0.0));
+        let n3 = t.local_normal_at(&point(0.5, 0.25, 0.0));
+        assert_eq!(n1, t.normal);
+        assert_eq!(n2, t.normal);
+        assert_eq!(n3, t.normal);
+    }
+
+    #[test]
+    fn intersecting_a_ray_parallel_to_the_triangle() {
+        let t = test_triangle();
+        let r = Ray::new(point(0.0, -1.0, -2.0), vector(0.0, 1.0, 0.0));
+        assert!(t.intersects_at(&r).is_empty());
+    }
+
+    #[test]
+    fn a_ray_misses_the_p1_p3_edge() {
+        let t = test_triangle();
+        let r = Ray::new(point(1.0, 1.0, -2.0), vector(0.0, 0.0, 1.0));
+        assert!(t.intersects_at(&r).is_empty());
+    }
+
+    #[test]
+    fn a_ray_misses_the_p1_p2_edge() {
+        let t = test_triangle();
+        let r = Ray::new(point(-1.0, 1.0, -2.0), vector(0.0, 0.0, 1.0));
+        assert!(t.intersects_at(&r).is_empty());
+    }
+
+    #[test]
+    fn a_ray_misses_the_p2_p3_edge() {
+        let t = test_triangle();
+        let r = Ray::new(point(0.0, -1.0, -2.0), vector(0.0, 0.0, 1.0));
+        assert!(t.intersects_at(&r).is_empty());
+    }
+
+    #[test]
+    fn a_ray_strikes_a_triangle() {
+        let t = test_triangle();
+        let r = Ray::new(point(0.0, 0.5, -2.0), vector(0.0, 0.0, 1.0));
+        let xs = t.intersects_at(&r);
+        assert_eq!(xs[0], 2.0);
+    }
+
+    fn test_smooth_triangle() -> SmoothTriangle {
+        SmoothTriangle::new(
+            point(0.0, 1.0, 0.0),
+            point(-1.0, 0.0, 0.0),
+            point(1.0, 0.0, 0.0),
+            vector(0.0, 1.0, 0.0),
+            vector(-1.0, 0.0, 0.0),
+            vector(1.0, 0.0, 0.0),
+        )
+    }
+
+    #[test]
+    fn constructing_a_smooth_triangle() {
+        let tri = test_smooth_triangle();
+        assert_eq!(tri.p1, point(0.0, 1.0, 0.0));
+        assert_eq!(tri.p2, point(-1.0, 0.0, 0.0));
+        assert_eq!(tri.p3, point(1.0, 0.0, 0.0));
+        assert_eq!(tri.n1, vector(0.0, 1.0, 0.0));
+        assert_eq!(tri.n2, vector(-1.0, 0.0, 0.0));
+        assert_eq!(tri.n3, vector(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn an_intersection_can_encapsulate_u_and_v() {
+        let s = Object::new(Box::new(test_triangle()));
+        let i = Intersection::with_uv(3.5, &s, 0.2, 0.4);
+        assert_eq!(i.u, Some(0.2));
+        assert_eq!(i.v, Some(0.4));
+    }
+
+    #[test]
+    fn an_intersection_with_a_smooth_triangle_stores_u_and_v() {
+        let tri = Object::new(Box::new(test_smooth_triangle()));
+        let r = Ray::new(point(-0.2, 0.3, -2.0), vector(0.0, 0.0, 1.0));
+        let xs = tri.intersect(&r);
+        assert!((xs[0].u.unwrap() - 0.45).abs() < 0.0001);
+        assert!((xs[0].v.unwrap() - 0.25).abs() < 0.0001);
+    }
+
+    #[test]
+    fn a_smooth_triangle_uses_u_and_v_to_interpolate_the_normal() {
+        let tri = Object::new(Box::new(test_smooth_triangle()));
+        let i = Intersection::with_uv(1.0, &tri, 0.45, 0.25);
+        let n = tri.normal_at_with_uv(&point(0.0, 0.0, 0.0), i.u, i.v);
+        assert_eq!(n, vector(-0.5547, 0.83205, 0.0));
+    }
+
+    #[test]
+    fn preparing_the_normal_on_a_smooth_triangle() {
+        let tri = Object::new(Box::new(test_smooth_triangle()));
+        let i = Intersection::with_uv(1.0, &tri, 0.45, 0.25);
+        let r = Ray::new(point(-0.2, 0.3, -2.0), vector(0.0, 0.0, 1.0));
+        let comps = i.prepare_computations(&r, &[&i]);
+        assert_eq!(comps.normalv, vector(-0.5547, 0.83205, 0.0));
+    }
+
+    #[test]
+    fn the_normal_at_the_centroid_of_a_smooth_triangle() {
+        let tri = Object::new(Box::new(test_smooth_triangle()));
+        let r = Ray::new(point(0.0, 1.0 / 3.0, -2.0), vector(0.0, 0.0, 1.0));
+        let xs = tri.intersect(&r);
+        let i = hit(&xs).unwrap();
+        let n = tri.normal_at_with_uv(&r.position(i.t), i.u, i.v);
+        assert_eq!(n, vector(0.0, 1.0, 0.0));
+    }
+}